@@ -39,6 +39,22 @@ pub use ls_types::{
     Range,
     RangedFilePosition,
     Symbol,
+    SymbolKind,
+    WorkspaceSymbol,
+    FoldingRange,
+    FoldingRangeKind,
+    DocumentHighlight,
+    DocumentHighlightKind,
+    SemanticToken,
+    SemanticTokenType,
+    CodeAction,
+    Diagnostic,
+    DiagnosticSeverity,
+    DiagnosticRelatedInformation,
+    CompletionItem,
+    CompletionItemKind,
+    TextEdit,
+    WorkspaceEdit,
 };
 
 pub type TaskId = usize;
@@ -48,6 +64,10 @@ pub enum QueryRequest {
     Initialize {
         task_id: TaskId,
         workspace_root_url: Option<Url>,
+
+        /// How many levels of children to include below each top-level
+        /// document symbol, if the client asked for a specific depth
+        document_symbol_depth: Option<usize>,
     },
     HoverAtPosition {
         task_id: TaskId,
@@ -67,10 +87,69 @@ pub enum QueryRequest {
         file_url: Url,
         changes: Vec<(LsRange, String)>,
     },
+    FileClosed {
+        file_url: Url,
+    },
     FileSymbols {
         task_id: TaskId,
         file_url: Url,
     },
+    WorkspaceSymbols {
+        task_id: TaskId,
+        query: String,
+    },
+    FoldingRanges {
+        task_id: TaskId,
+        file_url: Url,
+    },
+    DocumentHighlights {
+        task_id: TaskId,
+        file_url: Url,
+        file_pos: LsPos,
+    },
+    CodeActions {
+        task_id: TaskId,
+        file_url: Url,
+        range: LsRange,
+    },
+    SemanticTokens {
+        task_id: TaskId,
+        file_url: Url,
+
+        /// The result ID of the tokens the client already has, if it wants
+        /// only the changes since then
+        previous_result_id: Option<String>,
+    },
+    Completion {
+        task_id: TaskId,
+        file_url: Url,
+        file_pos: LsPos,
+    },
+    References {
+        task_id: TaskId,
+        file_url: Url,
+        file_pos: LsPos,
+        include_declarations: bool,
+    },
+    PrepareRename {
+        task_id: TaskId,
+        file_url: Url,
+        file_pos: LsPos,
+    },
+    Rename {
+        task_id: TaskId,
+        file_url: Url,
+        file_pos: LsPos,
+        new_name: String,
+    },
+    Format {
+        task_id: TaskId,
+        file_url: Url,
+        use_spaces: bool,
+
+        /// Only format the lines in this range, if given
+        range: Option<LsRange>,
+    },
 }
 
 impl QueryRequest {
@@ -79,10 +158,21 @@ impl QueryRequest {
             QueryRequest::Initialize { .. }
             | QueryRequest::FileOpened { .. }
             | QueryRequest::FileChanged { .. }
+            | QueryRequest::FileClosed { .. }
                 => true,
             QueryRequest::HoverAtPosition { .. }
             | QueryRequest::GoToDefinition { .. }
             | QueryRequest::FileSymbols { .. }
+            | QueryRequest::WorkspaceSymbols { .. }
+            | QueryRequest::FoldingRanges { .. }
+            | QueryRequest::DocumentHighlights { .. }
+            | QueryRequest::SemanticTokens { .. }
+            | QueryRequest::CodeActions { .. }
+            | QueryRequest::Completion { .. }
+            | QueryRequest::References { .. }
+            | QueryRequest::PrepareRename { .. }
+            | QueryRequest::Rename { .. }
+            | QueryRequest::Format { .. }
                 => false,
         }
     }
@@ -107,6 +197,65 @@ pub enum QueryResponse {
         task_id: TaskId,
         symbols: Vec<Symbol>,
     },
+    WorkspaceSymbols {
+        task_id: TaskId,
+        symbols: Vec<WorkspaceSymbol>,
+    },
+    FoldingRanges {
+        task_id: TaskId,
+        ranges: Vec<FoldingRange>,
+    },
+    DocumentHighlights {
+        task_id: TaskId,
+        highlights: Vec<DocumentHighlight>,
+    },
+    CodeActions {
+        task_id: TaskId,
+        actions: Vec<CodeAction>,
+    },
+    SemanticTokens {
+        task_id: TaskId,
+        file_url: Url,
+        tokens: Vec<SemanticToken>,
+        previous_result_id: Option<String>,
+    },
+    Diagnostics {
+        file_url: Url,
+        diagnostics: Vec<Diagnostic>,
+    },
+
+    /// The client closed `file_url`, so anything remembered about what was
+    /// sent for it can be forgotten
+    FileClosed {
+        file_url: Url,
+    },
+    Completions {
+        task_id: TaskId,
+        items: Vec<CompletionItem>,
+    },
+    References {
+        task_id: TaskId,
+        ranged_file_positions: Vec<RangedFilePosition>,
+    },
+    RenameRange {
+        task_id: TaskId,
+        range: Range<Position>,
+        placeholder: String,
+    },
+    WorkspaceEdit {
+        task_id: TaskId,
+        edit: WorkspaceEdit,
+    },
+    TextEdits {
+        task_id: TaskId,
+        edits: Vec<TextEdit>,
+    },
+
+    /// The request could not be fulfilled for a reason the user should see
+    Error {
+        task_id: TaskId,
+        message: String,
+    },
 }
 
 /// An actor in the task system.  This gives us a uniform way to
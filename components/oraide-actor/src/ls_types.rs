@@ -121,6 +121,7 @@ impl Into<languageserver_types::Location> for RangedFilePosition {
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
 pub struct Symbol {
     pub name: String,
+    pub kind: SymbolKind,
     pub detail: Option<String>,
     pub range: Range<Position>,
     pub children: Option<Vec<Self>>,
@@ -131,7 +132,7 @@ impl From<Symbol> for languageserver_types::DocumentSymbol {
         Self {
             name: sym.name,
             detail: sym.detail,
-            kind: languageserver_types::SymbolKind::Object,
+            kind: sym.kind.into(),
             range: sym.range.clone().into(),
             selection_range: sym.range.into(),
             deprecated: None,
@@ -142,4 +143,412 @@ impl From<Symbol> for languageserver_types::DocumentSymbol {
             )
         }
     }
+}
+
+/// What a [`Symbol`] or [`WorkspaceSymbol`] refers to
+///
+/// [`Symbol`]: struct.Symbol.html
+/// [`WorkspaceSymbol`]: struct.WorkspaceSymbol.html
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash)]
+pub enum SymbolKind {
+    Actor,
+
+    /// A definition that is only meant to be inherited, such as `^Infantry`
+    Abstract,
+    Weapon,
+    Sequence,
+    ChromeWidget,
+
+    /// A trait of an actor, such as `Health`
+    Trait,
+
+    /// A named instance of a trait, such as `Armament@PRIMARY`
+    TraitInstance,
+
+    /// A property of a trait (or of anything else that is not a definition)
+    Property,
+
+    /// The removal of an inherited node, such as `-Selectable`
+    Removal,
+}
+
+impl From<SymbolKind> for languageserver_types::SymbolKind {
+    fn from(kind: SymbolKind) -> Self {
+        match kind {
+            SymbolKind::Actor => languageserver_types::SymbolKind::Class,
+            SymbolKind::Abstract => languageserver_types::SymbolKind::Interface,
+            SymbolKind::Weapon => languageserver_types::SymbolKind::Function,
+            SymbolKind::Sequence => languageserver_types::SymbolKind::Array,
+            SymbolKind::ChromeWidget => languageserver_types::SymbolKind::Object,
+            SymbolKind::Trait => languageserver_types::SymbolKind::Module,
+            SymbolKind::TraitInstance => languageserver_types::SymbolKind::Namespace,
+            SymbolKind::Property => languageserver_types::SymbolKind::Property,
+            SymbolKind::Removal => languageserver_types::SymbolKind::Event,
+        }
+    }
+}
+
+/// `SymbolInformation` in https://microsoft.github.io/language-server-protocol/specification#workspace_symbol
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct WorkspaceSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub location: RangedFilePosition,
+
+    /// The IDs of the games that use this symbol, separated by `, `
+    pub container_name: Option<String>,
+}
+
+impl From<WorkspaceSymbol> for languageserver_types::SymbolInformation {
+    fn from(sym: WorkspaceSymbol) -> Self {
+        Self {
+            name: sym.name,
+            kind: sym.kind.into(),
+            deprecated: None,
+            location: sym.location.into(),
+            container_name: sym.container_name,
+        }
+    }
+}
+
+/// The severity of a [`Diagnostic`]
+///
+/// [`Diagnostic`]: struct.Diagnostic.html
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash)]
+pub enum DiagnosticSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl From<DiagnosticSeverity> for languageserver_types::DiagnosticSeverity {
+    fn from(severity: DiagnosticSeverity) -> Self {
+        match severity {
+            DiagnosticSeverity::Error => languageserver_types::DiagnosticSeverity::Error,
+            DiagnosticSeverity::Warning => languageserver_types::DiagnosticSeverity::Warning,
+            DiagnosticSeverity::Information => languageserver_types::DiagnosticSeverity::Information,
+            DiagnosticSeverity::Hint => languageserver_types::DiagnosticSeverity::Hint,
+        }
+    }
+}
+
+/// A location, and message, related to a [`Diagnostic`]
+///
+/// [`Diagnostic`]: struct.Diagnostic.html
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct DiagnosticRelatedInformation {
+    pub location: RangedFilePosition,
+    pub message: String,
+}
+
+/// `Diagnostic` in https://microsoft.github.io/language-server-protocol/specification#diagnostic
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct Diagnostic {
+    pub range: Range<Position>,
+    pub severity: DiagnosticSeverity,
+    pub code: Option<String>,
+    pub message: String,
+    pub related_information: Vec<DiagnosticRelatedInformation>,
+}
+
+impl From<Diagnostic> for languageserver_types::Diagnostic {
+    fn from(diag: Diagnostic) -> Self {
+        let related_information = if diag.related_information.is_empty() {
+            None
+        } else {
+            let related_information = diag.related_information.into_iter()
+                .map(|info| languageserver_types::DiagnosticRelatedInformation {
+                    location: info.location.into(),
+                    message: info.message,
+                })
+                .collect();
+
+            Some(related_information)
+        };
+
+        Self::new(
+            diag.range.into(),
+            Some(diag.severity.into()),
+            diag.code.map(languageserver_types::NumberOrString::String),
+            Some("oraide".into()),
+            diag.message,
+            related_information,
+        )
+    }
+}
+
+/// What a [`CompletionItem`] refers to
+///
+/// [`CompletionItem`]: struct.CompletionItem.html
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash)]
+pub enum CompletionItemKind {
+    /// A top-level definition, such as `E1` or `^Infantry`
+    Actor,
+    Trait,
+    Property,
+
+    /// One of the valid values of a property
+    Value,
+}
+
+impl From<CompletionItemKind> for languageserver_types::CompletionItemKind {
+    fn from(kind: CompletionItemKind) -> Self {
+        match kind {
+            CompletionItemKind::Actor => languageserver_types::CompletionItemKind::Class,
+            CompletionItemKind::Trait => languageserver_types::CompletionItemKind::Module,
+            CompletionItemKind::Property => languageserver_types::CompletionItemKind::Property,
+            CompletionItemKind::Value => languageserver_types::CompletionItemKind::EnumMember,
+        }
+    }
+}
+
+/// `CompletionItem` in https://microsoft.github.io/language-server-protocol/specification#textDocument_completion
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct CompletionItem {
+    pub label: String,
+    pub kind: CompletionItemKind,
+    pub detail: Option<String>,
+
+    /// Markdown documentation for this item
+    pub documentation: Option<String>,
+}
+
+impl From<CompletionItem> for languageserver_types::CompletionItem {
+    fn from(item: CompletionItem) -> Self {
+        Self {
+            label: item.label,
+            kind: Some(item.kind.into()),
+            detail: item.detail,
+            documentation: item.documentation.map(|value|
+                languageserver_types::Documentation::MarkupContent(
+                    languageserver_types::MarkupContent {
+                        kind: languageserver_types::MarkupKind::Markdown,
+                        value,
+                    }
+                )
+            ),
+            ..Default::default()
+        }
+    }
+}
+
+/// `TextEdit` in https://microsoft.github.io/language-server-protocol/specification#textedit
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct TextEdit {
+    pub range: Range<Position>,
+    pub new_text: String,
+}
+
+impl From<TextEdit> for languageserver_types::TextEdit {
+    fn from(edit: TextEdit) -> Self {
+        Self::new(edit.range.into(), edit.new_text)
+    }
+}
+
+/// `WorkspaceEdit` in https://microsoft.github.io/language-server-protocol/specification#workspaceedit
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash, Default)]
+pub struct WorkspaceEdit {
+    /// The edits to make to each file, with at most one entry per file
+    pub changes: Vec<(Url, Vec<TextEdit>)>,
+}
+
+impl From<WorkspaceEdit> for languageserver_types::WorkspaceEdit {
+    fn from(edit: WorkspaceEdit) -> Self {
+        let changes = edit.changes.into_iter()
+            .map(|(file_url, edits)| (file_url, edits.into_iter().map(Into::into).collect()))
+            .collect();
+
+        Self::new(changes)
+    }
+}
+
+/// A quick fix, in https://microsoft.github.io/language-server-protocol/specification#textDocument_codeAction
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct CodeAction {
+    pub title: String,
+    pub edit: WorkspaceEdit,
+}
+
+impl From<CodeAction> for languageserver_types::CodeAction {
+    fn from(action: CodeAction) -> Self {
+        Self {
+            title: action.title,
+            kind: Some("quickfix".into()),
+            diagnostics: None,
+            edit: Some(action.edit.into()),
+            command: None,
+        }
+    }
+}
+
+/// What a [`FoldingRange`] folds
+///
+/// [`FoldingRange`]: struct.FoldingRange.html
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash)]
+pub enum FoldingRangeKind {
+    /// A node and all of its descendants
+    Region,
+
+    /// Consecutive comment-only lines
+    Comment,
+}
+
+impl From<FoldingRangeKind> for languageserver_types::FoldingRangeKind {
+    fn from(kind: FoldingRangeKind) -> Self {
+        match kind {
+            FoldingRangeKind::Region => languageserver_types::FoldingRangeKind::Region,
+            FoldingRangeKind::Comment => languageserver_types::FoldingRangeKind::Comment,
+        }
+    }
+}
+
+/// `FoldingRange` in https://microsoft.github.io/language-server-protocol/specification#textDocument_foldingRange
+///
+/// Whole lines are folded, so no character indices are given.
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct FoldingRange {
+    /// 0-based
+    pub start_line_idx: usize,
+
+    /// 0-based, inclusive
+    pub end_line_idx: usize,
+
+    pub kind: FoldingRangeKind,
+}
+
+impl From<FoldingRange> for languageserver_types::FoldingRange {
+    fn from(range: FoldingRange) -> Self {
+        Self {
+            start_line: range.start_line_idx as u64,
+            start_character: None,
+            end_line: range.end_line_idx as u64,
+            end_character: None,
+            kind: Some(range.kind.into()),
+        }
+    }
+}
+
+/// Whether a [`DocumentHighlight`] is a definition or a reference
+///
+/// [`DocumentHighlight`]: struct.DocumentHighlight.html
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash)]
+pub enum DocumentHighlightKind {
+    Definition,
+    Reference,
+}
+
+impl From<DocumentHighlightKind> for languageserver_types::DocumentHighlightKind {
+    fn from(kind: DocumentHighlightKind) -> Self {
+        match kind {
+            DocumentHighlightKind::Definition => languageserver_types::DocumentHighlightKind::Write,
+            DocumentHighlightKind::Reference => languageserver_types::DocumentHighlightKind::Read,
+        }
+    }
+}
+
+/// `DocumentHighlight` in https://microsoft.github.io/language-server-protocol/specification#textDocument_documentHighlight
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct DocumentHighlight {
+    pub range: Range<Position>,
+    pub kind: DocumentHighlightKind,
+}
+
+impl From<DocumentHighlight> for languageserver_types::DocumentHighlight {
+    fn from(highlight: DocumentHighlight) -> Self {
+        Self {
+            range: highlight.range.into(),
+            kind: Some(highlight.kind.into()),
+        }
+    }
+}
+
+/// What a [`SemanticToken`] is
+///
+/// [`SemanticToken`]: struct.SemanticToken.html
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash)]
+pub enum SemanticTokenType {
+    /// The name of a definition that is not abstract, such as `E1`
+    Actor,
+
+    /// The name of a definition that is only meant to be inherited, such as `^Infantry`
+    Abstract,
+
+    /// The name of a weapon, such as `M1Carbine`
+    Weapon,
+
+    /// The name of a definition in a sequences file, such as `e1`
+    Sequence,
+
+    /// The name of a definition in a chrome or chrome layout file
+    ChromeWidget,
+
+    /// The name of a trait, such as `Armament` in `Armament@PRIMARY`
+    Trait,
+
+    /// The `@suffix` of a trait instance, such as `@PRIMARY` in `Armament@PRIMARY`
+    TraitSuffix,
+
+    /// The name of a property of a trait
+    Property,
+
+    /// The `-` that removes an inherited node
+    Removal,
+
+    /// `True`, `Yes`, `False` or `No`
+    Boolean,
+    Number,
+
+    /// A trait or property that is not in the type-data
+    Unknown,
+}
+
+impl SemanticTokenType {
+    /// Every token type, in the order of the legend sent to a language client
+    pub const ALL: [SemanticTokenType; 12] = [
+        SemanticTokenType::Actor,
+        SemanticTokenType::Abstract,
+        SemanticTokenType::Trait,
+        SemanticTokenType::TraitSuffix,
+        SemanticTokenType::Property,
+        SemanticTokenType::Removal,
+        SemanticTokenType::Boolean,
+        SemanticTokenType::Number,
+        SemanticTokenType::Unknown,
+        SemanticTokenType::Weapon,
+        SemanticTokenType::Sequence,
+        SemanticTokenType::ChromeWidget,
+    ];
+
+    /// The name of this token type in the legend sent to a language client
+    ///
+    /// The standard LSP token types are used where one fits.
+    pub fn legend_name(self) -> &'static str {
+        match self {
+            SemanticTokenType::Actor => "class",
+            SemanticTokenType::Abstract => "interface",
+            SemanticTokenType::Weapon => "function",
+            SemanticTokenType::Sequence => "enum",
+            SemanticTokenType::ChromeWidget => "struct",
+            SemanticTokenType::Trait => "type",
+            SemanticTokenType::TraitSuffix => "enumMember",
+            SemanticTokenType::Property => "property",
+            SemanticTokenType::Removal => "operator",
+            SemanticTokenType::Boolean => "keyword",
+            SemanticTokenType::Number => "number",
+            SemanticTokenType::Unknown => "unknown",
+        }
+    }
+}
+
+/// A classified range of a document, which never spans more than one line
+///
+/// See https://microsoft.github.io/language-server-protocol/specification#textDocument_semanticTokens
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct SemanticToken {
+    pub range: Range<Position>,
+    pub token_type: SemanticTokenType,
+
+    /// Whether this token is where its name is defined
+    pub is_declaration: bool,
 }
\ No newline at end of file
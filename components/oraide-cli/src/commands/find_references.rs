@@ -0,0 +1,74 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+use std::{
+    path::{
+        PathBuf,
+    },
+};
+
+use oraide_parser_miniyaml::{
+    FilesCtx as _,
+    TextFilesCtx as _,
+};
+
+use oraide_sdk::{
+    SdkCtx as _,
+    SdkCtxExt as _,
+};
+
+use oraide_language_server::{
+    LanguageServerCtx as _,
+};
+
+use oraide_query_system::OraideDatabase;
+
+pub(crate) struct FindReferences {
+    name_to_find: String,
+    db: OraideDatabase,
+}
+
+impl FindReferences {
+    pub(crate) fn new(name_to_find: String, project_root_dir: PathBuf) -> Self {
+        let mut db = OraideDatabase::default();
+        db.set_workspace_root(project_root_dir.clone().into());
+
+        // Files that no game's manifest lists can still refer to definitions
+        db.load_all_game_files();
+
+        let dir_walker = walkdir::WalkDir::new(&project_root_dir)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.metadata().map(|md| md.is_file()).unwrap_or(false))
+            .filter(|entry| entry.path().extension() == Some(std::ffi::OsString::from("yaml".to_string()).as_ref()))
+            ;
+
+        for entry in dir_walker {
+            db.load_disk_file(entry.into_path());
+        }
+
+        Self {
+            name_to_find,
+            db,
+        }
+    }
+
+    pub(crate) fn run(&self) {
+        for span in self.db.references_to_name(self.name_to_find.clone()) {
+            let file_id = span.source();
+            let file_name = self.db.file_path(file_id).unwrap();
+            let loc = self.db.convert_byte_index_to_location(file_id, span.start()).unwrap();
+            println!("{}:{}", file_name, loc);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,99 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+use std::{
+    fs,
+    path::{
+        PathBuf,
+    },
+};
+
+use oraide_span::FileId;
+
+use oraide_parser_miniyaml::{
+    FormatOptions,
+    IndentStyle,
+    FilesCtx as _,
+    TextFilesCtx as _,
+    ParserCtx as _,
+};
+
+use oraide_query_system::OraideDatabase;
+
+pub(crate) struct Fmt {
+    file_ids: Vec<FileId>,
+
+    /// Only report unformatted files instead of formatting them
+    check: bool,
+    db: OraideDatabase,
+}
+
+impl Fmt {
+    /// Create a `Fmt` for `paths`, which may be a mix of files and
+    /// directories (which are searched recursively for `.yaml` files)
+    pub(crate) fn new(paths: Vec<PathBuf>, check: bool) -> Result<Self, String> {
+        let mut db = OraideDatabase::default();
+        let file_ids = crate::add_files(&mut db, paths)?;
+
+        Ok(Self {
+            file_ids,
+            check,
+            db,
+        })
+    }
+
+    /// Format each file in place, keeping the indentation style each file
+    /// already uses, or only report them if checking
+    ///
+    /// # Returns
+    /// The number of files that could not be formatted, plus the number of
+    /// unformatted files if checking
+    pub(crate) fn run(&self) -> usize {
+        let mut problem_count = 0;
+
+        for file_id in self.file_ids.iter().cloned() {
+            let file_path = self.db.file_path(file_id).unwrap();
+            let file_text = self.db.file_text(file_id).unwrap();
+
+            let indent_style = self.db.indent_style_of_file(file_id).unwrap_or(IndentStyle::Tabs);
+            let formatted_text = match self.db.formatted_file_text(file_id, FormatOptions::new(indent_style)) {
+                Some(text) => text,
+                _ => {
+                    eprintln!("{}: has errors so can not be formatted (see `ora lint`)", file_path);
+                    problem_count += 1;
+                    continue;
+                },
+            };
+
+            if formatted_text == file_text {
+                continue;
+            }
+
+            if self.check {
+                println!("{}: is not formatted", file_path);
+                problem_count += 1;
+                continue;
+            }
+
+            match fs::write(&file_path, formatted_text) {
+                Ok(_) => println!("{}: formatted", file_path),
+                Err(e) => {
+                    eprintln!("{}: failed to write: {}", file_path, e);
+                    problem_count += 1;
+                },
+            }
+        }
+
+        problem_count
+    }
+}
\ No newline at end of file
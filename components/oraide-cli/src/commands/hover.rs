@@ -26,6 +26,11 @@ use oraide_query_system::{
     OraideDatabase,
 };
 
+use oraide_sdk::{
+    SdkCtx as _,
+    SdkCtxExt as _,
+};
+
 use oraide_language_server::{
     LanguageServerCtx as _,
 };
@@ -47,8 +52,14 @@ impl Hover {
         let mut db = OraideDatabase::default();
 
         db.set_workspace_root(root.clone().into());
+        // Actors can inherit from definitions in any of their game's files
+        db.load_all_game_files();
+
         let file_path = root.join(rel_file_path);
-        let file_id = crate::add_file(&mut db, &file_path)?;
+        let file_id = match db.file_id_of_disk_path(file_path.clone()) {
+            Some(id) => id,
+            _ => crate::add_file(&mut db, &file_path)?,
+        };
 
         Ok(Self {
             line_idx,
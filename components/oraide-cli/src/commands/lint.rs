@@ -0,0 +1,620 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+use std::{
+    fmt,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use language_reporting as reporting;
+
+use oraide_span::FileId;
+
+use oraide_parser_miniyaml::{
+    Diagnostic,
+    Label,
+    LabelStyle,
+    Severity,
+    FilesCtx as _,
+    TextFilesCtx as _,
+};
+
+use oraide_language_server::{
+    LanguageServerCtx as _,
+};
+
+use oraide_sdk::{
+    SdkCtx as _,
+    SdkCtxExt as _,
+};
+
+use oraide_query_system::OraideDatabase;
+
+/// How the results of linting should be presented
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum LintOutputFormat {
+    /// Annotated snippets, intended to be read by a human
+    Human,
+
+    /// A single JSON array, intended to be consumed by other tools
+    Json,
+}
+
+impl std::str::FromStr for LintOutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(LintOutputFormat::Human),
+            "json" => Ok(LintOutputFormat::Json),
+            other => Err(format!("Unknown lint output format `{}`", other)),
+        }
+    }
+}
+
+pub(crate) struct Lint {
+    file_ids: Vec<FileId>,
+    format: LintOutputFormat,
+    db: OraideDatabase,
+}
+
+/// Where in its file a [`Label`] starts
+///
+/// [`Label`]: ../../oraide_parser_miniyaml/struct.Label.html
+struct LabelLocation {
+    file_path: String,
+
+    /// 1-based
+    line_number: usize,
+
+    /// 1-based, counted in `char`s
+    column_number: usize,
+}
+
+/// A span of text in a file in the database, in the form `language_reporting`
+/// expects
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct DbSpan {
+    file_id: FileId,
+    start: usize,
+    end_exclusive: usize,
+}
+
+impl reporting::ReportingSpan for DbSpan {
+    fn with_start(&self, start: usize) -> Self {
+        Self { start, ..*self }
+    }
+
+    fn with_end(&self, end: usize) -> Self {
+        Self { end_exclusive: end, ..*self }
+    }
+
+    fn start(&self) -> usize {
+        self.start
+    }
+
+    fn end(&self) -> usize {
+        self.end_exclusive
+    }
+}
+
+/// Gives `language_reporting` access to the paths and text of the files in
+/// a database
+#[derive(Copy, Clone)]
+struct DbFiles<'db> {
+    db: &'db OraideDatabase,
+}
+
+impl fmt::Debug for DbFiles<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("DbFiles")
+    }
+}
+
+impl reporting::ReportingFiles for DbFiles<'_> {
+    type Span = DbSpan;
+    type FileId = FileId;
+
+    fn byte_span(&self, file_id: FileId, from_index: usize, to_index: usize) -> Option<DbSpan> {
+        Some(DbSpan {
+            file_id,
+            start: from_index,
+            end_exclusive: to_index,
+        })
+    }
+
+    fn file_id(&self, span: DbSpan) -> FileId {
+        span.file_id
+    }
+
+    fn file_name(&self, file_id: FileId) -> reporting::FileName {
+        let file_path = self.db.file_path(file_id).unwrap_or_default();
+        reporting::FileName::Real(file_path.into())
+    }
+
+    fn byte_index(&self, file_id: FileId, line: usize, column: usize) -> Option<usize> {
+        let line_start_offsets = self.db.line_start_offsets(file_id)?;
+        line_start_offsets.get(line).map(|line_start| line_start + column)
+    }
+
+    fn location(&self, file_id: FileId, byte_index: usize) -> Option<reporting::Location> {
+        let line_start_offsets = self.db.line_start_offsets(file_id)?;
+        let line = line_idx_of(&line_start_offsets, byte_index);
+
+        Some(reporting::Location {
+            line,
+            column: byte_index - line_start_offsets[line],
+        })
+    }
+
+    fn line_span(&self, file_id: FileId, lineno: usize) -> Option<DbSpan> {
+        let file_text = self.db.file_text(file_id)?;
+        let line_start = *self.db.line_start_offsets(file_id)?.get(lineno)?;
+
+        Some(DbSpan {
+            file_id,
+            start: line_start,
+            end_exclusive: line_end_of(&file_text, line_start),
+        })
+    }
+
+    fn source(&self, span: DbSpan) -> Option<String> {
+        let file_text = self.db.file_text(span.file_id)?;
+        file_text.get(span.start..span.end_exclusive).map(str::to_owned)
+    }
+}
+
+/// Find the workspace that `path` is part of, which is the nearest directory
+/// containing `path` (or `path` itself) that has a `mods/` directory
+fn find_root_dir(path: &Path) -> Option<PathBuf> {
+    let path = path.canonicalize().ok()?;
+
+    path.ancestors()
+        .find(|dir| dir.join("mods").is_dir())
+        .map(Path::to_path_buf)
+}
+
+/// The index of the line that `byte_index` is on
+fn line_idx_of(line_start_offsets: &[usize], byte_index: usize) -> usize {
+    match line_start_offsets.binary_search(&byte_index) {
+        Ok(idx) => idx,
+        Err(next_line_idx) => next_line_idx.saturating_sub(1),
+    }
+}
+
+/// The (exclusive) end of the line starting at `line_start`, not including
+/// its line terminator
+fn line_end_of(file_text: &str, line_start: usize) -> usize {
+    file_text[line_start..]
+        .find(|ch| ch == '\r' || ch == '\n')
+        .map(|idx| line_start + idx)
+        .unwrap_or_else(|| file_text.len())
+}
+
+fn reporting_severity(severity: Severity) -> reporting::Severity {
+    match severity {
+        Severity::Note => reporting::Severity::Note,
+        Severity::Warning => reporting::Severity::Warning,
+        Severity::Error => reporting::Severity::Error,
+        Severity::Bug => reporting::Severity::Bug,
+    }
+}
+
+impl Lint {
+    /// Create a `Lint` for `paths`, which may be a mix of files and
+    /// directories (which are searched recursively for `.yaml` files)
+    ///
+    /// Traits, inheritance and maps can only be checked in the context of a
+    /// game, so the games of `opt_root_dir` (or, if it is `None`, of the
+    /// workspace that contains the first of `paths`, see [`find_root_dir`])
+    /// are loaded.  Without a workspace, files are checked on their own.
+    ///
+    /// [`find_root_dir`]: fn.find_root_dir.html
+    pub(crate) fn new(
+        paths: Vec<PathBuf>,
+        opt_root_dir: Option<PathBuf>,
+        format: LintOutputFormat,
+    ) -> Result<Self, String> {
+        let mut db = OraideDatabase::default();
+
+        let opt_root_dir = opt_root_dir.or_else(|| paths.iter().filter_map(|path| find_root_dir(path)).next());
+
+        let file_ids = match opt_root_dir {
+            Some(root_dir) => {
+                let root_dir = root_dir.canonicalize()
+                    .map_err(|e| format!("Error opening `{}`: {}", root_dir.display(), e))?;
+
+                db.set_workspace_root(Some(root_dir));
+                db.load_all_game_files();
+
+                // Files are tracked by the paths that games resolve them to,
+                // so a linted file that a game lists is only loaded once
+                let mut file_ids = vec![];
+
+                for path in crate::yaml_file_paths(paths) {
+                    let file_id = path.canonicalize().ok()
+                        .and_then(|path| db.load_disk_file(path))
+                        .ok_or_else(|| format!("Error reading `{}`", path.display()))?;
+
+                    file_ids.push(file_id);
+                }
+
+                file_ids
+            },
+            _ => {
+                db.set_workspace_root(None);
+                crate::add_files(&mut db, paths)?
+            },
+        };
+
+        Ok(Self {
+            file_ids,
+            format,
+            db,
+        })
+    }
+
+    /// Print all diagnostics found in the linted files
+    ///
+    /// # Returns
+    /// The number of error-level (or worse) diagnostics found
+    pub(crate) fn run(&self) -> usize {
+        let (output, error_count) = self.report();
+        println!("{}", output);
+        error_count
+    }
+
+    /// Render all diagnostics found in the linted files in `self.format`
+    ///
+    /// # Returns
+    /// The rendered output and the number of error-level (or worse)
+    /// diagnostics found
+    fn report(&self) -> (String, usize) {
+        let diagnostics = self.file_ids.iter()
+            .filter_map(|file_id| self.db.diagnostics_in_file(*file_id))
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let error_count = diagnostics.iter().filter(|diag| diag.is_error()).count();
+        let warning_count = diagnostics.iter().filter(|diag| diag.severity == Severity::Warning).count();
+
+        let output = match self.format {
+            LintOutputFormat::Human => {
+                let mut output = diagnostics.iter()
+                    .map(|diag| self.render_diagnostic(diag))
+                    .collect::<String>();
+
+                output.push_str(&format!(
+                    "[info] found {} error(s) and {} warning(s) in {} file(s)",
+                    error_count,
+                    warning_count,
+                    self.file_ids.len()
+                ));
+
+                output
+            },
+            LintOutputFormat::Json => {
+                let json_diagnostics = diagnostics.iter()
+                    .map(|diag| self.diagnostic_to_json(diag))
+                    .collect::<Vec<_>>();
+
+                serde_json::Value::Array(json_diagnostics).to_string()
+            },
+        };
+
+        (output, error_count)
+    }
+
+    /// Render `diag` with `language_reporting`, in a style similar to
+    /// `rustc`'s, where each of its help notes is rendered as a `help`
+    fn render_diagnostic(&self, diag: &Diagnostic) -> String {
+        let files = DbFiles { db: &self.db };
+
+        let mut reporting_diag = reporting::Diagnostic::new(reporting_severity(diag.severity), diag.message.clone());
+
+        if let Some(code) = diag.code.as_ref() {
+            reporting_diag = reporting_diag.with_code(code.clone());
+        }
+
+        for label in diag.labels.iter() {
+            let span = DbSpan {
+                file_id: label.span.source(),
+                start: label.span.start().to_usize(),
+                end_exclusive: label.span.end_exclusive().to_usize(),
+            };
+
+            let mut reporting_label = match label.style {
+                LabelStyle::Primary => reporting::Label::new_primary(span),
+                LabelStyle::Secondary => reporting::Label::new_secondary(span),
+            };
+
+            if let Some(message) = label.message.as_ref() {
+                reporting_label = reporting_label.with_message(message.clone());
+            }
+
+            reporting_diag = reporting_diag.with_label(reporting_label);
+        }
+
+        let reporting_diags = std::iter::once(reporting_diag)
+            .chain(diag.help_notes.iter().map(|help_note| reporting::Diagnostic::new_help(help_note.clone())));
+
+        let mut buffer = reporting::termcolor::Buffer::no_color();
+
+        for reporting_diag in reporting_diags {
+            if let Err(e) = reporting::emit(&mut buffer, &files, &reporting_diag, &reporting::DefaultConfig) {
+                eprintln!("Failed to render diagnostic `{}`: {}", diag.message, e);
+            }
+        }
+
+        String::from_utf8_lossy(buffer.as_slice()).into_owned()
+    }
+
+    /// Compute the file, line and column that `label` starts at
+    fn label_location(&self, label: &Label) -> Option<LabelLocation> {
+        let file_id = label.span.source();
+        let file_path = self.db.file_path(file_id)?;
+        let file_text = self.db.file_text(file_id)?;
+        let line_start_offsets = self.db.line_start_offsets(file_id)?;
+
+        let start = label.span.start().to_usize();
+        let line_idx = line_idx_of(&line_start_offsets, start);
+        let line_start = line_start_offsets[line_idx];
+
+        Some(LabelLocation {
+            file_path,
+            line_number: line_idx + 1,
+            column_number: file_text[line_start..start].chars().count() + 1,
+        })
+    }
+
+    fn diagnostic_to_json(&self, diag: &Diagnostic) -> serde_json::Value {
+        let labels = diag.labels.iter()
+            .map(|label| {
+                let location = self.label_location(label);
+
+                serde_json::json!({
+                    "style": match label.style {
+                        LabelStyle::Primary => "primary",
+                        LabelStyle::Secondary => "secondary",
+                    },
+                    "file": location.as_ref().map(|location| location.file_path.clone()),
+                    "line": location.as_ref().map(|location| location.line_number),
+                    "column": location.as_ref().map(|location| location.column_number),
+                    "byte_start": label.span.start().to_usize(),
+                    "byte_end_exclusive": label.span.end_exclusive().to_usize(),
+                    "message": label.message,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::json!({
+            "severity": diag.severity.to_str(),
+            "code": diag.code,
+            "message": diag.message,
+            "labels": labels,
+            "help_notes": diag.help_notes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::PathBuf,
+    };
+
+    use oraide_parser_miniyaml::{
+        TextFilesCtx as _,
+    };
+
+    use oraide_sdk::{
+        SdkCtx as _,
+    };
+
+    use oraide_query_system::OraideDatabase;
+
+    use super::{
+        Lint,
+        LintOutputFormat,
+    };
+
+    /// Type-data that only describes `Health`, with an `HP` integer property
+    const TYPE_DATA: &str = r#"[
+        {
+            "DefiningAssemblyName": "OpenRA.Mods.Common",
+            "IsConditional": false,
+            "RequiredTraits": [],
+            "Properties": [
+                { "Kind": "Single", "TypeName": "Int32", "HumanFriendlyTypeName": "Integer", "Name": "HP", "DocLines": null, "DefaultValue": "0", "ValidValues": null }
+            ],
+            "DocLines": null,
+            "Namespace": "OpenRA.Mods.Common.Traits",
+            "Name": "Health"
+        }
+    ]"#;
+
+    /// Write a workspace, with type-data and `files`, to a directory that
+    /// the caller must remove
+    fn temp_workspace(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("oraide-cli-{}-{}", name, std::process::id()));
+        let files = std::iter::once((".oraide/type-data.json", TYPE_DATA)).chain(files.iter().cloned());
+
+        for (rel_path, text) in files {
+            let path = root.join(rel_path);
+            fs::create_dir_all(path.parent().unwrap()).expect("TEST LOGIC ERROR: failed to create a directory");
+            fs::write(&path, text).expect("TEST LOGIC ERROR: failed to write a file");
+        }
+
+        root
+    }
+
+    fn lint(format: LintOutputFormat, files: &[(&str, &str)]) -> Lint {
+        let mut db = OraideDatabase::default();
+        db.set_workspace_root(None);
+
+        let file_ids = files.iter()
+            .map(|(file_path, file_text)| db.add_text_file(*file_path, *file_text))
+            .collect();
+
+        Lint {
+            file_ids,
+            format,
+            db,
+        }
+    }
+
+    #[test]
+    fn human_output_annotates_the_offending_line() {
+        // Arrange
+        let lint = lint(LintOutputFormat::Human, &[
+            ("rules/infantry.yaml", "E1:\n        Health:\n"),
+        ]);
+
+        // Act
+        let (output, error_count) = lint.report();
+
+        // Assert
+        assert_eq!(error_count, 1);
+        assert!(output.contains("A:E0004"), "{}", output);
+        assert!(output.contains("Indentation difference must be 4 spaces"), "{}", output);
+        assert!(output.contains("rules/infantry.yaml"), "{}", output);
+        assert!(output.contains("        Health:"), "{}", output);
+        assert!(output.contains("Consider deleting 4 space(s)"), "{}", output);
+        assert!(output.ends_with("[info] found 1 error(s) and 0 warning(s) in 1 file(s)"), "{}", output);
+    }
+
+    #[test]
+    fn human_output_of_clean_files_is_only_the_summary() {
+        // Arrange
+        let lint = lint(LintOutputFormat::Human, &[
+            ("rules/infantry.yaml", "E1:\n    Health:\n"),
+            ("rules/vehicles.yaml", "JEEP:\n    Mobile:\n"),
+        ]);
+
+        // Act
+        let (output, error_count) = lint.report();
+
+        // Assert
+        assert_eq!(error_count, 0);
+        assert_eq!(output, "[info] found 0 error(s) and 0 warning(s) in 2 file(s)");
+    }
+
+    #[test]
+    fn json_output_describes_each_diagnostic_and_label() {
+        // Arrange
+        let lint = lint(LintOutputFormat::Json, &[
+            ("rules/infantry.yaml", "E1:\n    Health:\nE1:\n"),
+        ]);
+
+        // Act
+        let (output, error_count) = lint.report();
+
+        // Assert
+        assert_eq!(error_count, 1);
+
+        let json: serde_json::Value = serde_json::from_str(&output).expect("output is not JSON");
+        let expected = serde_json::json!([
+            {
+                "severity": "error",
+                "code": "S:E0001",
+                "message": "Duplicate key `E1`",
+                "labels": [
+                    {
+                        "style": "primary",
+                        "file": "rules/infantry.yaml",
+                        "line": 3,
+                        "column": 1,
+                        "byte_start": 16,
+                        "byte_end_exclusive": 18,
+                        "message": null,
+                    },
+                    {
+                        "style": "secondary",
+                        "file": "rules/infantry.yaml",
+                        "line": 1,
+                        "column": 1,
+                        "byte_start": 0,
+                        "byte_end_exclusive": 2,
+                        "message": "first defined here",
+                    },
+                ],
+                "help_notes": [
+                    "Keys must be unique amongst their siblings, consider removing or renaming one of these nodes",
+                ],
+            },
+        ]);
+
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn json_output_of_clean_files_is_an_empty_array() {
+        // Arrange
+        let lint = lint(LintOutputFormat::Json, &[
+            ("rules/infantry.yaml", "E1:\n    Health:\n"),
+        ]);
+
+        // Act
+        let (output, error_count) = lint.report();
+
+        // Assert
+        assert_eq!(error_count, 0);
+        assert_eq!(output, "[]");
+    }
+
+    #[test]
+    fn files_of_a_game_in_the_enclosing_workspace_are_validated() {
+        // Arrange
+        let root = temp_workspace("lint-game", &[
+            ("mods/ra/mod.yaml", "Rules:\n    ra|rules/infantry.yaml\n"),
+            ("mods/ra/rules/infantry.yaml", "E1:\n    Helth:\n"),
+        ]);
+
+        // Act
+        let lint = Lint::new(vec![root.join("mods/ra/rules")], None, LintOutputFormat::Json);
+        let report = lint.map(|lint| lint.report());
+
+        let _ = fs::remove_dir_all(&root);
+
+        // Assert
+        let (output, error_count) = report.expect("the workspace should have been loaded");
+        assert_eq!(error_count, 1, "{}", output);
+        assert!(output.contains("S:E0006"), "{}", output);
+    }
+
+    #[test]
+    fn actors_of_a_map_in_the_enclosing_workspace_are_checked_against_its_game() {
+        // Arrange
+        let root = temp_workspace("lint-map", &[
+            ("mods/ra/mod.yaml", "Rules:\n    ra|rules/infantry.yaml\nMapFolders:\n    ra|maps: System\n"),
+            ("mods/ra/rules/infantry.yaml", "E1:\n    Health:\n"),
+            ("mods/ra/maps/forest/map.yaml", "RequiresMod: ra\nActors:\n    Actor0: e1\n    Actor1: e9\n"),
+        ]);
+
+        // Act
+        let lint = Lint::new(vec![root.join("mods/ra/maps")], None, LintOutputFormat::Json);
+        let report = lint.map(|lint| lint.report());
+
+        let _ = fs::remove_dir_all(&root);
+
+        // Assert
+        let (output, error_count) = report.expect("the workspace should have been loaded");
+        assert_eq!(error_count, 1, "{}", output);
+        assert!(output.contains("S:E0014"), "{}", output);
+        assert!(output.contains("Unknown actor type `e9`"), "{}", output);
+    }
+}
\ No newline at end of file
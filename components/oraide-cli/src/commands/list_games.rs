@@ -19,11 +19,11 @@ use {
     oraide_query_system::{
         OraideDatabase,
     },
-    oraide_language_server::{
-        LanguageServerCtx as _,
-    },
     oraide_sdk::{
+        GameId,
+        ManifestSection,
         SdkCtx as _,
+        SdkCtxExt as _,
     },
 };
 
@@ -35,6 +35,7 @@ impl ListGames {
     pub fn new_with_root_dir(root_dir: PathBuf) -> Self {
         let mut db = OraideDatabase::default();
         db.set_workspace_root(root_dir.into());
+        db.load_game_manifests();
 
         Self {
             db,
@@ -52,7 +53,36 @@ impl ListGames {
             let manifest_path = root.join("mod.yaml");
 
             println!("{}:", id);
+
+            if let Some(manifest) = self.db.game_manifest(GameId::from(id.to_owned())) {
+                if let Some(title) = manifest.metadata.title {
+                    println!("  title: {}", title.text);
+                }
+
+                if let Some(version) = manifest.metadata.version {
+                    println!("  version: {}", version.text);
+                }
+            }
+
             println!("  manifest: {}", manifest_path.display());
+
+            for section in ManifestSection::ALL.iter() {
+                let file_paths = match self.db.resolved_file_paths_for_game_section(GameId::from(id.to_owned()), *section) {
+                    Some(paths) => paths,
+                    _ => continue,
+                };
+
+                if file_paths.is_empty() {
+                    continue;
+                }
+
+                println!("  {}:", section.key());
+
+                for file_path in file_paths {
+                    println!("    - {}", file_path.display());
+                }
+            }
+
             println!();
         }
     }
@@ -18,8 +18,20 @@ pub(crate) use parse::Parse;
 mod find_definition;
 pub(crate) use find_definition::FindDefinition;
 
+mod find_references;
+pub(crate) use find_references::FindReferences;
+
 mod hover;
 pub(crate) use hover::Hover;
 
+mod fmt;
+pub(crate) use fmt::Fmt;
+
 mod list_games;
-pub(crate) use list_games::ListGames;
\ No newline at end of file
+pub(crate) use list_games::ListGames;
+
+mod lint;
+pub(crate) use lint::{
+    Lint,
+    LintOutputFormat,
+};
\ No newline at end of file
@@ -40,8 +40,12 @@ mod commands;
 use commands::{
     Parse,
     FindDefinition,
+    FindReferences,
+    Fmt,
     Hover,
     ListGames,
+    Lint,
+    LintOutputFormat,
 };
 
 mod ide;
@@ -100,6 +104,68 @@ pub fn main() {
             find_def.run();
             println!("[info] took {:?} to look for definition(s) of `{}`", start.elapsed(), name_to_find);
         },
+        "find-ref" | "find-refs" | "find-reference" | "find-references" => {
+            let project_root_dir = match args.next() {
+                Some(n) => PathBuf::from(n),
+                _ => {
+                    eprintln!("Please provide a path to a project root directory");
+                    return;
+                },
+            };
+
+            let name_to_find = match args.next() {
+                Some(n) => n,
+                _ => {
+                    eprintln!("Please provide an item name to find (ex: E1)");
+                    return;
+                },
+            };
+
+            let find_refs = FindReferences::new(name_to_find.clone(), project_root_dir);
+
+            let start = std::time::Instant::now();
+            find_refs.run();
+            println!("[info] took {:?} to look for reference(s) to `{}`", start.elapsed(), name_to_find);
+        },
+        "fmt" => {
+            let mut check = false;
+            let mut paths = vec![];
+
+            for arg in args {
+                if arg == "--check" {
+                    check = true;
+                } else if arg == "--root" {
+                    opt_root_dir = match args.next() {
+                        Some(root_dir) => Some(PathBuf::from(root_dir)),
+                        _ => {
+                            eprintln!();
+                            eprintln!("Please provide a directory to `--root` (see below for more information)");
+                            eprintln!();
+                            print_usage_instructions();
+                            return;
+                        },
+                    };
+                } else {
+                    paths.push(PathBuf::from(arg));
+                }
+            }
+
+            if paths.is_empty() {
+                eprintln!();
+                eprintln!("Please provide at least one <path> to format (see below for more information)");
+                eprintln!();
+                print_usage_instructions();
+                return;
+            }
+
+            let fmt = Fmt::new(paths, check)
+                .expect("Failed to setup fmt");
+
+            let problem_count = fmt.run();
+            if problem_count > 0 {
+                std::process::exit(1);
+            }
+        },
         "hover" => {
             let root_dir = match args.next() {
                 Some(path) => path.into(),
@@ -154,11 +220,57 @@ pub fn main() {
             ide::ide();
         },
         "lint" => {
-            match args.next() {
-                Some(_file_path) => {
-                    unimplemented!("linting")
+            let mut format = LintOutputFormat::Human;
+            let mut opt_root_dir = None;
+            let mut paths = vec![];
+
+            while let Some(arg) = args.next() {
+                if arg == "--format" {
+                    format = match args.next().and_then(|s| s.parse().ok()) {
+                        Some(format) => format,
+                        _ => {
+                            eprintln!();
+                            eprintln!("Please provide `human` or `json` to `--format` (see below for more information)");
+                            eprintln!();
+                            print_usage_instructions();
+                            return;
+                        },
+                    };
+                } else if arg == "--root" {
+                    opt_root_dir = match args.next() {
+                        Some(root_dir) => Some(PathBuf::from(root_dir)),
+                        _ => {
+                            eprintln!();
+                            eprintln!("Please provide a directory to `--root` (see below for more information)");
+                            eprintln!();
+                            print_usage_instructions();
+                            return;
+                        },
+                    };
+                } else {
+                    paths.push(PathBuf::from(arg));
+                }
+            }
+
+            if paths.is_empty() {
+                eprintln!();
+                eprintln!("Please provide at least one <path> to lint (see below for more information)");
+                eprintln!();
+                print_usage_instructions();
+                return;
+            }
+
+            let lint = match Lint::new(paths, opt_root_dir, format) {
+                Ok(lint) => lint,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
                 },
-                _ => eprintln!("Please provide a file path to lint"),
+            };
+
+            let error_count = lint.run();
+            if error_count > 0 {
+                std::process::exit(1);
             }
         },
         "list-games" => {
@@ -188,10 +300,13 @@ fn print_usage_instructions() {
     eprintln!("  ora ide                                                                 - run the OpenRA language server / IDE support");
     eprintln!("  ora parse      <file-path>                                              - print all definitions (top-level items) in a file");
     eprintln!("  ora find-defs  <project-root-path> <item-name>                          - find all definitions with name <item-name> in <project-root-path>");
+    eprintln!("  ora find-refs  <project-root-path> <item-name>                          - find all references to <item-name> (ex: `Inherits` values, `Armament` weapons) in <project-root-path>");
+    eprintln!("  ora fmt        [--check] <path>...                                      - format each file (or .yaml file in each directory) in <path>..., with `--check` only reporting unformatted files and exiting with 1 if any are found");
     eprintln!("  ora hover      <root-dir> <rel-file-path> <line-number> <column-number> - print hover data for the token at <root-dir>/<rel-file-path>:<line-number>:<column-number>");
     eprintln!("    example: ora hover /path/to/project/root/dir rules/infantry.yaml 15 8");
-    eprintln!("  ora list-games <root-dir>                                               - print IDs and names for all games in <root-dir>/mods/");
-  //eprintln!("  ora lint <file-path>                                                   - unimplemented");
+    eprintln!("  ora list-games <root-dir>                                               - print the ID, title and version of all games in <root-dir>/mods/");
+    eprintln!("  ora lint       [--format <human|json>] [--root <root-dir>] <path>...    - print diagnostics for each file (or .yaml file in each directory) in <path>..., exiting with 1 if errors are found");
+    eprintln!("    files are checked in the context of the games in <root-dir>/mods/, which defaults to the first directory containing <path> that has a mods/ directory");
 }
 
 /// Read the contents of `file_path` and add it to `db`, creating and returning
@@ -213,4 +328,39 @@ pub(crate) fn add_file(db: &mut OraideDatabase, file_path: &Path) -> Result<File
     let file_id = db.add_text_file(file_path.to_string_lossy(), text);
 
     Ok(file_id)
+}
+
+/// Add each of `paths` to `db`, where directories are searched recursively
+/// (in sorted order) for `.yaml` files, returning the newly-created
+/// [`FileId`]s, or `Err(String)` if something goes wrong.
+///
+/// [`FileId`]: ../oraide_span/struct.FileId.html
+pub(crate) fn add_files(db: &mut OraideDatabase, paths: Vec<PathBuf>) -> Result<Vec<FileId>, String> {
+    yaml_file_paths(paths).iter()
+        .map(|path| add_file(db, path))
+        .collect()
+}
+
+/// Expand `paths` into the files they refer to, where directories are
+/// searched recursively (in sorted order) for `.yaml` files
+pub(crate) fn yaml_file_paths(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut file_paths = vec![];
+
+    for path in paths {
+        if path.is_dir() {
+            let dir_walker = walkdir::WalkDir::new(&path)
+                .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+                .into_iter()
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.metadata().map(|md| md.is_file()).unwrap_or(false))
+                .filter(|entry| entry.path().extension() == Some(std::ffi::OsString::from("yaml".to_string()).as_ref()))
+                ;
+
+            file_paths.extend(dir_walker.map(|entry| entry.path().to_path_buf()));
+        } else {
+            file_paths.push(path);
+        }
+    }
+
+    file_paths
 }
\ No newline at end of file
@@ -0,0 +1,552 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Computing quick fixes for common mistakes
+
+use oraide_span::{
+    FileId,
+    FileSpan,
+};
+
+use oraide_parser_miniyaml::{
+    ArenaNodeId,
+    Diagnostic,
+    IndentStyle,
+    Tree,
+};
+
+use oraide_sdk::{
+    ResolvedActor,
+    RuleNode,
+};
+
+use crate::{
+    folding,
+    types::TraitDetail,
+    validation,
+};
+
+/// The number of spaces that make up a single level of indentation
+const SPACES_PER_INDENT_LEVEL: usize = 4;
+
+/// The most suggestions that will be offered for a single misspelled name
+const MAX_SUGGESTIONS: usize = 3;
+
+/// The number of single-character insertions, removals and substitutions
+/// needed to turn `a` into `b`, ignoring case
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<_> = a.chars().flat_map(char::to_lowercase).collect();
+    let b: Vec<_> = b.chars().flat_map(char::to_lowercase).collect();
+
+    // Only the previous row of the distance matrix is needed to compute
+    // the current row
+    let mut previous_row: Vec<_> = (0..=b.len()).collect();
+
+    for (a_idx, a_ch) in a.iter().enumerate() {
+        let mut current_row = vec![a_idx + 1];
+
+        for (b_idx, b_ch) in b.iter().enumerate() {
+            let substitution_cost = if a_ch == b_ch { 0 } else { 1 };
+
+            let distance = (previous_row[b_idx] + substitution_cost)
+                .min(previous_row[b_idx + 1] + 1)
+                .min(current_row[b_idx] + 1);
+
+            current_row.push(distance);
+        }
+
+        previous_row = current_row;
+    }
+
+    previous_row[b.len()]
+}
+
+/// Find the `candidates` that `name` is most likely a misspelling of
+///
+/// A candidate is only suggested if it is at most a third of `name`'s length
+/// (and at least one character) away from `name`.
+///
+/// # Returns
+/// At most [`MAX_SUGGESTIONS`] candidates, closest first
+///
+/// [`MAX_SUGGESTIONS`]: constant.MAX_SUGGESTIONS.html
+pub(crate) fn did_you_mean<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    let mut suggestions: Vec<_> = candidates.into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+
+    suggestions.sort();
+    suggestions.dedup();
+
+    suggestions.into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// A quick fix whose edits have not yet been converted to positions
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Fix {
+    pub(crate) title: String,
+
+    /// The span of the problem being fixed, the fix is only offered for
+    /// ranges that intersect it
+    pub(crate) trigger_span: FileSpan,
+
+    /// The span of text to replace and the text to replace it with
+    pub(crate) edits: Vec<(FileSpan, String)>,
+}
+
+/// The indentation level of `line_text`, which may use either tabs or
+/// spaces for indentation
+pub(crate) fn indentation_level(line_text: &str) -> usize {
+    let indentation: Vec<_> = line_text.chars()
+        .take_while(|ch| *ch == ' ' || *ch == '\t')
+        .collect();
+
+    let tab_count = indentation.iter().filter(|ch| **ch == '\t').count();
+    let space_count = indentation.len() - tab_count;
+
+    tab_count + space_count / SPACES_PER_INDENT_LEVEL
+}
+
+/// The indentation level that a line indented with `space_count` spaces was
+/// most likely meant to have, given that the line before it has indentation
+/// level `previous_level`
+///
+/// An indented line is never moved to the top level, and can be at most one
+/// level deeper than the line before it.
+pub(crate) fn intended_indentation_level(space_count: usize, previous_level: usize) -> usize {
+    let nearest_level = (space_count + SPACES_PER_INDENT_LEVEL / 2) / SPACES_PER_INDENT_LEVEL;
+    nearest_level.max(1).min(previous_level + 1)
+}
+
+/// A short, human-readable, description of `indentation`, such as `2 tabs`
+pub(crate) fn describe_indentation(indentation: &str) -> String {
+    let count = indentation.chars().count();
+    let unit = if indentation.starts_with('\t') { "tab" } else { "space" };
+
+    if count == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+/// The text of line `line_idx`, including its line terminator
+fn line_text<'text>(
+    file_text: &'text str,
+    line_start_offsets: &[usize],
+    line_idx: usize,
+) -> Option<&'text str> {
+    let start = *line_start_offsets.get(line_idx)?;
+    let end = line_start_offsets.get(line_idx + 1).cloned().unwrap_or_else(|| file_text.len());
+    file_text.get(start..end)
+}
+
+/// The span covering lines `start_line_idx` through `end_line_idx`,
+/// inclusive, including the final line's terminator
+fn lines_span(
+    file_id: FileId,
+    file_text: &str,
+    line_start_offsets: &[usize],
+    start_line_idx: usize,
+    end_line_idx: usize,
+) -> Option<FileSpan> {
+    let start = *line_start_offsets.get(start_line_idx)?;
+    let end = line_start_offsets.get(end_line_idx + 1).cloned().unwrap_or_else(|| file_text.len());
+    Some(FileSpan::new(file_id, start, end))
+}
+
+/// The indentation level of the closest line, before line `line_idx`,
+/// that is neither empty nor comment-only
+fn previous_indentation_level(
+    file_text: &str,
+    line_start_offsets: &[usize],
+    line_idx: usize,
+) -> usize {
+    (0..line_idx).rev()
+        .filter_map(|idx| line_text(file_text, line_start_offsets, idx))
+        .find(|text| {
+            let trimmed_text = text.trim();
+            !trimmed_text.is_empty() && !trimmed_text.starts_with('#')
+        })
+        .map(indentation_level)
+        .unwrap_or(0)
+}
+
+/// Fix indentation that mixes tabs and spaces (`A:E0001`) by using
+/// `indent_style`, and space indentation that is not a whole number of
+/// levels (`A:E0002` and `A:E0004`) by using the nearest level
+pub(crate) fn indentation_fixes(
+    diagnostics: &[Diagnostic],
+    indent_style: IndentStyle,
+    file_text: &str,
+    line_start_offsets: &[usize],
+) -> Vec<Fix> {
+    let mut fixes = vec![];
+
+    for diag in diagnostics {
+        let (span, indentation_text) = match diag.primary_span() {
+            Some(span) => match span.text(file_text) {
+                Some(text) => (span, text),
+                _ => continue,
+            },
+            _ => continue,
+        };
+
+        let new_indentation = match diag.code.as_ref().map(String::as_str) {
+            Some("A:E0001") => indent_style.indentation(indentation_level(indentation_text)),
+            Some("A:E0002") | Some("A:E0004") => {
+                let line_idx = folding::line_idx_of(line_start_offsets, span.start().to_usize());
+                let previous_level = previous_indentation_level(file_text, line_start_offsets, line_idx);
+                let level = intended_indentation_level(indentation_text.len(), previous_level);
+                IndentStyle::Spaces.indentation(level)
+            },
+            _ => continue,
+        };
+
+        if new_indentation == indentation_text {
+            continue;
+        }
+
+        let fix = Fix {
+            title: format!("Indent with {}", describe_indentation(&new_indentation)),
+            trigger_span: span,
+            edits: vec![(span, new_indentation)],
+        };
+
+        // A line may be both `A:E0002` and `A:E0004`
+        if !fixes.contains(&fix) {
+            fixes.push(fix);
+        }
+    }
+
+    fixes
+}
+
+/// Suggest known trait names for each unknown trait of `definition`, and
+/// known property names for each unknown property of its known traits
+pub(crate) fn misspelling_fixes(
+    type_data: &[TraitDetail],
+    definition: &RuleNode,
+) -> Vec<Fix> {
+    let mut fixes = vec![];
+
+    let trait_nodes = definition.children.iter()
+        .filter(|node| !node.is_inherits() && !node.is_removal());
+
+    for trait_node in trait_nodes {
+        let name = validation::trait_name(&trait_node.key);
+
+        let trait_detail = match type_data.iter().find(|detail| detail.name == name) {
+            Some(detail) => detail,
+            _ => {
+                // Keep any `@suffix` as it is
+                let name_span = trait_node.key_span.with_end_exclusive(trait_node.key_span.start().to_usize() + name.len());
+                let candidates = type_data.iter().map(|detail| detail.name.as_str());
+
+                for suggestion in did_you_mean(name, candidates) {
+                    fixes.push(Fix {
+                        title: format!("Change to `{}`", suggestion),
+                        trigger_span: trait_node.key_span,
+                        edits: vec![(name_span, suggestion.to_owned())],
+                    });
+                }
+
+                continue;
+            },
+        };
+
+        let property_nodes = trait_node.children.iter()
+            .filter(|node| !node.is_removal())
+            .filter(|node| trait_detail.properties.iter().all(|prop| prop.name != node.key));
+
+        for property_node in property_nodes {
+            let candidates = trait_detail.properties.iter().map(|prop| prop.name.as_str());
+
+            for suggestion in did_you_mean(&property_node.key, candidates) {
+                fixes.push(Fix {
+                    title: format!("Change to `{}`", suggestion),
+                    trigger_span: property_node.key_span,
+                    edits: vec![(property_node.key_span, suggestion.to_owned())],
+                });
+            }
+        }
+    }
+
+    fixes
+}
+
+/// Insert an empty block for each trait that is required by, but missing
+/// from, a (non-abstract) definition in `rule_nodes`
+///
+/// `resolved_actors` resolves a definition's name to the actor it defines in
+/// each game the file belongs to.  The block is inserted directly after the
+/// definition's key.
+pub(crate) fn missing_required_trait_fixes(
+    type_data: &[TraitDetail],
+    rule_nodes: &[RuleNode],
+    resolved_actors: impl Fn(&str) -> Vec<ResolvedActor>,
+    indent_style: IndentStyle,
+    file_text: &str,
+    line_start_offsets: &[usize],
+) -> Vec<Fix> {
+    let mut fixes = vec![];
+
+    // Abstract definitions (such as `^Infantry`) are never created in
+    // game so they needn't have the traits their traits require
+    for rule_node in rule_nodes.iter().filter(|node| !node.key.starts_with('^')) {
+        let key_line_idx = folding::line_idx_of(line_start_offsets, rule_node.key_span.start().to_usize());
+
+        // Match the indentation of the existing traits, if there are any
+        let indentation = rule_node.children.first()
+            .map(|child| folding::line_idx_of(line_start_offsets, child.key_span.start().to_usize()))
+            .and_then(|line_idx| line_text(file_text, line_start_offsets, line_idx))
+            .map(|text| text.chars().take_while(|ch| *ch == ' ' || *ch == '\t').collect::<String>())
+            .unwrap_or_else(|| indent_style.indentation(1));
+
+        // The last line of a file may not be terminated
+        let key_line_end = line_start_offsets.get(key_line_idx + 1).cloned().unwrap_or_else(|| file_text.len());
+        let is_key_line_terminated = file_text[..key_line_end].ends_with('\n');
+
+        for resolved_actor in resolved_actors(&rule_node.key) {
+            for (_, missing_name) in validation::missing_required_traits(type_data, &resolved_actor.node) {
+                let new_text = if is_key_line_terminated {
+                    format!("{}{}:\n", indentation, missing_name)
+                } else {
+                    format!("\n{}{}:", indentation, missing_name)
+                };
+
+                let insert_at = FileSpan::new(rule_node.key_span.source(), key_line_end, key_line_end);
+
+                let fix = Fix {
+                    title: format!("Add required trait `{}`", missing_name),
+                    trigger_span: rule_node.key_span,
+                    edits: vec![(insert_at, new_text)],
+                };
+
+                // Several traits, or games, may require the same trait
+                if !fixes.contains(&fix) {
+                    fixes.push(fix);
+                }
+            }
+        }
+    }
+
+    fixes
+}
+
+/// The keys of the children of the node `arena_node_id`, without any
+/// surrounding whitespace
+fn child_key_texts<'text>(
+    tree: &Tree,
+    arena_node_id: ArenaNodeId,
+    file_text: &'text str,
+) -> Vec<&'text str> {
+    arena_node_id.children(&tree.arena)
+        .filter_map(|child_node_id| tree.arena.get(child_node_id))
+        .filter_map(|shrd_arena_node| shrd_arena_node.get().key_text(file_text))
+        .map(str::trim)
+        .collect()
+}
+
+/// Offer to remove each node whose key duplicates that of an earlier
+/// sibling (`S:E0001`) or, if the duplicate only adds children, to merge
+/// those children into the earlier sibling
+///
+/// `duplicate_node_ids` are the IDs of the earlier sibling and of the
+/// duplicate, for each duplicate in `tree`.
+pub(crate) fn duplicate_key_fixes(
+    file_id: FileId,
+    tree: &Tree,
+    duplicate_node_ids: &[(ArenaNodeId, ArenaNodeId)],
+    file_text: &str,
+    line_start_offsets: &[usize],
+) -> Vec<Fix> {
+    let mut fixes = vec![];
+
+    for &(first_node_id, duplicate_node_id) in duplicate_node_ids {
+        let (shrd_first_node, shrd_duplicate_node) = match (tree.arena.get(first_node_id), tree.arena.get(duplicate_node_id)) {
+            (Some(first), Some(duplicate)) => (first.get(), duplicate.get()),
+            _ => continue,
+        };
+
+        let (key_text, key_span) = match (shrd_duplicate_node.key_text(file_text), shrd_duplicate_node.key_span()) {
+            (Some(text), Some(span)) => (text, span),
+            _ => continue,
+        };
+
+        let (first_line_idxs, duplicate_line_idxs) = match (
+            folding::block_line_idxs(tree, first_node_id, line_start_offsets),
+            folding::block_line_idxs(tree, duplicate_node_id, line_start_offsets),
+        ) {
+            (Some(first), Some(duplicate)) => (first, duplicate),
+            _ => continue,
+        };
+
+        let (_, first_end_line_idx) = first_line_idxs;
+        let (duplicate_start_line_idx, duplicate_end_line_idx) = duplicate_line_idxs;
+
+        let duplicate_block_span = match lines_span(file_id, file_text, line_start_offsets, duplicate_start_line_idx, duplicate_end_line_idx) {
+            Some(span) => span,
+            _ => continue,
+        };
+
+        fixes.push(Fix {
+            title: format!("Remove duplicate `{}`", key_text),
+            trigger_span: key_span,
+            edits: vec![(duplicate_block_span, String::new())],
+        });
+
+        // Merging is only meaningful if the duplicate has children and
+        // doesn't conflict with the value, or the children, of the first
+        // node (merging a child the first node already has would only
+        // create another duplicate)
+        let has_children = duplicate_node_id.children(&tree.arena).next().is_some();
+        let duplicate_value = shrd_duplicate_node.value_text(file_text);
+        let is_value_compatible = duplicate_value.is_none() || duplicate_value == shrd_first_node.value_text(file_text);
+
+        let first_child_keys = child_key_texts(tree, first_node_id, file_text);
+        let has_conflicting_children = child_key_texts(tree, duplicate_node_id, file_text).iter()
+            .any(|key| first_child_keys.contains(key));
+
+        if !has_children || !is_value_compatible || has_conflicting_children {
+            continue;
+        }
+
+        let edits = if first_end_line_idx + 1 == duplicate_start_line_idx {
+            // The duplicate's children already directly follow the first
+            // node's block, so removing the duplicate's key is enough
+            match lines_span(file_id, file_text, line_start_offsets, duplicate_start_line_idx, duplicate_start_line_idx) {
+                Some(span) => vec![(span, String::new())],
+                _ => continue,
+            }
+        } else {
+            let (insert_at, children_text) = match (
+                line_start_offsets.get(first_end_line_idx + 1),
+                lines_span(file_id, file_text, line_start_offsets, duplicate_start_line_idx + 1, duplicate_end_line_idx)
+                    .and_then(|span| span.text(file_text)),
+            ) {
+                (Some(insert_at), Some(text)) => (*insert_at, text),
+                _ => continue,
+            };
+
+            let mut children_text = children_text.to_owned();
+
+            if !children_text.ends_with('\n') {
+                children_text.push('\n');
+            }
+
+            vec![
+                (FileSpan::new(file_id, insert_at, insert_at), children_text),
+                (duplicate_block_span, String::new()),
+            ]
+        };
+
+        fixes.push(Fix {
+            title: format!("Merge into first `{}`", key_text),
+            trigger_span: key_span,
+            edits,
+        });
+    }
+
+    fixes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_counts_insertions_removals_and_substitutions() {
+        // Arrange, Act, Assert
+        assert_eq!(edit_distance("Health", "Health"), 0);
+        assert_eq!(edit_distance("Helth", "Health"), 1);
+        assert_eq!(edit_distance("Heallth", "Health"), 1);
+        assert_eq!(edit_distance("Heakth", "Health"), 1);
+        assert_eq!(edit_distance("", "Mobile"), 6);
+    }
+
+    #[test]
+    fn edit_distance_ignores_case() {
+        // Arrange, Act, Assert
+        assert_eq!(edit_distance("health", "Health"), 0);
+    }
+
+    #[test]
+    fn did_you_mean_suggests_closest_candidates_first() {
+        // Arrange
+        let candidates = vec!["Armament", "Armor", "Aircraft", "AttackBase"];
+
+        // Act
+        let actual = did_you_mean("Armour", candidates);
+
+        // Assert
+        assert_eq!(actual, vec!["Armor"]);
+    }
+
+    #[test]
+    fn did_you_mean_excludes_distant_candidates() {
+        // Arrange
+        let candidates = vec!["Mobile", "Health"];
+
+        // Act
+        let actual = did_you_mean("Buildable", candidates);
+
+        // Assert
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn did_you_mean_limits_suggestions() {
+        // Arrange
+        let candidates = vec!["Abcd", "Abce", "Abcf", "Abcg"];
+
+        // Act
+        let actual = did_you_mean("Abcx", candidates);
+
+        // Assert
+        assert_eq!(actual, vec!["Abcd", "Abce", "Abcf"]);
+    }
+
+    #[test]
+    fn intended_indentation_level_rounds_to_nearest_level() {
+        // Arrange, Act, Assert
+        assert_eq!(intended_indentation_level(3, 0), 1);
+        assert_eq!(intended_indentation_level(5, 1), 1);
+        assert_eq!(intended_indentation_level(7, 1), 2);
+    }
+
+    #[test]
+    fn intended_indentation_level_is_at_most_one_deeper_than_previous_line() {
+        // Arrange, Act, Assert
+        assert_eq!(intended_indentation_level(12, 0), 1);
+        assert_eq!(intended_indentation_level(1, 2), 1);
+    }
+
+    #[test]
+    fn describe_indentation_pluralizes() {
+        // Arrange, Act, Assert
+        assert_eq!(describe_indentation("\t"), "1 tab");
+        assert_eq!(describe_indentation("        "), "8 spaces");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,188 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Computing the ranges of lines that a language client may fold
+
+use oraide_parser_miniyaml::{
+    ArenaNodeId,
+    Node,
+    Tree,
+};
+
+use oraide_actor::{
+    FoldingRange,
+    FoldingRangeKind,
+};
+
+/// The index of the line that contains `byte_idx`
+pub(crate) fn line_idx_of(line_start_offsets: &[usize], byte_idx: usize) -> usize {
+    match line_start_offsets.binary_search(&byte_idx) {
+        Ok(idx) => idx,
+        Err(idx) => idx.saturating_sub(1),
+    }
+}
+
+/// The indices of the first and last lines, inclusive, of the node
+/// `arena_node_id` and all of its descendants
+///
+/// # Returns
+/// `None` if the node has no span
+pub(crate) fn block_line_idxs(
+    tree: &Tree,
+    arena_node_id: ArenaNodeId,
+    line_start_offsets: &[usize],
+) -> Option<(usize, usize)> {
+    let start = tree.arena.get(arena_node_id)?.get().span()?.start().to_usize();
+
+    let end = arena_node_id.descendants(&tree.arena)
+        .filter_map(|descendant_id| tree.arena.get(descendant_id))
+        .filter_map(|shrd_arena_node| shrd_arena_node.get().span())
+        .map(|span| span.end_exclusive().to_usize().saturating_sub(1).max(span.start().to_usize()))
+        .max()?;
+
+    Some((line_idx_of(line_start_offsets, start), line_idx_of(line_start_offsets, end)))
+}
+
+/// Compute a [`FoldingRange`] for every node in `tree` that has children,
+/// covering the node and all of its descendants, and one for every run of
+/// consecutive comment-only lines in `nodes`
+///
+/// Ranges that would only cover a single line are not included.
+///
+/// [`FoldingRange`]: ../oraide_actor/struct.FoldingRange.html
+pub(crate) fn folding_ranges(
+    tree: &Tree,
+    nodes: &[Node],
+    line_start_offsets: &[usize],
+) -> Vec<FoldingRange> {
+    let mut ranges = vec![];
+
+    for arena_node_id in tree.node_ids.iter().skip(1) { // skip the sentinel
+        if arena_node_id.children(&tree.arena).next().is_none() {
+            continue;
+        }
+
+        let (start_line_idx, end_line_idx) = match block_line_idxs(tree, *arena_node_id, line_start_offsets) {
+            Some(line_idxs) => line_idxs,
+            _ => continue,
+        };
+
+        if end_line_idx > start_line_idx {
+            ranges.push(FoldingRange {
+                start_line_idx,
+                end_line_idx,
+                kind: FoldingRangeKind::Region,
+            });
+        }
+    }
+
+    // Each node is a single line, so consecutive comment-only nodes are
+    // consecutive comment-only lines
+    let mut comment_run: Option<(usize, usize)> = None;
+
+    for node in nodes.iter().map(Some).chain(std::iter::once(None)) {
+        let comment_line_idx = node
+            .filter(|node| node.is_comment_only())
+            .and_then(Node::span)
+            .map(|span| line_idx_of(line_start_offsets, span.start().to_usize()));
+
+        comment_run = match (comment_run, comment_line_idx) {
+            (Some((start_line_idx, _)), Some(line_idx)) => Some((start_line_idx, line_idx)),
+            (None, Some(line_idx)) => Some((line_idx, line_idx)),
+            (Some((start_line_idx, end_line_idx)), None) => {
+                if end_line_idx > start_line_idx {
+                    ranges.push(FoldingRange {
+                        start_line_idx,
+                        end_line_idx,
+                        kind: FoldingRangeKind::Comment,
+                    });
+                }
+
+                None
+            },
+            (None, None) => None,
+        };
+    }
+
+    ranges.sort();
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use oraide_span::FileId;
+
+    use oraide_parser_miniyaml::Parse;
+
+    use oraide_actor::{
+        FoldingRange,
+        FoldingRangeKind,
+    };
+
+    use super::folding_ranges;
+
+    fn line_start_offsets(text: &str) -> Vec<usize> {
+        std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect()
+    }
+
+    fn folding_ranges_of(text: &str) -> Vec<FoldingRange> {
+        let parse = Parse::new(FileId(0), text);
+        folding_ranges(&parse.tree(), &parse.nodes(), &line_start_offsets(text))
+    }
+
+    #[test]
+    fn nodes_with_children_fold_to_their_last_descendant() {
+        // Arrange
+        let text = "E1:\n\tInherits: ^Infantry\n\tHealth:\n\t\tHP: 5000\n\nE2:\n\tInherits: E1\n";
+
+        // Act
+        let actual = folding_ranges_of(text);
+
+        // Assert
+        assert_eq!(actual, vec![
+            FoldingRange { start_line_idx: 0, end_line_idx: 3, kind: FoldingRangeKind::Region },
+            FoldingRange { start_line_idx: 2, end_line_idx: 3, kind: FoldingRangeKind::Region },
+            FoldingRange { start_line_idx: 5, end_line_idx: 6, kind: FoldingRangeKind::Region },
+        ]);
+    }
+
+    #[test]
+    fn consecutive_comment_lines_fold_together() {
+        // Arrange
+        let text = "# Infantry\n# (the squishy ones)\nE1:\n# lonely comment\nE2:\n";
+
+        // Act
+        let actual = folding_ranges_of(text);
+
+        // Assert
+        assert_eq!(actual, vec![
+            FoldingRange { start_line_idx: 0, end_line_idx: 1, kind: FoldingRangeKind::Comment },
+        ]);
+    }
+
+    #[test]
+    fn nodes_without_children_do_not_fold() {
+        // Arrange
+        let text = "E1:\nE2: value\n";
+
+        // Act
+        let actual = folding_ranges_of(text);
+
+        // Assert
+        assert!(actual.is_empty());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,161 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Fuzzy matching of symbol names against a user-typed query
+
+/// Awarded for every character of the query that is matched
+const MATCH_SCORE: usize = 1;
+
+/// Awarded when a matched character directly follows the previous match
+const CONSECUTIVE_BONUS: usize = 4;
+
+/// Awarded when a matched character starts a "word" of the candidate
+const WORD_START_BONUS: usize = 8;
+
+/// Awarded when the query matches the whole candidate
+const EXACT_BONUS: usize = 100;
+
+/// Whether the character at `idx` of `chars` starts a "word", which is the
+/// start of the candidate, the character after a separator (`^`, `@`, `_`,
+/// `.`, `-`) or an uppercase character after a lowercase one
+fn is_word_start(chars: &[char], idx: usize) -> bool {
+    if idx == 0 {
+        return true;
+    }
+
+    let prev = chars[idx - 1];
+    let curr = chars[idx];
+
+    match prev {
+        '^' | '@' | '_' | '.' | '-' => true,
+        _ => prev.is_lowercase() && curr.is_uppercase(),
+    }
+}
+
+/// Score how well `candidate` matches `query`, ignoring case
+///
+/// Every character of `query` must appear, in order, in `candidate`.
+/// Matches that are consecutive or that start a word score higher.
+///
+/// # Returns
+/// `None` if `candidate` does not match `query`, otherwise a score where
+/// higher is a better match
+pub(crate) fn fuzzy_match_score(query: &str, candidate: &str) -> Option<usize> {
+    let query_chars: Vec<_> = query.chars().flat_map(char::to_lowercase).collect();
+
+    if query_chars.is_empty() {
+        return Some(0);
+    }
+
+    let candidate_chars: Vec<_> = candidate.chars().collect();
+    let mut score = 0;
+    let mut query_idx = 0;
+    let mut prev_match_idx: Option<usize> = None;
+
+    for (idx, ch) in candidate_chars.iter().enumerate() {
+        if query_idx == query_chars.len() {
+            break;
+        }
+
+        if !ch.to_lowercase().eq(std::iter::once(query_chars[query_idx])) {
+            continue;
+        }
+
+        score += MATCH_SCORE;
+
+        if prev_match_idx.map(|prev_idx| prev_idx + 1 == idx).unwrap_or(false) {
+            score += CONSECUTIVE_BONUS;
+        }
+
+        if is_word_start(&candidate_chars, idx) {
+            score += WORD_START_BONUS;
+        }
+
+        prev_match_idx = Some(idx);
+        query_idx += 1;
+    }
+
+    if query_idx < query_chars.len() {
+        return None;
+    }
+
+    if query.to_lowercase() == candidate.to_lowercase() {
+        score += EXACT_BONUS;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fuzzy_match_score;
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(fuzzy_match_score("", "E1"), Some(0));
+    }
+
+    #[test]
+    fn matching_ignores_case() {
+        assert!(fuzzy_match_score("harv", "HARV").is_some());
+        assert!(fuzzy_match_score("HARV", "harv").is_some());
+    }
+
+    #[test]
+    fn characters_must_appear_in_order() {
+        assert!(fuzzy_match_score("inf", "^Infantry").is_some());
+        assert!(fuzzy_match_score("fni", "^Infantry").is_none());
+        assert!(fuzzy_match_score("tanks", "Tank").is_none());
+    }
+
+    #[test]
+    fn exact_match_scores_highest() {
+        // Arrange
+        let query = "mcv";
+
+        // Act
+        let exact = fuzzy_match_score(query, "MCV");
+        let prefix = fuzzy_match_score(query, "MCV.Husk");
+
+        // Assert
+        assert!(exact > prefix);
+    }
+
+    #[test]
+    fn word_starts_score_higher_than_inner_characters() {
+        // Arrange
+        let query = "pb";
+
+        // Act
+        let word_starts = fuzzy_match_score(query, "^PlayerBuilding");
+        let inner = fuzzy_match_score(query, "^Airpbase");
+
+        // Assert
+        assert!(word_starts > inner);
+    }
+
+    #[test]
+    fn consecutive_matches_score_higher_than_scattered_ones() {
+        // Arrange
+        let query = "tank";
+
+        // Act
+        let consecutive = fuzzy_match_score(query, "^Tank");
+        let scattered = fuzzy_match_score(query, "^TeslaAnk");
+
+        // Assert
+        assert!(consecutive > scattered);
+    }
+}
\ No newline at end of file
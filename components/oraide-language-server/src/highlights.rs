@@ -0,0 +1,199 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Indexing the identifiers in a file so that every occurrence of the one
+//! under a client's cursor can be highlighted
+
+use oraide_span::FileSpan;
+
+use oraide_sdk::RuleNode;
+
+use crate::{
+    references,
+};
+
+/// What an [`IdentifierOccurrence`] names
+///
+/// [`IdentifierOccurrence`]: struct.IdentifierOccurrence.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum IdentifierKind {
+    /// A top-level definition, such as an actor or a weapon
+    Definition,
+
+    /// A trait, not including any `@suffix`
+    Trait,
+
+    /// The `@suffix` of a trait instance, including the `@`
+    Suffix,
+}
+
+/// A single occurrence of an identifier in a file
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdentifierOccurrence {
+    pub kind: IdentifierKind,
+    pub name: String,
+    pub span: FileSpan,
+
+    /// Whether this occurrence defines `name`, as opposed to referring to
+    /// (or removing) it
+    pub is_definition: bool,
+}
+
+/// Find every occurrence of an identifier in `definitions`, which must all
+/// be from the same file
+///
+/// These are:
+/// - the key of each definition, and each reference to a definition
+/// - the trait name and `@suffix` of each trait key, with removals
+///   (`-Trait@suffix`) not considered definitions
+pub(crate) fn identifier_occurrences(definitions: &[RuleNode]) -> Vec<IdentifierOccurrence> {
+    let mut occurrences = vec![];
+
+    for definition in definitions {
+        let start = definition.key_span.start().to_usize();
+
+        occurrences.push(IdentifierOccurrence {
+            kind: IdentifierKind::Definition,
+            name: definition.key.clone(),
+            span: FileSpan::new(definition.key_span.source(), start, start + definition.key.len()),
+            is_definition: true,
+        });
+
+        let trait_nodes = definition.children.iter()
+            .filter(|trait_node| !trait_node.is_inherits());
+
+        for trait_node in trait_nodes {
+            occurrences.extend(trait_key_occurrences(trait_node));
+        }
+    }
+
+    let references = references::reference_items(definitions).into_iter()
+        .map(|(name, span)| IdentifierOccurrence {
+            kind: IdentifierKind::Definition,
+            name,
+            span,
+            is_definition: false,
+        });
+
+    occurrences.extend(references);
+    occurrences.sort_by_key(|occurrence| occurrence.span);
+    occurrences
+}
+
+/// Split the key of `trait_node` into its trait name and `@suffix`, if any
+pub(crate) fn trait_key_occurrences(trait_node: &RuleNode) -> Vec<IdentifierOccurrence> {
+    let is_removal = trait_node.is_removal();
+    let key = trait_node.key.trim_start_matches('-');
+    let key_start = trait_node.key_span.start().to_usize() + (trait_node.key.len() - key.len());
+    let source = trait_node.key_span.source();
+
+    let (trait_name, opt_suffix) = match key.find('@') {
+        Some(at_idx) => (&key[..at_idx], Some(&key[at_idx..])),
+        None => (key, None),
+    };
+
+    let mut occurrences = vec![];
+
+    if !trait_name.is_empty() {
+        occurrences.push(IdentifierOccurrence {
+            kind: IdentifierKind::Trait,
+            name: trait_name.to_owned(),
+            span: FileSpan::new(source, key_start, key_start + trait_name.len()),
+            is_definition: !is_removal,
+        });
+    }
+
+    if let Some(suffix) = opt_suffix {
+        let suffix_start = key_start + trait_name.len();
+
+        occurrences.push(IdentifierOccurrence {
+            kind: IdentifierKind::Suffix,
+            name: suffix.to_owned(),
+            span: FileSpan::new(source, suffix_start, suffix_start + suffix.len()),
+            is_definition: !is_removal,
+        });
+    }
+
+    occurrences
+}
+
+#[cfg(test)]
+mod tests {
+    use oraide_span::{
+        FileId,
+        FileSpan,
+    };
+
+    use oraide_parser_miniyaml::Parse;
+
+    use oraide_sdk::rule_nodes_from_tree;
+
+    use super::{
+        identifier_occurrences,
+        IdentifierKind,
+        IdentifierOccurrence,
+    };
+
+    fn occurrences_of(text: &str) -> Vec<IdentifierOccurrence> {
+        let parse = Parse::new(FileId(0), text);
+        let definitions = rule_nodes_from_tree(&parse.tree(), text);
+        identifier_occurrences(&definitions)
+    }
+
+    fn occurrence(kind: IdentifierKind, name: &str, start: usize, is_definition: bool) -> IdentifierOccurrence {
+        IdentifierOccurrence {
+            kind,
+            name: name.to_owned(),
+            span: FileSpan::new(FileId(0), start, start + name.len()),
+            is_definition,
+        }
+    }
+
+    #[test]
+    fn definitions_and_references_are_distinguished() {
+        // Arrange
+        let text = "^Infantry:\n\tHealth:\nE1:\n\tInherits: ^Infantry\n";
+
+        // Act
+        let actual = occurrences_of(text);
+
+        // Assert
+        assert_eq!(actual, vec![
+            occurrence(IdentifierKind::Definition, "^Infantry", 0, true),
+            occurrence(IdentifierKind::Trait, "Health", 12, true),
+            occurrence(IdentifierKind::Definition, "E1", 20, true),
+            occurrence(IdentifierKind::Definition, "^Infantry", 35, false),
+        ]);
+    }
+
+    #[test]
+    fn trait_keys_are_split_into_name_and_suffix() {
+        // Arrange
+        let text = "E1:\n\tArmament@PRIMARY:\n\t\tWeapon: M1Carbine\n\t-Armament@SECONDARY:\n";
+
+        // Act
+        let actual = occurrences_of(text);
+
+        // Assert
+        assert_eq!(actual, vec![
+            occurrence(IdentifierKind::Definition, "E1", 0, true),
+            occurrence(IdentifierKind::Trait, "Armament", 5, true),
+            occurrence(IdentifierKind::Suffix, "@PRIMARY", 13, true),
+            occurrence(IdentifierKind::Definition, "M1Carbine", 33, false),
+            occurrence(IdentifierKind::Trait, "Armament", 45, false),
+            occurrence(IdentifierKind::Suffix, "@SECONDARY", 53, false),
+        ]);
+    }
+}
\ No newline at end of file
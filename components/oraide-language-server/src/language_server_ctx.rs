@@ -15,31 +15,74 @@
 use {
     std::{
         fs::File,
-        path::PathBuf,
         io::Read as _,
+        collections::HashMap,
+        ops::Range,
+        path::{
+            Path,
+            PathBuf,
+        },
     },
     oraide_span::{
+        ByteIndex,
         FileId,
+        FileSpan,
     },
     oraide_actor::{
         Position,
         Symbol,
+        CompletionItem,
+        CompletionItemKind,
+        RangedFilePosition,
+        TextEdit,
+        CodeAction,
+        WorkspaceEdit,
+        WorkspaceSymbol,
+        SymbolKind,
+        FoldingRange,
+        DocumentHighlight,
+        DocumentHighlightKind,
+        SemanticToken,
     },
     oraide_parser_miniyaml::{
-        ParserCtx,
         TokenKind,
+        Diagnostic,
+        Label,
+        FormatOptions,
+        IndentStyle,
+    },
+    oraide_sdk::{
+        SdkCtx,
+        GameId,
+        ManifestSection,
+        Map,
+        MAP_FILE_NAME,
+        ResolvedActor,
+        Resolver,
     },
     crate::{
         types,
+        code_actions,
+        folding,
+        fuzzy,
+        highlights::{
+            self,
+            IdentifierKind,
+            IdentifierOccurrence,
+        },
+        references,
+        semantic_tokens,
+        validation,
     },
     url::Url,
 };
 
-#[salsa::query_group(LanguageServerCtxStorage)]
-pub trait LanguageServerCtx: ParserCtx {
-    #[salsa::input]
-    fn workspace_root(&self) -> Option<PathBuf>;
+/// The most symbols that a workspace symbol search will return, so that
+/// short queries do not flood the language client
+const MAX_WORKSPACE_SYMBOLS: usize = 256;
 
+#[salsa::query_group(LanguageServerCtxStorage)]
+pub trait LanguageServerCtx: SdkCtx {
     fn type_data(&self) -> Option<Vec<types::TraitDetail>>;
 
     fn documentation_lines_for_type_data(
@@ -59,6 +102,19 @@ pub trait LanguageServerCtx: ParserCtx {
         position: Position,
     ) -> Option<String>;
 
+    /// Render the fully resolved definition of the actor at `position` in
+    /// `file_id` as markdown
+    ///
+    /// # Returns
+    /// `None` if `file_id` is listed in a manifest section other than `Rules`,
+    /// or if `position` is not on a top-level key or on the value of an
+    /// `Inherits` node
+    fn resolved_actor_documentation_for_position_in_file(
+        &self,
+        file_id: FileId,
+        position: Position,
+    ) -> Option<String>;
+
     fn definition_position_in_file_path(
         &self,
         file_path: String,
@@ -71,11 +127,202 @@ pub trait LanguageServerCtx: ParserCtx {
         position: Position,
     ) -> Option<(Url, Position, Position)>;
 
+    /// The name, and its span, of the definition that is defined, or
+    /// referred to, at `position` in `file_id`
+    fn definition_name_and_span_at_position_in_file(
+        &self,
+        file_id: FileId,
+        position: Position,
+    ) -> Option<(String, FileSpan)>;
+
+    /// The name of the definition that is defined, or referred to, at
+    /// `position` in `file_id`
+    fn definition_name_at_position_in_file(
+        &self,
+        file_id: FileId,
+        position: Position,
+    ) -> Option<String>;
+
+    /// Find the key span of every top-level definition of `name` in the
+    /// workspace, ignoring the case of `name` like OpenRA does
+    fn definitions_of_name(
+        &self,
+        name: String,
+    ) -> Vec<FileSpan>;
+
+    /// Find every place in the workspace that refers to the definition `name`,
+    /// not including the definitions themselves
+    fn references_to_name(
+        &self,
+        name: String,
+    ) -> Vec<FileSpan>;
+
+    /// Find every place in the workspace that refers to the definition
+    /// defined, or referred to, at `position` in `file_id`
+    fn references_to(
+        &self,
+        file_id: FileId,
+        position: Position,
+    ) -> Option<Vec<FileSpan>>;
+
+    fn reference_positions_in_file_path(
+        &self,
+        file_path: String,
+        position: Position,
+        include_declarations: bool,
+    ) -> Option<Vec<RangedFilePosition>>;
+
+    /// The span of the definition name at `position` in `file_id` if that
+    /// definition exists and so can be renamed
+    fn renamable_span_at_position_in_file(
+        &self,
+        file_id: FileId,
+        position: Position,
+    ) -> Option<FileSpan>;
+
+    /// Compute the spans that must be replaced by `new_name` to rename the
+    /// definition at `position` in `file_id`, including the definitions
+    /// themselves
+    ///
+    /// Only the games that the definition is part of are affected, so a
+    /// definition of the same name in an unrelated game is left alone.
+    ///
+    /// # Returns
+    /// - `Ok` with no spans if `new_name` is the definition's current name
+    /// - `Err(message)` if the definition can not be renamed to `new_name`,
+    ///   such as when another definition named `new_name` exists in the same
+    ///   game
+    fn rename_spans(
+        &self,
+        file_id: FileId,
+        position: Position,
+        new_name: String,
+    ) -> Result<Vec<FileSpan>, String>;
+
+    fn rename_range_in_file_path(
+        &self,
+        file_path: String,
+        position: Position,
+    ) -> Option<(oraide_actor::Range<Position>, String)>;
+
+    fn workspace_edit_for_rename_in_file_path(
+        &self,
+        file_path: String,
+        position: Position,
+        new_name: String,
+    ) -> Result<WorkspaceEdit, String>;
+
+    /// Compute the edits that format `file_path`, or only the lines in
+    /// `line_range` if given
+    ///
+    /// The file's indentation style is kept if it can be detected, otherwise
+    /// `use_spaces` decides it.
+    fn formatting_edits_in_file_path(
+        &self,
+        file_path: String,
+        use_spaces: bool,
+        line_range: Option<Range<usize>>,
+    ) -> Option<Vec<TextEdit>>;
+
+    fn completions_for_position_in_file_path(
+        &self,
+        file_path: String,
+        position: Position,
+    ) -> Option<Vec<CompletionItem>>;
+
+    /// Compute the completions that make sense at `position` in `file_id`
+    ///
+    /// What is completed depends on where `position` is:
+    /// - trait names, when on the key of a node directly under a definition
+    /// - property names of the enclosing trait, when on the key of a node under a trait
+    /// - the valid values of the property, when on the value of such a node
+    /// - definition names, when on the value of an `Inherits` node
+    ///
+    /// Traits, properties and their values are only completed in rules files
+    /// (or files that no manifest lists).
+    fn completions_for_position_in_file(
+        &self,
+        file_id: FileId,
+        position: Position,
+    ) -> Option<Vec<CompletionItem>>;
+
+    /// Compute a symbol for every top-level node in `file_id`, including at
+    /// most `max_depth` levels of children below each of them
     fn symbols_in_file(
         &self,
         file_id: FileId,
-        top_level_only: bool,
+        max_depth: usize,
     ) -> Option<Vec<Symbol>>;
+
+    /// Compute a symbol for every top-level definition in the files listed
+    /// in a game's manifest
+    ///
+    /// Only files listed in the `Rules`, `Weapons`, `Sequences`, `Chrome` and
+    /// `ChromeLayout` sections are included.
+    fn workspace_symbol_definitions(&self) -> Vec<WorkspaceSymbol>;
+
+    /// Compute the ranges of lines in `file_id` that a language client may
+    /// fold: each node that has children and each run of comment-only lines
+    fn folding_ranges_in_file(
+        &self,
+        file_id: FileId,
+    ) -> Option<Vec<FoldingRange>>;
+
+    /// Compute every occurrence of an identifier in `file_id`
+    fn identifier_occurrences_in_file(
+        &self,
+        file_id: FileId,
+    ) -> Option<Vec<IdentifierOccurrence>>;
+
+    /// Compute the highlights of every occurrence, in `file_id`, of the
+    /// identifier at `position`
+    fn document_highlights_in_file(
+        &self,
+        file_id: FileId,
+        position: Position,
+    ) -> Option<Vec<DocumentHighlight>>;
+
+    /// Compute the semantic tokens of `file_id`, in document order
+    ///
+    /// Definitions are classified by the manifest section that lists
+    /// `file_id`, as for workspace symbols.  Traits and properties are only
+    /// checked against the type-data in rules files (or files that no
+    /// manifest lists).
+    fn semantic_tokens_in_file(
+        &self,
+        file_id: FileId,
+    ) -> Option<Vec<SemanticToken>>;
+
+    /// Compute the quick fixes for the problems in `file_id` that intersect
+    /// `range`
+    ///
+    /// Misspelled names and missing required traits are only fixed in rules
+    /// files, and only when the workspace has type-data.
+    fn code_actions_in_file(
+        &self,
+        file_id: FileId,
+        range: oraide_actor::Range<Position>,
+    ) -> Option<Vec<CodeAction>>;
+
+    /// Compute the diagnostics in `file_id` that can only be found by
+    /// inspecting the meaning of the document, not just its structure
+    fn semantic_diagnostics_in_file(
+        &self,
+        file_id: FileId,
+    ) -> Option<Vec<Diagnostic>>;
+
+    /// Compute all of the diagnostics, both parser and semantic, in `file_id`
+    fn diagnostics_in_file(
+        &self,
+        file_id: FileId,
+    ) -> Option<Vec<Diagnostic>>;
+
+    /// Compute all of the diagnostics in `file_id` in a form suitable for
+    /// sending to a language client
+    fn lsp_diagnostics_in_file(
+        &self,
+        file_id: FileId,
+    ) -> Option<Vec<oraide_actor::Diagnostic>>;
 }
 
 fn type_data(db: &impl LanguageServerCtx) -> Option<Vec<types::TraitDetail>> {
@@ -161,7 +408,7 @@ fn documentation_for_position_in_file_path(
     file_path: String,
     position: Position,
 ) -> Option<String> {
-    let file_id = match db.file_id_of_file_path(file_path.clone()) {
+    let file_id = match helpers::file_id_of_path_or_url(db, &file_path) {
         Some(id) => id,
         _ => {
             log::error!("No `FileId` found for file path `{}`", file_path);
@@ -177,6 +424,10 @@ fn documentation_for_position_in_file(
     file_id: FileId,
     position: Position,
 ) -> Option<String> {
+    if let Some(markdown) = db.resolved_actor_documentation_for_position_in_file(file_id, position) {
+        return Some(markdown);
+    }
+
     let file_text = match db.file_text(file_id) {
         Some(text) => text,
         _ => {
@@ -229,12 +480,64 @@ fn documentation_for_position_in_file(
     Some(joined_doc_lines)
 }
 
+fn resolved_actor_documentation_for_position_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+    position: Position,
+) -> Option<String> {
+    let file_text = db.file_text(file_id)?;
+    let byte_index = db.convert_position_to_byte_index(file_id, position)?;
+
+    if let Some(markdown) = helpers::map_actor_documentation(db, file_id, byte_index) {
+        return Some(markdown);
+    }
+
+    // Only the top-level nodes of rules files are actors, a file that no
+    // manifest lists is assumed to be a rules file
+    match helpers::manifest_section_of_file(db, file_id) {
+        None | Some(ManifestSection::Rules) => {},
+        _ => return None,
+    }
+
+    let node = db.node_spanning_byte_index_in_file(file_id, byte_index)?;
+    let key_text = node.key_text(&file_text)?;
+
+    let is_on_key = node.key_span().map_or(false, |span| span.contains(byte_index));
+    let is_on_value = node.value_span().map_or(false, |span| span.contains(byte_index));
+    let is_inherits = key_text == "Inherits" || key_text.starts_with("Inherits@");
+
+    let actor_name = if node.is_top_level() && is_on_key {
+        key_text
+    } else if is_inherits && is_on_value {
+        node.value_text(&file_text)?
+    } else {
+        return None;
+    };
+
+    // Inheritance can only be fully resolved in the context of a game, but a
+    // file that no game uses can still be resolved on its own.
+    let game_id = db.game_ids_of_rule_file(file_id)
+        .unwrap_or_default()
+        .into_iter()
+        .next();
+
+    let resolved_actor = match game_id {
+        Some(game_id) => db.resolved_actor(game_id, actor_name.to_owned())?,
+        _ => {
+            let definitions = db.rule_nodes_in_file(file_id)?;
+            Resolver::new(&definitions).resolve(actor_name)?
+        },
+    };
+
+    helpers::render_resolved_actor(db, actor_name, &resolved_actor).into()
+}
+
 fn definition_position_in_file_path(
     db: &impl LanguageServerCtx,
     file_path: String,
     position: Position,
 ) -> Option<(Url, Position, Position)> {
-    let file_id = match db.file_id_of_file_path(file_path.clone()) {
+    let file_id = match helpers::file_id_of_path_or_url(db, &file_path) {
         Some(id) => id,
         _ => {
             log::error!("No `FileId` found for file `{}`", file_path);
@@ -253,6 +556,12 @@ fn definition_position_in_file(
     let file_text = db.file_text(file_id)?;
     let byte_index = db.convert_position_to_byte_index(file_id, position)?;
 
+    if let Some(definition_span) = helpers::map_actor_definition_span(db, file_id, byte_index) {
+        let (start_pos, end_exclusive_pos) = db.convert_file_span_to_2_positions(definition_span)?;
+        let file_url = db.file_url(definition_span.source())?;
+        return Some((file_url, start_pos, end_exclusive_pos));
+    }
+
     // Get the entire `Node` so we can grab multiple `Token`s if necessary
     let node = db.node_spanning_byte_index_in_file(file_id, byte_index)?;
 
@@ -271,112 +580,1437 @@ fn definition_position_in_file(
         _ => token_text.into(),
     };
 
-    // TODO: Search _all_ relevant files, not just the explicitly-opened ones.
-    let file_ids = db.all_file_ids();
+    let definitions: Vec<_> = db.all_file_ids().into_iter()
+        .filter_map(|f_id| db.rule_nodes_in_file(f_id))
+        .flatten()
+        .filter(|definition| references::is_same_definition_name(&definition.key, &text_to_search_for))
+        .collect();
 
-    for f_id in file_ids {
-        if let Some(node) = db.top_level_node_by_key_in_file(f_id, text_to_search_for.clone()) {
-            let key_span = match node.key_span() {
-                Some(span) => span,
-                _ => continue,
-            };
+    // Names are compared case-insensitively, but an exact match is preferred
+    let key_span = definitions.iter()
+        .find(|definition| definition.key == text_to_search_for)
+        .or_else(|| definitions.first())?
+        .key_span;
 
-            let (start_pos, end_exclusive_pos) = match db.convert_file_span_to_2_positions(key_span) {
-                Some((s, e)) => (s, e),
-                _ => continue,
-            };
+    let (start_pos, end_exclusive_pos) = db.convert_file_span_to_2_positions(key_span)?;
+    let file_url = db.file_url(key_span.source())?;
+    Some((file_url, start_pos, end_exclusive_pos))
+}
+
+fn definition_name_and_span_at_position_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+    position: Position,
+) -> Option<(String, FileSpan)> {
+    let byte_index = db.convert_position_to_byte_index(file_id, position)?;
+    let rule_nodes = db.rule_nodes_in_file(file_id)?;
 
-            let file_path = db.file_path(f_id)?;
+    // Search the whole tree: a top-level key names a definition, as does any
+    // item of a value (which may be nested at any depth, such as under a
+    // trait or one of its properties).
+    let mut candidates: Vec<&oraide_sdk::RuleNode> = rule_nodes.iter().collect();
 
-            use std::str::FromStr as _;
-            let file_url = Url::from_str(&file_path).ok()?;
+    while let Some(node) = candidates.pop() {
+        if node.key_span.contains(byte_index) {
+            let is_top_level = rule_nodes.iter().any(|definition| definition.key_span == node.key_span);
+            return if is_top_level { Some((node.key.clone(), node.key_span)) } else { None };
+        }
 
-            return Some((file_url, start_pos, end_exclusive_pos));
+        if node.value_span.map_or(false, |span| span.contains(byte_index)) {
+            return references::value_item_at(node, byte_index);
         }
+
+        candidates.extend(node.children.iter());
     }
 
     None
 }
 
-fn symbols_in_file(
+fn definition_name_at_position_in_file(
     db: &impl LanguageServerCtx,
     file_id: FileId,
-    top_level_only: bool,
-) -> Option<Vec<Symbol>> {
-    let file_text = db.file_text(file_id)?;
-    let tree = db.file_tree(file_id)?;
+    position: Position,
+) -> Option<String> {
+    db.definition_name_and_span_at_position_in_file(file_id, position)
+        .map(|(name, _)| name)
+}
 
-    let top_level_arena_node_ids: Vec<_> = {
-        let iter = tree.node_ids.iter().skip(1); // skip the sentinel
-        let tups = iter.filter_map(|arena_node_id|
-            tree.arena.get(*arena_node_id)
-                .map(|shrd_arena_node| (arena_node_id, shrd_arena_node.get()))
-        );
+fn definitions_of_name(
+    db: &impl LanguageServerCtx,
+    name: String,
+) -> Vec<FileSpan> {
+    db.all_file_ids().into_iter()
+        .filter_map(|file_id| db.rule_nodes_in_file(file_id))
+        .flatten()
+        .filter(|definition| references::is_same_definition_name(&definition.key, &name))
+        .map(|definition| definition.key_span)
+        .collect()
+}
 
-        let tups = tups.filter(|(_arena_node_id, shrd_node)|
-            shrd_node.is_top_level() && shrd_node.has_key()
-        );
+fn references_to_name(
+    db: &impl LanguageServerCtx,
+    name: String,
+) -> Vec<FileSpan> {
+    let mut spans = vec![];
 
-        let tups = tups.map(|(arena_node_id, _shrd_node)| arena_node_id);
+    for file_id in db.all_file_ids() {
+        let definitions = db.rule_nodes_in_file(file_id).unwrap_or_default();
+        spans.extend(references::reference_spans(&definitions, &name));
+    }
 
-        tups.collect()
+    spans.sort();
+    spans.dedup();
+    spans
+}
+
+fn references_to(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+    position: Position,
+) -> Option<Vec<FileSpan>> {
+    let name = db.definition_name_at_position_in_file(file_id, position)?;
+    db.references_to_name(name).into()
+}
+
+fn reference_positions_in_file_path(
+    db: &impl LanguageServerCtx,
+    file_path: String,
+    position: Position,
+    include_declarations: bool,
+) -> Option<Vec<RangedFilePosition>> {
+    let file_id = match helpers::file_id_of_path_or_url(db, &file_path) {
+        Some(id) => id,
+        _ => {
+            log::error!("No `FileId` found for file path `{}`", file_path);
+            return None;
+        },
     };
 
-    let symbols: Vec<_> = top_level_arena_node_ids.into_iter()
-        .filter_map(|node_id| helpers::arena_node_id_to_sym(
-            db,
-            &file_text,
-            &tree.arena,
-            *node_id,
-            top_level_only,
-        )).collect();
+    let mut spans = db.references_to(file_id, position)?;
 
-    symbols.into()
+    if include_declarations {
+        let name = db.definition_name_at_position_in_file(file_id, position)?;
+        spans.extend(db.definitions_of_name(name));
+    }
+
+    let positions: Vec<_> = spans.into_iter()
+        .filter_map(|span| helpers::file_span_to_ranged_file_position(db, span))
+        .collect();
+
+    positions.into()
 }
 
-mod helpers {
-    use super::*;
+fn renamable_span_at_position_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+    position: Position,
+) -> Option<FileSpan> {
+    let (name, span) = db.definition_name_and_span_at_position_in_file(file_id, position)?;
 
-    pub(crate) fn arena_node_id_to_sym(
-        db: &impl LanguageServerCtx,
-        file_text: &'_ str,
-        shrd_arena: &oraide_parser_miniyaml::Arena,
-        arena_node_id: oraide_parser_miniyaml::ArenaNodeId,
-        top_level_only: bool,
-    ) -> Option<Symbol> {
-        let shrd_node = &shrd_arena.get(arena_node_id)?.get();
-        let name = shrd_node.key_text(file_text)?.to_owned();
-        let span = shrd_node.span()?;
-        let (start, end_exclusive) = db.convert_file_span_to_2_positions(span)?;
-        let range = oraide_actor::Range {
-            start,
-            end_exclusive,
-        };
+    // Values that merely look like a name, but are not, must not be renamed
+    if db.definitions_of_name(name).is_empty() {
+        return None;
+    }
 
-        let children = if top_level_only {
-            None
-        } else {
-            let children_iter = arena_node_id.children(&shrd_arena);
-            let children_syms: Vec<_> = children_iter.filter_map(|child_node_id|
-                arena_node_id_to_sym(
-                    db,
-                    file_text,
-                    shrd_arena,
-                    child_node_id,
-                    // Hardcoding to 1-level deep so we don't crowd the user's
-                    // UI with a ton of data.
-                    true,
-                )
-            ).collect();
+    Some(span)
+}
 
-            Some(children_syms)
+fn rename_spans(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+    position: Position,
+    new_name: String,
+) -> Result<Vec<FileSpan>, String> {
+    if db.renamable_span_at_position_in_file(file_id, position).is_none() {
+        return Err("Only definitions, and references to them, can be renamed".into());
+    }
+
+    let is_valid_name = !new_name.is_empty()
+        && !new_name.starts_with(|ch: char| ch == '-' || ch == '~' || ch == '!')
+        && !new_name.contains(|ch: char| ch.is_whitespace() || ch == ':' || ch == ',' || ch == '#');
+
+    if !is_valid_name {
+        return Err(format!("`{}` is not a valid definition name", new_name));
+    }
+
+    let name = db.definition_name_at_position_in_file(file_id, position)
+        .ok_or_else(|| "Only definitions, and references to them, can be renamed".to_owned())?;
+
+    if new_name == name {
+        return Ok(vec![]);
+    }
+
+    // Definitions are only visible to other definitions in the same game(s),
+    // so only rename the definitions that the file at `position` can see,
+    // and everything in the games that those definitions are part of.
+    // Files that are not part of any game can only see each other.
+    //
+    // Weapons are defined in the files of a manifest's `Weapons` section
+    // but referred to from its `Rules` files, so every section is considered.
+    let mut game_ids = helpers::game_ids_of_listed_file(db, file_id);
+
+    let visible_definition_spans: Vec<_> = db.definitions_of_name(name.clone()).into_iter()
+        .filter(|span| helpers::is_span_in_games(db, *span, &game_ids))
+        .collect();
+
+    for span in visible_definition_spans {
+        for game_id in helpers::game_ids_of_listed_file(db, span.source()) {
+            if !game_ids.contains(&game_id) {
+                game_ids.push(game_id);
+            }
+        }
+    }
+
+    // Changing only the case of the name can not conflict with anything but
+    // the definitions being renamed
+    let conflicting_span = db.definitions_of_name(new_name.clone()).into_iter()
+        .filter(|_| !references::is_same_definition_name(&name, &new_name))
+        .find(|span| helpers::is_span_in_games(db, *span, &game_ids));
+
+    if let Some(span) = conflicting_span {
+        let conflicting_game_id = helpers::game_ids_of_listed_file(db, span.source()).into_iter()
+            .find(|game_id| game_ids.contains(game_id));
+
+        return Err(match conflicting_game_id {
+            Some(game_id) => format!("A definition named `{}` already exists in game `{}`", new_name, &*game_id),
+            _ => format!("A definition named `{}` already exists", new_name),
+        });
+    }
+
+    let mut spans = db.definitions_of_name(name.clone());
+    spans.extend(db.references_to_name(name));
+    spans.retain(|span| helpers::is_span_in_games(db, *span, &game_ids));
+    spans.sort();
+    spans.dedup();
+
+    Ok(spans)
+}
+
+fn rename_range_in_file_path(
+    db: &impl LanguageServerCtx,
+    file_path: String,
+    position: Position,
+) -> Option<(oraide_actor::Range<Position>, String)> {
+    let file_id = helpers::file_id_of_path_or_url(db, &file_path)?;
+    let span = db.renamable_span_at_position_in_file(file_id, position)?;
+    let file_text = db.file_text(file_id)?;
+    let (start, end_exclusive) = db.convert_file_span_to_2_positions(span)?;
+
+    let placeholder = span.text(&file_text)?.to_owned();
+    let range = oraide_actor::Range {
+        start,
+        end_exclusive,
+    };
+
+    Some((range, placeholder))
+}
+
+fn workspace_edit_for_rename_in_file_path(
+    db: &impl LanguageServerCtx,
+    file_path: String,
+    position: Position,
+    new_name: String,
+) -> Result<WorkspaceEdit, String> {
+    let file_id = helpers::file_id_of_path_or_url(db, &file_path)
+        .ok_or_else(|| format!("No `FileId` found for file path `{}`", file_path))?;
+
+    let spans = db.rename_spans(file_id, position, new_name.clone())?;
+    let mut changes = Vec::<(Url, Vec<TextEdit>)>::new();
+
+    for span in spans {
+        let location = helpers::file_span_to_ranged_file_position(db, span)
+            .ok_or_else(|| format!("Failed to determine the location of `{:?}`", span))?;
+
+        let edit = TextEdit {
+            range: location.range,
+            new_text: new_name.clone(),
         };
 
-        Some(Symbol {
-            name,
-            detail: None,
-            children,
-            range,
-        })
+        match changes.iter_mut().find(|(file_url, _)| *file_url == location.file_url) {
+            Some((_, edits)) => edits.push(edit),
+            None => changes.push((location.file_url, vec![edit])),
+        }
+    }
+
+    Ok(WorkspaceEdit {
+        changes,
+    })
+}
+
+fn formatting_edits_in_file_path(
+    db: &impl LanguageServerCtx,
+    file_path: String,
+    use_spaces: bool,
+    line_range: Option<Range<usize>>,
+) -> Option<Vec<TextEdit>> {
+    let file_id = match helpers::file_id_of_path_or_url(db, &file_path) {
+        Some(id) => id,
+        _ => {
+            log::error!("No `FileId` found for file path `{}`", file_path);
+            return None;
+        },
+    };
+
+    // Keep the file's own indentation, only falling back to the client's
+    // preference for files that have no indented lines
+    let indent_style = db.indent_style_of_file(file_id)
+        .unwrap_or(if use_spaces { IndentStyle::Spaces } else { IndentStyle::Tabs });
+
+    let options = FormatOptions {
+        indent_style,
+        line_range,
+    };
+
+    let file_text = db.file_text(file_id)?;
+    let formatted_text = db.formatted_file_text(file_id, options)?;
+
+    if formatted_text == file_text {
+        return Some(vec![]);
+    }
+
+    // Replace the entire document since formatting may add or remove lines
+    let whole_span = FileSpan::new(file_id, 0, file_text.len());
+    let (start, end_exclusive) = db.convert_file_span_to_2_positions(whole_span)?;
+
+    Some(vec![
+        TextEdit {
+            range: oraide_actor::Range {
+                start,
+                end_exclusive,
+            },
+            new_text: formatted_text,
+        },
+    ])
+}
+
+fn completions_for_position_in_file_path(
+    db: &impl LanguageServerCtx,
+    file_path: String,
+    position: Position,
+) -> Option<Vec<CompletionItem>> {
+    let file_id = match helpers::file_id_of_path_or_url(db, &file_path) {
+        Some(id) => id,
+        _ => {
+            log::error!("No `FileId` found for file path `{}`", file_path);
+            return None;
+        },
+    };
+
+    db.completions_for_position_in_file(file_id, position)
+}
+
+fn completions_for_position_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+    position: Position,
+) -> Option<Vec<CompletionItem>> {
+    let file_text = db.file_text(file_id)?;
+    let line_start_offsets = db.line_start_offsets(file_id)?;
+    let byte_index = db.convert_position_to_byte_index(file_id, position)?;
+
+    // The line being completed is likely incomplete, and so may not parse
+    // into the node the user has in mind, so inspect the text directly.
+    let line_start = *line_start_offsets.get(position.line_idx)?;
+    let line_text_before_position = file_text.get(line_start..byte_index.to_usize())?;
+    let indentation_level = code_actions::indentation_level(line_text_before_position);
+    let trimmed_text = line_text_before_position.trim_start();
+
+    let is_rules_file = match helpers::manifest_section_of_file(db, file_id) {
+        None | Some(ManifestSection::Rules) => true,
+        _ => false,
+    };
+
+    if let Some(colon_idx) = trimmed_text.find(':') {
+        let key = trimmed_text[..colon_idx].trim();
+
+        if key == "Inherits" || key.starts_with("Inherits@") {
+            let definition_name = helpers::enclosing_key(&file_text, &line_start_offsets, position.line_idx, 0);
+            return helpers::definition_name_completions(db, file_id, definition_name).into();
+        }
+
+        if indentation_level != 2 || !is_rules_file {
+            return None;
+        }
+
+        let trait_key = helpers::enclosing_key(&file_text, &line_start_offsets, position.line_idx, 1)?;
+        let trait_detail = helpers::trait_detail(db, validation::trait_name(trait_key))?;
+        let property = trait_detail.properties.into_iter().find(|prop| prop.name == key)?;
+
+        let items: Vec<_> = property.valid_values?.into_iter()
+            .map(|value| CompletionItem {
+                label: value,
+                kind: CompletionItemKind::Value,
+                detail: None,
+                documentation: None,
+            })
+            .collect();
+
+        return items.into();
+    }
+
+    if !is_rules_file {
+        return None;
+    }
+
+    match indentation_level {
+        1 => {
+            let items: Vec<_> = db.type_data()?.into_iter()
+                .map(|trait_detail| CompletionItem {
+                    label: trait_detail.name,
+                    kind: CompletionItemKind::Trait,
+                    detail: None,
+                    documentation: trait_detail.doc_lines.map(|lines| lines.join("\n")),
+                })
+                .collect();
+
+            items.into()
+        },
+        2 => {
+            let trait_key = helpers::enclosing_key(&file_text, &line_start_offsets, position.line_idx, 1)?;
+            let trait_detail = helpers::trait_detail(db, validation::trait_name(trait_key))?;
+
+            let items: Vec<_> = trait_detail.properties.into_iter()
+                .map(|property| CompletionItem {
+                    label: property.name,
+                    kind: CompletionItemKind::Property,
+                    detail: Some(property.human_friendly_type_name),
+                    documentation: property.doc_lines.map(|lines| lines.join("\n")),
+                })
+                .collect();
+
+            items.into()
+        },
+        _ => None,
+    }
+}
+
+fn symbols_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+    max_depth: usize,
+) -> Option<Vec<Symbol>> {
+    let file_text = db.file_text(file_id)?;
+    let tree = db.file_tree(file_id)?;
+    let section = helpers::manifest_section_of_file(db, file_id);
+
+    let top_level_arena_node_ids: Vec<_> = {
+        let iter = tree.node_ids.iter().skip(1); // skip the sentinel
+        let tups = iter.filter_map(|arena_node_id|
+            tree.arena.get(*arena_node_id)
+                .map(|shrd_arena_node| (arena_node_id, shrd_arena_node.get()))
+        );
+
+        let tups = tups.filter(|(_arena_node_id, shrd_node)|
+            shrd_node.is_top_level() && shrd_node.has_key()
+        );
+
+        let tups = tups.map(|(arena_node_id, _shrd_node)| arena_node_id);
+
+        tups.collect()
+    };
+
+    let symbols: Vec<_> = top_level_arena_node_ids.into_iter()
+        .filter_map(|node_id| helpers::arena_node_id_to_sym(
+            db,
+            &file_text,
+            &tree.arena,
+            *node_id,
+            section,
+            None,
+            max_depth,
+        )).collect();
+
+    symbols.into()
+}
+
+fn workspace_symbol_definitions(
+    db: &impl LanguageServerCtx,
+) -> Vec<WorkspaceSymbol> {
+    let listed_files = db.manifest_listed_files().unwrap_or_default();
+
+    // A file may be listed by several games, so group the games by file
+    let mut file_listings: Vec<(FileId, ManifestSection, Vec<GameId>)> = vec![];
+
+    for (file_id, game_id, section) in listed_files {
+        match file_listings.iter_mut().find(|(listed_file_id, _, _)| *listed_file_id == file_id) {
+            Some((_, _, game_ids)) => {
+                if !game_ids.contains(&game_id) {
+                    game_ids.push(game_id);
+                }
+            },
+            _ => file_listings.push((file_id, section, vec![game_id])),
+        }
+    }
+
+    let top_level_nodes_in_all_files = db.top_level_nodes_in_all_files().unwrap_or_default();
+    let mut symbols = vec![];
+
+    for (file_id, top_level_nodes) in top_level_nodes_in_all_files {
+        let (section, game_ids) = match file_listings.iter().find(|(listed_file_id, _, _)| *listed_file_id == file_id) {
+            Some((_, section, game_ids)) => (*section, game_ids),
+            _ => continue,
+        };
+
+        let file_text = match db.file_text(file_id) {
+            Some(text) => text,
+            _ => continue,
+        };
+
+        let container_name = game_ids.iter()
+            .map(|game_id| &**game_id)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        for node in top_level_nodes {
+            let (name, key_span) = match (node.key_text(&file_text), node.key_span()) {
+                (Some(name), Some(span)) => (name, span),
+                _ => continue,
+            };
+
+            let kind = match helpers::workspace_symbol_kind(section, name) {
+                Some(kind) => kind,
+                _ => continue,
+            };
+
+            let location = match helpers::file_span_to_ranged_file_position(db, key_span) {
+                Some(location) => location,
+                _ => continue,
+            };
+
+            symbols.push(WorkspaceSymbol {
+                name: name.to_owned(),
+                kind,
+                location,
+                container_name: Some(container_name.clone()),
+            });
+        }
+    }
+
+    symbols
+}
+
+/// Compute the workspace symbols that fuzzy-match `query`, best matches first
+///
+/// This is not a query since `query` changes with every keystroke, so
+/// memoizing by it would only grow the database; the symbols themselves are
+/// memoized by [`workspace_symbol_definitions`].
+///
+/// [`workspace_symbol_definitions`]: trait.LanguageServerCtx.html#tymethod.workspace_symbol_definitions
+pub fn workspace_symbols(
+    db: &impl LanguageServerCtx,
+    query: &str,
+) -> Vec<WorkspaceSymbol> {
+    let mut scored_symbols: Vec<_> = db.workspace_symbol_definitions().into_iter()
+        .filter_map(|sym| fuzzy::fuzzy_match_score(query, &sym.name).map(|score| (score, sym)))
+        .collect();
+
+    scored_symbols.sort_by(|(score_a, sym_a), (score_b, sym_b)|
+        score_b.cmp(score_a)
+            .then_with(|| sym_a.name.len().cmp(&sym_b.name.len()))
+            .then_with(|| sym_a.cmp(sym_b))
+    );
+
+    scored_symbols.into_iter()
+        .take(MAX_WORKSPACE_SYMBOLS)
+        .map(|(_score, sym)| sym)
+        .collect()
+}
+
+fn folding_ranges_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+) -> Option<Vec<FoldingRange>> {
+    let tree = db.file_tree(file_id)?;
+    let nodes = db.file_nodes(file_id)?;
+    let line_start_offsets = db.line_start_offsets(file_id)?;
+    folding::folding_ranges(&tree, &nodes, &line_start_offsets).into()
+}
+
+fn identifier_occurrences_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+) -> Option<Vec<IdentifierOccurrence>> {
+    let definitions = db.rule_nodes_in_file(file_id)?;
+    highlights::identifier_occurrences(&definitions).into()
+}
+
+fn document_highlights_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+    position: Position,
+) -> Option<Vec<DocumentHighlight>> {
+    let byte_index = db.convert_position_to_byte_index(file_id, position)?;
+    let token = db.token_spanning_byte_index_in_file(file_id, byte_index)?;
+    let occurrences = db.identifier_occurrences_in_file(file_id)?;
+
+    let (kind, name) = occurrences.iter()
+        .find(|occurrence| occurrence.span.contains_span(token.span))
+        .map(|occurrence| (occurrence.kind, occurrence.name.clone()))?;
+
+    let highlights: Vec<_> = occurrences.into_iter()
+        .filter(|occurrence| occurrence.kind == kind)
+        .filter(|occurrence| match kind {
+            IdentifierKind::Definition => references::is_same_definition_name(&occurrence.name, &name),
+            _ => occurrence.name == name,
+        })
+        .filter_map(|occurrence| {
+            let (start, end_exclusive) = db.convert_file_span_to_2_positions(occurrence.span)?;
+
+            Some(DocumentHighlight {
+                range: oraide_actor::Range {
+                    start,
+                    end_exclusive,
+                },
+                kind: if occurrence.is_definition {
+                    DocumentHighlightKind::Definition
+                } else {
+                    DocumentHighlightKind::Reference
+                },
+            })
+        })
+        .collect();
+
+    highlights.into()
+}
+
+fn semantic_tokens_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+) -> Option<Vec<SemanticToken>> {
+    let definitions = db.rule_nodes_in_file(file_id)?;
+    let tokens = db.file_tokens(file_id)?;
+
+    let section = helpers::manifest_section_of_file(db, file_id);
+    let type_data = match section {
+        None | Some(ManifestSection::Rules) => db.type_data(),
+        _ => None,
+    };
+
+    let semantic_tokens: Vec<_> = semantic_tokens::classify(section, type_data.as_ref().map(Vec::as_slice), &definitions, &tokens)
+        .into_iter()
+        .filter_map(|classified| {
+            let (start, end_exclusive) = db.convert_file_span_to_2_positions(classified.span)?;
+
+            Some(SemanticToken {
+                range: oraide_actor::Range {
+                    start,
+                    end_exclusive,
+                },
+                token_type: classified.token_type,
+                is_declaration: classified.is_declaration,
+            })
+        })
+        .collect();
+
+    semantic_tokens.into()
+}
+
+fn code_actions_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+    range: oraide_actor::Range<Position>,
+) -> Option<Vec<CodeAction>> {
+    let file_url = db.file_url(file_id)?;
+    let file_text = db.file_text(file_id)?;
+    let line_start_offsets = db.line_start_offsets(file_id)?;
+
+    let range_start = db.convert_position_to_byte_index(file_id, range.start)?.to_usize();
+    let range_end = db.convert_position_to_byte_index(file_id, range.end_exclusive)
+        .map(|byte_index| byte_index.to_usize())
+        .unwrap_or_else(|| file_text.len());
+
+    let nodes = db.file_nodes(file_id).unwrap_or_default();
+    let indent_style = IndentStyle::detect(&nodes, &file_text).unwrap_or(IndentStyle::Tabs);
+
+    let diagnostics = db.file_diagnostics(file_id).unwrap_or_default();
+    let mut fixes = code_actions::indentation_fixes(&diagnostics, indent_style, &file_text, &line_start_offsets);
+
+    if let Some(tree) = db.file_tree(file_id) {
+        let duplicate_node_ids = helpers::duplicate_key_node_ids(&tree, &file_text);
+        fixes.extend(code_actions::duplicate_key_fixes(file_id, &tree, &duplicate_node_ids, &file_text, &line_start_offsets));
+    }
+
+    let game_ids = db.game_ids_of_rule_file(file_id).unwrap_or_default();
+    let type_data = if game_ids.is_empty() { None } else { db.type_data() };
+
+    if let Some(type_data) = &type_data {
+        let rule_nodes = db.rule_nodes_in_file(file_id).unwrap_or_default();
+
+        for rule_node in rule_nodes.iter() {
+            fixes.extend(code_actions::misspelling_fixes(type_data, rule_node));
+        }
+
+        let resolved_actors = |actor_name: &str| game_ids.iter()
+            .filter_map(|game_id| db.resolved_actor(game_id.clone(), actor_name.to_owned()))
+            .collect::<Vec<_>>();
+
+        fixes.extend(code_actions::missing_required_trait_fixes(type_data, &rule_nodes, resolved_actors, indent_style, &file_text, &line_start_offsets));
+    }
+
+    let actions: Vec<_> = fixes.into_iter()
+        .filter(|fix| {
+            fix.trigger_span.start().to_usize() <= range_end
+                && range_start <= fix.trigger_span.end_exclusive().to_usize()
+        })
+        .filter_map(|fix| {
+            let mut edits = vec![];
+
+            for (span, new_text) in fix.edits {
+                let (start, end_exclusive) = db.convert_file_span_to_2_positions(span)?;
+
+                edits.push(TextEdit {
+                    range: oraide_actor::Range {
+                        start,
+                        end_exclusive,
+                    },
+                    new_text,
+                });
+            }
+
+            Some(CodeAction {
+                title: fix.title,
+                edit: WorkspaceEdit {
+                    changes: vec![(file_url.clone(), edits)],
+                },
+            })
+        })
+        .collect();
+
+    actions.into()
+}
+
+fn semantic_diagnostics_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+) -> Option<Vec<Diagnostic>> {
+    let file_text = db.file_text(file_id)?;
+    let tree = db.file_tree(file_id)?;
+
+    let is_map_file = helpers::is_map_file(db, file_id);
+    let mut diagnostics = vec![];
+
+    for (first_node_id, duplicate_node_id) in helpers::duplicate_key_node_ids(&tree, &file_text) {
+        // Duplicate actors are reported by the map's own validation
+        if is_map_file && helpers::is_map_actor_node(&tree, &file_text, duplicate_node_id) {
+            continue;
+        }
+
+        let (shrd_first_node, shrd_duplicate_node) = match (tree.arena.get(first_node_id), tree.arena.get(duplicate_node_id)) {
+            (Some(first), Some(duplicate)) => (first.get(), duplicate.get()),
+            _ => continue,
+        };
+
+        let (key_text, key_span, first_key_span) = match (
+            shrd_duplicate_node.key_text(&file_text),
+            shrd_duplicate_node.key_span(),
+            shrd_first_node.key_span(),
+        ) {
+            (Some(text), Some(span), Some(first_span)) => (text, span, first_span),
+            _ => continue,
+        };
+
+        diagnostics.push(
+            Diagnostic::new_error(format!("Duplicate key `{}`", key_text))
+                .with_code("S:E0001")
+                .with_label(Label::new_primary(key_span))
+                .with_label(
+                    Label::new_secondary(first_key_span)
+                        .with_message("first defined here")
+                )
+                .with_help("Keys must be unique amongst their siblings, consider removing or renaming one of these nodes")
+        );
+    }
+
+    // Inheritance can only be resolved in the context of a game, so resolve
+    // the definitions in this file for each game that uses it.
+    let game_ids = db.game_ids_of_rule_file(file_id).unwrap_or_default();
+    let rule_nodes = db.rule_nodes_in_file(file_id).unwrap_or_default();
+
+    // Traits only appear in rules files and can only be validated when the
+    // workspace has type-data
+    let type_data = if game_ids.is_empty() { None } else { db.type_data() };
+
+    if let Some(type_data) = &type_data {
+        for rule_node in rule_nodes.iter() {
+            diagnostics.extend(validation::validate_definition(type_data, rule_node));
+        }
+    }
+
+    for game_id in game_ids {
+        for rule_node in rule_nodes.iter() {
+            let resolved_actor = match db.resolved_actor(game_id.clone(), rule_node.key.clone()) {
+                Some(resolved) => resolved,
+                _ => continue,
+            };
+
+            let mut resolution_diagnostics = resolved_actor.diagnostics;
+
+            // Abstract definitions (such as `^Infantry`) are never created in
+            // game so they needn't have the traits their traits require
+            if let Some(type_data) = &type_data {
+                if !rule_node.key.starts_with('^') {
+                    resolution_diagnostics.extend(validation::validate_required_traits(type_data, &resolved_actor.node));
+                }
+            }
+
+            // Problems found in other files will be reported by those files
+            let resolution_diagnostics = resolution_diagnostics.into_iter()
+                .filter(|diag| diag.primary_span().map(|span| span.source()) == Some(file_id));
+
+            for diag in resolution_diagnostics {
+                if !diagnostics.contains(&diag) {
+                    diagnostics.push(diag);
+                }
+            }
+        }
+    }
+
+    // A map's actors are checked against the rules of a game it is played in
+    if is_map_file {
+        if let Some(map) = db.map_in_file(file_id) {
+            let definitions = helpers::game_ids_of_map(db, file_id, &map).into_iter()
+                .filter_map(|game_id| db.map_rule_definitions(game_id, file_id))
+                .next();
+
+            diagnostics.extend(map.validate(definitions.as_ref().map(Vec::as_slice)));
+        }
+    }
+
+    // A manifest's paths are checked against the workspace on disk
+    for game in db.all_games().unwrap_or_default() {
+        let game_id = GameId::from(game.id().to_owned());
+        let manifest_file_id = db.manifest_path(game_id.clone())
+            .and_then(|manifest_path| db.file_id_of_disk_path(manifest_path));
+
+        if manifest_file_id == Some(file_id) {
+            diagnostics.extend(db.manifest_diagnostics(game_id).unwrap_or_default());
+        }
+    }
+
+    diagnostics.into()
+}
+
+fn diagnostics_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+) -> Option<Vec<Diagnostic>> {
+    let mut diagnostics = db.file_diagnostics(file_id)?;
+    diagnostics.extend(db.semantic_diagnostics_in_file(file_id)?);
+    diagnostics.into()
+}
+
+fn lsp_diagnostics_in_file(
+    db: &impl LanguageServerCtx,
+    file_id: FileId,
+) -> Option<Vec<oraide_actor::Diagnostic>> {
+    let diagnostics = db.diagnostics_in_file(file_id)?;
+
+    let lsp_diagnostics: Vec<_> = diagnostics.into_iter()
+        .filter_map(|diag| helpers::diagnostic_to_lsp_diagnostic(db, diag))
+        .collect();
+
+    lsp_diagnostics.into()
+}
+
+mod helpers {
+    use super::*;
+
+    /// Find the `FileId` of `file_path`, which may be either a path or a URL
+    pub(crate) fn file_id_of_path_or_url(
+        db: &impl LanguageServerCtx,
+        file_path: &str,
+    ) -> Option<FileId> {
+        if let Some(file_id) = db.file_id_of_file_path(file_path.to_owned()) {
+            return Some(file_id);
+        }
+
+        let url = Url::parse(file_path).ok()?;
+        db.file_id_of_url(url)
+    }
+
+    /// The games whose manifest lists `file_id`, in any section
+    pub(crate) fn game_ids_of_listed_file(
+        db: &impl LanguageServerCtx,
+        file_id: FileId,
+    ) -> Vec<GameId> {
+        let mut game_ids: Vec<_> = db.manifest_listed_files()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(listed_file_id, _, _)| *listed_file_id == file_id)
+            .map(|(_, game_id, _)| game_id)
+            .collect();
+
+        game_ids.dedup();
+        game_ids
+    }
+
+    /// Whether `span` is in a file listed by any of `game_ids`, or, if
+    /// `game_ids` is empty, in a file that no game lists
+    pub(crate) fn is_span_in_games(
+        db: &impl LanguageServerCtx,
+        span: FileSpan,
+        game_ids: &[GameId],
+    ) -> bool {
+        let span_game_ids = game_ids_of_listed_file(db, span.source());
+
+        if game_ids.is_empty() {
+            span_game_ids.is_empty()
+        } else {
+            span_game_ids.iter().any(|game_id| game_ids.contains(game_id))
+        }
+    }
+
+    /// Render the traits of `resolved_actor` as a markdown list, noting where
+    /// each trait was (most recently) defined and which parent it came from
+    pub(crate) fn render_resolved_actor(
+        db: &impl LanguageServerCtx,
+        actor_name: &str,
+        resolved_actor: &ResolvedActor,
+    ) -> String {
+        let mut lines = vec![
+            format!("**`{}`**", actor_name),
+            String::new(),
+        ];
+
+        for trait_node in resolved_actor.node.children.iter() {
+            lines.push(format!("- {}", describe_rule_node(db, trait_node)));
+        }
+
+        if !resolved_actor.removed.is_empty() {
+            lines.push(String::new());
+            lines.push("Removed:".into());
+
+            for removed_node in resolved_actor.removed.iter() {
+                lines.push(format!("- ~~{}~~", describe_rule_node(db, removed_node)));
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    fn describe_rule_node(
+        db: &impl LanguageServerCtx,
+        rule_node: &oraide_sdk::RuleNode,
+    ) -> String {
+        let mut description = format!("`{}`", rule_node.key);
+
+        if let Some(location) = display_file_span(db, rule_node.key_span) {
+            description.push_str(&format!(" ({})", location));
+        }
+
+        if let Some(parent_name) = &rule_node.inherited_from {
+            description.push_str(&format!(" from `{}`", parent_name));
+        }
+
+        description
+    }
+
+    /// Format the start of `span` as `path:line`, with `path` relative to the
+    /// workspace root when possible
+    pub(crate) fn display_file_span(
+        db: &impl LanguageServerCtx,
+        span: FileSpan,
+    ) -> Option<String> {
+        let file_id = span.source();
+        let location = db.convert_byte_index_to_location(file_id, span.start())?;
+        let file_path = db.file_path(file_id)?;
+
+        // Files opened by a language client are tracked by URL
+        let path = match Url::parse(&file_path) {
+            Ok(url) => url.to_file_path().ok()?,
+            _ => PathBuf::from(file_path),
+        };
+
+        let relative_path = db.workspace_root()
+            .and_then(|root| path.strip_prefix(root).ok().map(Path::to_path_buf))
+            .unwrap_or_else(|| path.clone());
+
+        Some(format!("{}:{}", relative_path.display(), location.line_number))
+    }
+
+    /// The kind of the top-level definition `name` in a file listed in
+    /// `section` of a manifest
+    ///
+    /// # Returns
+    /// `None` if definitions in `section` are not workspace symbols
+    pub(crate) fn workspace_symbol_kind(
+        section: ManifestSection,
+        name: &str,
+    ) -> Option<SymbolKind> {
+        let kind = match section {
+            ManifestSection::Rules if name.starts_with('^') => SymbolKind::Abstract,
+            ManifestSection::Rules => SymbolKind::Actor,
+            ManifestSection::Weapons => SymbolKind::Weapon,
+            ManifestSection::Sequences => SymbolKind::Sequence,
+            ManifestSection::Chrome
+            | ManifestSection::ChromeLayout => SymbolKind::ChromeWidget,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    /// Find each node in `tree` whose key is the same as that of an earlier
+    /// sibling
+    ///
+    /// # Returns
+    /// The ID of the earlier sibling and the ID of the duplicate, for each
+    /// duplicate
+    pub(crate) fn duplicate_key_node_ids(
+        tree: &oraide_parser_miniyaml::Tree,
+        file_text: &str,
+    ) -> Vec<(oraide_parser_miniyaml::ArenaNodeId, oraide_parser_miniyaml::ArenaNodeId)> {
+        let mut duplicates = vec![];
+
+        // Skip the sentinel, its children are the nodes whose parent could not be
+        // determined so they aren't actually siblings of each other.
+        for parent_node_id in tree.node_ids.iter().skip(1) {
+            let mut first_node_ids = HashMap::<&str, oraide_parser_miniyaml::ArenaNodeId>::new();
+
+            for child_node_id in parent_node_id.children(&tree.arena) {
+                let key_text = match tree.arena.get(child_node_id).and_then(|shrd_arena_node| shrd_arena_node.get().key_text(file_text)) {
+                    Some(text) => text,
+                    _ => continue,
+                };
+
+                match first_node_ids.get(key_text) {
+                    Some(first_node_id) => duplicates.push((*first_node_id, child_node_id)),
+                    None => {
+                        first_node_ids.insert(key_text, child_node_id);
+                    },
+                }
+            }
+        }
+
+        duplicates
+    }
+
+    /// Whether `file_id` describes a map, see [`MAP_FILE_NAME`]
+    ///
+    /// [`MAP_FILE_NAME`]: ../oraide_sdk/constant.MAP_FILE_NAME.html
+    pub(crate) fn is_map_file(
+        db: &impl LanguageServerCtx,
+        file_id: FileId,
+    ) -> bool {
+        db.file_path(file_id)
+            .map_or(false, |file_path| {
+                Path::new(&file_path).file_name().and_then(|name| name.to_str()) == Some(MAP_FILE_NAME)
+            })
+    }
+
+    /// Whether the node `arena_node_id` is an entry in the top-level `Actors`
+    /// node of a map
+    pub(crate) fn is_map_actor_node(
+        tree: &oraide_parser_miniyaml::Tree,
+        file_text: &str,
+        arena_node_id: oraide_parser_miniyaml::ArenaNodeId,
+    ) -> bool {
+        tree.arena.get(arena_node_id)
+            .and_then(|shrd_arena_node| shrd_arena_node.parent())
+            .and_then(|parent_node_id| tree.arena.get(parent_node_id))
+            .map(|shrd_arena_node| shrd_arena_node.get())
+            .map_or(false, |shrd_parent_node| {
+                shrd_parent_node.is_top_level()
+                    && shrd_parent_node.key_text(file_text).map(str::trim) == Some("Actors")
+            })
+    }
+
+    /// The games that `map`, in `file_id`, may be played in, with the game
+    /// it requires (`RequiresMod`) first
+    pub(crate) fn game_ids_of_map(
+        db: &impl LanguageServerCtx,
+        file_id: FileId,
+        map: &Map,
+    ) -> Vec<GameId> {
+        let mut game_ids = db.game_ids_of_map_file(file_id).unwrap_or_default();
+
+        if let Some(required_game_id) = map.requires_mod.clone().map(GameId::from) {
+            game_ids.retain(|game_id| *game_id != required_game_id);
+
+            if db.game_dir(required_game_id.clone()).is_some() {
+                game_ids.insert(0, required_game_id);
+            }
+        }
+
+        game_ids
+    }
+
+    /// Find the definition of the type of the map actor whose type is
+    /// written at `byte_index` in `file_id`
+    ///
+    /// A game's rules are preferred over the map's own `Rules`, since those
+    /// typically only tweak an existing definition.
+    pub(crate) fn map_actor_definition_span(
+        db: &impl LanguageServerCtx,
+        file_id: FileId,
+        byte_index: ByteIndex,
+    ) -> Option<FileSpan> {
+        if !is_map_file(db, file_id) {
+            return None;
+        }
+
+        let map = db.map_in_file(file_id)?;
+        let actor = map.actor_with_type_at(byte_index)?;
+
+        for game_id in game_ids_of_map(db, file_id, &map) {
+            let definitions = db.game_rule_definitions(game_id).unwrap_or_default();
+
+            if let Some(definition) = Map::definition_for_actor_type(&definitions, &actor.actor_type) {
+                return Some(definition.key_span);
+            }
+        }
+
+        Map::definition_for_actor_type(&map.rules, &actor.actor_type)
+            .map(|definition| definition.key_span)
+    }
+
+    /// Render the resolved definition, including the map's own `Rules`, of
+    /// the map actor whose type is written at `byte_index` in `file_id`
+    pub(crate) fn map_actor_documentation(
+        db: &impl LanguageServerCtx,
+        file_id: FileId,
+        byte_index: ByteIndex,
+    ) -> Option<String> {
+        if !is_map_file(db, file_id) {
+            return None;
+        }
+
+        let map = db.map_in_file(file_id)?;
+        let actor = map.actor_with_type_at(byte_index)?;
+
+        // A map that no game uses can still be resolved on its own
+        let resolved_actor = game_ids_of_map(db, file_id, &map).into_iter()
+            .filter_map(|game_id| db.resolved_map_actor(game_id, file_id, actor.actor_type.clone()))
+            .next()
+            .or_else(|| {
+                let definition = Map::definition_for_actor_type(&map.rules, &actor.actor_type)?;
+                Resolver::new(&map.rules).resolve(&definition.key)
+            })?;
+
+        let mut markdown = render_resolved_actor(db, &resolved_actor.node.key, &resolved_actor);
+        markdown.push_str(&format!("\n\nPlaced as `{}`", actor.name));
+
+        if let Some(owner) = &actor.owner {
+            markdown.push_str(&format!(", owned by `{}`", owner));
+        }
+
+        if let Some((x, y)) = actor.location {
+            markdown.push_str(&format!(", at `{},{}`", x, y));
+        }
+
+        Some(markdown)
+    }
+
+    /// The section of a game's manifest that lists `file_id`, if any
+    pub(crate) fn manifest_section_of_file(
+        db: &impl LanguageServerCtx,
+        file_id: FileId,
+    ) -> Option<ManifestSection> {
+        db.manifest_listed_files()?.into_iter()
+            .find(|(listed_file_id, _, _)| *listed_file_id == file_id)
+            .map(|(_, _, section)| section)
+    }
+
+    pub(crate) fn file_span_to_ranged_file_position(
+        db: &impl LanguageServerCtx,
+        span: FileSpan,
+    ) -> Option<RangedFilePosition> {
+        let file_url = db.file_url(span.source())?;
+        let (start, end_exclusive) = db.convert_file_span_to_2_positions(span)?;
+        RangedFilePosition::new_from_components(file_url, start, end_exclusive).into()
+    }
+
+    /// Find the key of the closest node, before line `line_idx`, at
+    /// indentation level `level`
+    ///
+    /// # Returns
+    /// `None` if a node with a lower indentation level is found first
+    pub(crate) fn enclosing_key<'text>(
+        file_text: &'text str,
+        line_start_offsets: &[usize],
+        line_idx: usize,
+        level: usize,
+    ) -> Option<&'text str> {
+        for idx in (0..line_idx).rev() {
+            let line_text = file_text.get(line_start_offsets[idx]..line_start_offsets[idx + 1])?;
+            let trimmed_text = line_text.trim();
+
+            if trimmed_text.is_empty() || trimmed_text.starts_with('#') {
+                continue;
+            }
+
+            let line_level = code_actions::indentation_level(line_text);
+
+            if line_level == level {
+                let key = trimmed_text.split(':').next().unwrap_or(trimmed_text);
+                return Some(key.trim());
+            }
+
+            if line_level < level {
+                return None;
+            }
+        }
+
+        None
+    }
+
+    pub(crate) fn trait_detail(
+        db: &impl LanguageServerCtx,
+        trait_name: &str,
+    ) -> Option<types::TraitDetail> {
+        db.type_data()?.into_iter().find(|detail| detail.name == trait_name)
+    }
+
+    /// Complete the names of the definitions that `file_id`'s game (or
+    /// `file_id` itself, if no game uses it) defines, other than `excluded_name`
+    pub(crate) fn definition_name_completions(
+        db: &impl LanguageServerCtx,
+        file_id: FileId,
+        excluded_name: Option<&str>,
+    ) -> Vec<CompletionItem> {
+        let game_id = db.game_ids_of_rule_file(file_id)
+            .unwrap_or_default()
+            .into_iter()
+            .next();
+
+        let definitions = match game_id {
+            Some(game_id) => db.game_rule_definitions(game_id),
+            _ => db.rule_nodes_in_file(file_id),
+        }.unwrap_or_default();
+
+        definitions.into_iter()
+            .filter(|definition| Some(definition.key.as_str()) != excluded_name)
+            .map(|definition| CompletionItem {
+                label: definition.key,
+                kind: CompletionItemKind::Actor,
+                detail: None,
+                documentation: None,
+            })
+            .collect()
+    }
+
+    pub(crate) fn diagnostic_to_lsp_diagnostic(
+        db: &impl LanguageServerCtx,
+        diag: Diagnostic,
+    ) -> Option<oraide_actor::Diagnostic> {
+        use oraide_parser_miniyaml::{
+            LabelStyle,
+            Severity,
+        };
+
+        let range = match diag.primary_span() {
+            Some(span) => {
+                let (start, end_exclusive) = db.convert_file_span_to_2_positions(span)?;
+                oraide_actor::Range {
+                    start,
+                    end_exclusive,
+                }
+            },
+            None => oraide_actor::Range {
+                start: Position::default(),
+                end_exclusive: Position::default(),
+            },
+        };
+
+        let severity = match diag.severity {
+            Severity::Bug | Severity::Error => oraide_actor::DiagnosticSeverity::Error,
+            Severity::Warning => oraide_actor::DiagnosticSeverity::Warning,
+            Severity::Note => oraide_actor::DiagnosticSeverity::Information,
+        };
+
+        let mut message = diag.message;
+        for help_note in diag.help_notes {
+            message.push_str("\nhelp: ");
+            message.push_str(&help_note);
+        }
+
+        let related_information = diag.labels.into_iter()
+            .filter(|label| label.style == LabelStyle::Secondary)
+            .filter_map(|label| {
+                let message = label.message?;
+                let file_url = db.file_url(label.span.source())?;
+                let (start, end_exclusive) = db.convert_file_span_to_2_positions(label.span)?;
+
+                Some(oraide_actor::DiagnosticRelatedInformation {
+                    location: oraide_actor::RangedFilePosition::new_from_components(
+                        file_url,
+                        start,
+                        end_exclusive,
+                    ),
+                    message,
+                })
+            })
+            .collect();
+
+        Some(oraide_actor::Diagnostic {
+            range,
+            severity,
+            code: diag.code,
+            message,
+            related_information,
+        })
+    }
+
+    pub(crate) fn arena_node_id_to_sym(
+        db: &impl LanguageServerCtx,
+        file_text: &'_ str,
+        shrd_arena: &oraide_parser_miniyaml::Arena,
+        arena_node_id: oraide_parser_miniyaml::ArenaNodeId,
+        section: Option<ManifestSection>,
+        parent: Option<(SymbolKind, &str)>,
+        max_depth: usize,
+    ) -> Option<Symbol> {
+        let shrd_node = &shrd_arena.get(arena_node_id)?.get();
+        let name = shrd_node.key_text(file_text)?.to_owned();
+        let span = shrd_node.span()?;
+        let (start, end_exclusive) = db.convert_file_span_to_2_positions(span)?;
+        let range = oraide_actor::Range {
+            start,
+            end_exclusive,
+        };
+
+        let (kind, detail) = symbol_kind_and_detail(
+            db,
+            file_text,
+            shrd_arena,
+            arena_node_id,
+            &name,
+            section,
+            parent,
+        );
+
+        let children = if max_depth == 0 {
+            None
+        } else {
+            let children_iter = arena_node_id.children(&shrd_arena);
+            let children_syms: Vec<_> = children_iter.filter_map(|child_node_id|
+                arena_node_id_to_sym(
+                    db,
+                    file_text,
+                    shrd_arena,
+                    child_node_id,
+                    section,
+                    Some((kind, &name)),
+                    max_depth - 1,
+                )
+            ).collect();
+
+            Some(children_syms)
+        };
+
+        Some(Symbol {
+            name,
+            kind,
+            detail,
+            children,
+            range,
+        })
+    }
+
+    /// Classify the node `arena_node_id`, whose key is `name`, and describe it
+    ///
+    /// `section` is the manifest section that lists the node's file, if any,
+    /// and `parent` is the kind and name of the node's parent symbol, if any.
+    ///
+    /// # Returns
+    /// The kind of the symbol and its detail, which is:
+    /// - the inherited parents, for actors and abstracts
+    /// - the namespace, for traits (if type-data is available)
+    /// - the type, for properties of traits (if type-data is available)
+    /// - the node's value, otherwise
+    fn symbol_kind_and_detail(
+        db: &impl LanguageServerCtx,
+        file_text: &str,
+        shrd_arena: &oraide_parser_miniyaml::Arena,
+        arena_node_id: oraide_parser_miniyaml::ArenaNodeId,
+        name: &str,
+        section: Option<ManifestSection>,
+        parent: Option<(SymbolKind, &str)>,
+    ) -> (SymbolKind, Option<String>) {
+        let value_text = shrd_arena.get(arena_node_id)
+            .and_then(|shrd_arena_node| shrd_arena_node.get().value_text(file_text))
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_owned);
+
+        if parent.is_some() && name.starts_with('-') {
+            return (SymbolKind::Removal, Some(format!("removes {}", &name[1..])));
+        }
+
+        match parent {
+            None => {
+                let kind = section.and_then(|section| workspace_symbol_kind(section, name))
+                    .unwrap_or_else(|| if name.starts_with('^') {
+                        SymbolKind::Abstract
+                    } else {
+                        SymbolKind::Actor
+                    });
+
+                let detail = match kind {
+                    SymbolKind::Actor
+                    | SymbolKind::Abstract => {
+                        let parents = inherited_parents(file_text, shrd_arena, arena_node_id);
+
+                        if parents.is_empty() {
+                            None
+                        } else {
+                            Some(format!("inherits {}", parents.join(", ")))
+                        }
+                    },
+                    _ => value_text,
+                };
+
+                (kind, detail)
+            },
+            Some((SymbolKind::Actor, _))
+            | Some((SymbolKind::Abstract, _)) => {
+                let trait_name = name.split('@').next().unwrap_or(name);
+
+                if trait_name == "Inherits" {
+                    return (SymbolKind::Property, value_text);
+                }
+
+                let kind = if name.contains('@') {
+                    SymbolKind::TraitInstance
+                } else {
+                    SymbolKind::Trait
+                };
+
+                let detail = trait_detail(db, trait_name).map(|detail| detail.namespace);
+                (kind, detail)
+            },
+            Some((SymbolKind::Trait, parent_name))
+            | Some((SymbolKind::TraitInstance, parent_name)) => {
+                let trait_name = parent_name.split('@').next().unwrap_or(parent_name);
+
+                let detail = trait_detail(db, trait_name)
+                    .and_then(|detail| detail.properties.into_iter().find(|property| property.name == name))
+                    .map(|property| property.human_friendly_type_name)
+                    .or(value_text);
+
+                (SymbolKind::Property, detail)
+            },
+            Some(_) => (SymbolKind::Property, value_text),
+        }
+    }
+
+    /// The names of the definitions that the node `arena_node_id` inherits
+    /// from, in the order they are written
+    fn inherited_parents<'text>(
+        file_text: &'text str,
+        shrd_arena: &oraide_parser_miniyaml::Arena,
+        arena_node_id: oraide_parser_miniyaml::ArenaNodeId,
+    ) -> Vec<&'text str> {
+        arena_node_id.children(shrd_arena)
+            .filter_map(|child_node_id| shrd_arena.get(child_node_id))
+            .map(|shrd_arena_node| shrd_arena_node.get())
+            .filter(|shrd_node| match shrd_node.key_text(file_text) {
+                Some(key) => key == "Inherits" || key.starts_with("Inherits@"),
+                _ => false,
+            })
+            .filter_map(|shrd_node| shrd_node.value_text(file_text))
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .collect()
     }
 }
\ No newline at end of file
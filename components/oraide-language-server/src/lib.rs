@@ -13,7 +13,10 @@
 // along with oraide.  If not, see <https://www.gnu.org/licenses/>.
 
 use std::{
-    collections::VecDeque,
+    collections::{
+        HashMap,
+        VecDeque,
+    },
     sync::mpsc::Sender,
     io::{self, Read as _, Write as _},
     fmt,
@@ -33,11 +36,20 @@ use oraide_actor::{
 };
 
 mod language_server_ctx;
+mod code_actions;
+mod folding;
+mod fuzzy;
+mod highlights;
+mod lsp_ext;
+mod references;
+mod semantic_tokens;
+mod validation;
 pub mod types;
 
 pub use language_server_ctx::{
     LanguageServerCtx,
     LanguageServerCtxStorage,
+    workspace_symbols,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -60,6 +72,11 @@ pub enum LspMessage {
         params: languageserver_types::DidChangeTextDocumentParams,
     },
 
+    #[serde(rename = "textDocument/didClose")]
+    TextDocDidClose {
+        params: languageserver_types::DidCloseTextDocumentParams,
+    },
+
     #[serde(rename = "textDocument/hover")]
     TextDocHover {
         id: usize,
@@ -72,12 +89,84 @@ pub enum LspMessage {
         params: languageserver_types::TextDocumentPositionParams,
     },
 
+    #[serde(rename = "textDocument/completion")]
+    TextDocCompletion {
+        id: usize,
+        params: languageserver_types::CompletionParams,
+    },
+
+    #[serde(rename = "textDocument/references")]
+    TextDocReferences {
+        id: usize,
+        params: languageserver_types::ReferenceParams,
+    },
+
+    #[serde(rename = "textDocument/prepareRename")]
+    TextDocPrepareRename {
+        id: usize,
+        params: languageserver_types::TextDocumentPositionParams,
+    },
+
+    #[serde(rename = "textDocument/rename")]
+    TextDocRename {
+        id: usize,
+        params: languageserver_types::RenameParams,
+    },
+
+    #[serde(rename = "textDocument/formatting")]
+    TextDocFormatting {
+        id: usize,
+        params: languageserver_types::DocumentFormattingParams,
+    },
+
+    #[serde(rename = "textDocument/rangeFormatting")]
+    TextDocRangeFormatting {
+        id: usize,
+        params: languageserver_types::DocumentRangeFormattingParams,
+    },
+
     #[serde(rename = "textDocument/documentSymbol")]
     TextDocSymbols {
         id: usize,
         params: languageserver_types::DocumentSymbolParams,
     },
 
+    #[serde(rename = "textDocument/documentHighlight")]
+    TextDocHighlights {
+        id: usize,
+        params: languageserver_types::TextDocumentPositionParams,
+    },
+
+    #[serde(rename = "textDocument/codeAction")]
+    TextDocCodeActions {
+        id: usize,
+        params: languageserver_types::CodeActionParams,
+    },
+
+    #[serde(rename = "textDocument/foldingRange")]
+    TextDocFoldingRanges {
+        id: usize,
+        params: languageserver_types::FoldingRangeParams,
+    },
+
+    #[serde(rename = "textDocument/semanticTokens/full")]
+    TextDocSemanticTokens {
+        id: usize,
+        params: lsp_ext::SemanticTokensParams,
+    },
+
+    #[serde(rename = "textDocument/semanticTokens/full/delta")]
+    TextDocSemanticTokensDelta {
+        id: usize,
+        params: lsp_ext::SemanticTokensDeltaParams,
+    },
+
+    #[serde(rename = "workspace/symbol")]
+    WorkspaceSymbols {
+        id: usize,
+        params: languageserver_types::WorkspaceSymbolParams,
+    },
+
     #[serde(rename = "$/cancelRequest")]
     CancelRequest {
         params: languageserver_types::CancelParams,
@@ -90,7 +179,14 @@ pub enum LspMessage {
 /// The server sends messages *to* the task manager for work that
 /// needs to be done. The responder receives messages *from* the
 /// task manager for work that has been accomplished.
-pub struct LspResponder;
+#[derive(Default)]
+pub struct LspResponder {
+    /// The most recently sent semantic tokens of each file, and their result
+    /// ID, so that a client can be sent only what has changed since then
+    semantic_tokens: HashMap<url::Url, (String, Vec<u32>)>,
+
+    next_semantic_tokens_result_id: usize,
+}
 
 impl Actor for LspResponder {
     type Input = QueryResponse;
@@ -108,28 +204,51 @@ impl Actor for LspResponder {
                             ),
                         ),
                         hover_provider: Some(true),
-                        completion_provider: None,
+                        completion_provider: Some(languageserver_types::CompletionOptions {
+                            resolve_provider: Some(false),
+                            trigger_characters: None,
+                        }),
                         signature_help_provider: None,
                         definition_provider: Some(true),
                         type_definition_provider: None,
                         implementation_provider: None,
-                        references_provider: Some(false),
-                        document_highlight_provider: None,
+                        references_provider: Some(true),
+                        document_highlight_provider: Some(true),
                         document_symbol_provider: true.into(),
-                        workspace_symbol_provider: None,
-                        code_action_provider: None,
+                        workspace_symbol_provider: Some(true),
+                        code_action_provider: Some(languageserver_types::CodeActionProviderCapability::Simple(true)),
                         code_lens_provider: None,
-                        document_formatting_provider: None,
-                        document_range_formatting_provider: None,
+                        document_formatting_provider: Some(true),
+                        document_range_formatting_provider: Some(true),
                         document_on_type_formatting_provider: None,
-                        rename_provider: None,
+                        rename_provider: Some(languageserver_types::RenameProviderCapability::Options(
+                            languageserver_types::RenameOptions {
+                                prepare_provider: Some(true),
+                            },
+                        )),
                         color_provider: None,
-                        folding_range_provider: None,
+                        folding_range_provider: Some(
+                            languageserver_types::FoldingRangeProviderCapability::Simple(true),
+                        ),
                         execute_command_provider: None,
                         workspace: None,
                     },
                 };
 
+                // `languageserver_types` predates semantic tokens so the
+                // capability has to be added to the serialized result
+                let mut result = match serde_json::to_value(result) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        log::error!("Could not serialize the initialize result: {}", e);
+                        return;
+                    },
+                };
+
+                if let Ok(semantic_tokens_provider) = serde_json::to_value(lsp_ext::SemanticTokensOptions::default()) {
+                    result["capabilities"]["semanticTokensProvider"] = semantic_tokens_provider;
+                }
+
                 send_response(task_id, result);
             },
             QueryResponse::HoverData { task_id, data } => {
@@ -174,7 +293,7 @@ impl Actor for LspResponder {
                     languageserver_types::DocumentSymbol {
                         name: sym.name,
                         detail: sym.detail,
-                        kind: languageserver_types::SymbolKind::Object,
+                        kind: sym.kind.into(),
                         range: sym.range.clone().into(),
                         selection_range: sym.range.into(),
                         deprecated: None,
@@ -192,6 +311,104 @@ impl Actor for LspResponder {
 
                 send_response(task_id, symbols);
             },
+            QueryResponse::WorkspaceSymbols { task_id, symbols } => {
+                let symbols: Vec<languageserver_types::SymbolInformation> = symbols.into_iter()
+                    .map(Into::into)
+                    .collect();
+
+                send_response(task_id, symbols);
+            },
+            QueryResponse::FoldingRanges { task_id, ranges } => {
+                let ranges: Vec<languageserver_types::FoldingRange> = ranges.into_iter()
+                    .map(Into::into)
+                    .collect();
+
+                send_response(task_id, ranges);
+            },
+            QueryResponse::DocumentHighlights { task_id, highlights } => {
+                let highlights: Vec<languageserver_types::DocumentHighlight> = highlights.into_iter()
+                    .map(Into::into)
+                    .collect();
+
+                send_response(task_id, highlights);
+            },
+            QueryResponse::CodeActions { task_id, actions } => {
+                let actions: Vec<languageserver_types::CodeActionOrCommand> = actions.into_iter()
+                    .map(|action| languageserver_types::CodeActionOrCommand::CodeAction(action.into()))
+                    .collect();
+
+                send_response(task_id, actions);
+            },
+            QueryResponse::SemanticTokens { task_id, file_url, tokens, previous_result_id } => {
+                let data = lsp_ext::encode_semantic_tokens(&tokens);
+                let result_id = self.next_semantic_tokens_result_id.to_string();
+                self.next_semantic_tokens_result_id += 1;
+
+                let previous_data = match (previous_result_id.as_ref(), self.semantic_tokens.get(&file_url)) {
+                    (Some(previous_result_id), Some((sent_result_id, sent_data))) if previous_result_id == sent_result_id => Some(sent_data),
+                    _ => None,
+                };
+
+                match previous_data {
+                    Some(previous_data) => send_response(task_id, lsp_ext::SemanticTokensDelta {
+                        result_id: Some(result_id.clone()),
+                        edits: lsp_ext::semantic_tokens_edits(previous_data, &data),
+                    }),
+                    _ => send_response(task_id, lsp_ext::SemanticTokens {
+                        result_id: Some(result_id.clone()),
+                        data: data.clone(),
+                    }),
+                }
+
+                self.semantic_tokens.insert(file_url, (result_id, data));
+            },
+            QueryResponse::FileClosed { file_url } => {
+                self.semantic_tokens.remove(&file_url);
+            },
+            QueryResponse::Completions { task_id, items } => {
+                let items: Vec<languageserver_types::CompletionItem> = items.into_iter()
+                    .map(Into::into)
+                    .collect();
+
+                send_response(task_id, items);
+            },
+            QueryResponse::References { task_id, ranged_file_positions } => {
+                let locations: Vec<languageserver_types::Location> = ranged_file_positions.into_iter()
+                    .map(Into::into)
+                    .collect();
+
+                send_response(task_id, locations);
+            },
+            QueryResponse::RenameRange { task_id, range, placeholder } => {
+                send_response(task_id, languageserver_types::PrepareRenameResponse::RangeWithPlaceholder {
+                    range: range.into(),
+                    placeholder,
+                });
+            },
+            QueryResponse::WorkspaceEdit { task_id, edit } => {
+                send_response(task_id, languageserver_types::WorkspaceEdit::from(edit));
+            },
+            QueryResponse::TextEdits { task_id, edits } => {
+                let edits: Vec<languageserver_types::TextEdit> = edits.into_iter()
+                    .map(Into::into)
+                    .collect();
+
+                send_response(task_id, edits);
+            },
+            QueryResponse::Error { task_id, message } => send_error_response(task_id, message),
+            QueryResponse::Diagnostics { file_url, diagnostics } => {
+                let diagnostics: Vec<languageserver_types::Diagnostic> = diagnostics.into_iter()
+                    .map(Into::into)
+                    .collect();
+
+                send_notification(
+                    "textDocument/publishDiagnostics".into(),
+                    languageserver_types::PublishDiagnosticsParams::new(
+                        file_url,
+                        diagnostics,
+                    ),
+                );
+            },
         }
     }
 }
@@ -215,6 +432,38 @@ impl<T> JsonRpcResponse<T> {
     }
 }
 
+/// A wrapper for error responses back to the client from the server.
+/// These must follow the JSON 2.0 RPC spec.
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonRpcErrorResponse {
+    jsonrpc: String,
+    id: usize,
+    error: JsonRpcError,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+impl JsonRpcErrorResponse {
+    /// The JSON-RPC `InvalidParams` error code, used since the request was
+    /// valid but could not be fulfilled for the given parameters
+    const INVALID_PARAMS: i64 = -32602;
+
+    pub fn new(id: usize, message: String) -> Self {
+        Self {
+            jsonrpc: "2.0".into(),
+            id,
+            error: JsonRpcError {
+                code: Self::INVALID_PARAMS,
+                message,
+            },
+        }
+    }
+}
+
 /// A wrapper for notifications to the client from the server.
 /// These must follow the JSON 2.0 RPC spec.
 #[derive(Debug, Serialize, Deserialize)]
@@ -249,6 +498,16 @@ fn send_response<T: Serialize + fmt::Debug>(task_id: TaskId, response: T) {
     let _ = io::stdout().flush();
 }
 
+/// Helper function to send an error back to the client, which is typically
+/// shown to the user
+fn send_error_response(task_id: TaskId, message: String) {
+    let response = JsonRpcErrorResponse::new(task_id, message);
+    let response_string = serde_json::to_string(&response).unwrap();
+
+    print!("Content-Length: {}\r\n\r\n{}", response_string.len(), response_string);
+    let _ = io::stdout().flush();
+}
+
 /// Helper function to send a notification to the client
 fn send_notification<T: Serialize + fmt::Debug>(method: String, notice: T) {
     let notice = JsonRpcNotification::new(method, notice);
@@ -282,6 +541,10 @@ pub fn lsp_serve(send_to_query_channel: Sender<QueryRequest>) {
                                 QueryRequest::Initialize {
                                     task_id,
                                     workspace_root_url: params.root_uri,
+                                    document_symbol_depth: params.initialization_options.as_ref()
+                                        .and_then(|opts| opts.get("documentSymbolDepth"))
+                                        .and_then(serde_json::Value::as_u64)
+                                        .map(|depth| depth as usize),
                                 }
                             );
                         },
@@ -311,6 +574,11 @@ pub fn lsp_serve(send_to_query_channel: Sender<QueryRequest>) {
                                     .collect(),
                             });
                         },
+                        Ok(LspMessage::TextDocDidClose { params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::FileClosed {
+                                file_url: params.text_document.uri,
+                            });
+                        },
                         Ok(LspMessage::TextDocHover { id: task_id, params }) => {
                             let _ = send_to_query_channel.send(QueryRequest::HoverAtPosition {
                                 task_id,
@@ -325,12 +593,98 @@ pub fn lsp_serve(send_to_query_channel: Sender<QueryRequest>) {
                                 file_pos: params.position,
                             });
                         },
+                        Ok(LspMessage::TextDocCompletion { id: task_id, params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::Completion {
+                                task_id,
+                                file_url: params.text_document.uri,
+                                file_pos: params.position,
+                            });
+                        },
+                        Ok(LspMessage::TextDocReferences { id: task_id, params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::References {
+                                task_id,
+                                file_url: params.text_document.uri,
+                                file_pos: params.position,
+                                include_declarations: params.context.include_declaration,
+                            });
+                        },
+                        Ok(LspMessage::TextDocPrepareRename { id: task_id, params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::PrepareRename {
+                                task_id,
+                                file_url: params.text_document.uri,
+                                file_pos: params.position,
+                            });
+                        },
+                        Ok(LspMessage::TextDocRename { id: task_id, params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::Rename {
+                                task_id,
+                                file_url: params.text_document.uri,
+                                file_pos: params.position,
+                                new_name: params.new_name,
+                            });
+                        },
+                        Ok(LspMessage::TextDocFormatting { id: task_id, params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::Format {
+                                task_id,
+                                file_url: params.text_document.uri,
+                                use_spaces: params.options.insert_spaces,
+                                range: None,
+                            });
+                        },
+                        Ok(LspMessage::TextDocRangeFormatting { id: task_id, params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::Format {
+                                task_id,
+                                file_url: params.text_document.uri,
+                                use_spaces: params.options.insert_spaces,
+                                range: Some(params.range),
+                            });
+                        },
                         Ok(LspMessage::TextDocSymbols { id: task_id, params }) => {
                             let _ = send_to_query_channel.send(QueryRequest::FileSymbols {
                                 task_id,
                                 file_url: params.text_document.uri,
                             });
                         },
+                        Ok(LspMessage::TextDocHighlights { id: task_id, params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::DocumentHighlights {
+                                task_id,
+                                file_url: params.text_document.uri,
+                                file_pos: params.position,
+                            });
+                        },
+                        Ok(LspMessage::TextDocCodeActions { id: task_id, params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::CodeActions {
+                                task_id,
+                                file_url: params.text_document.uri,
+                                range: params.range,
+                            });
+                        },
+                        Ok(LspMessage::TextDocFoldingRanges { id: task_id, params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::FoldingRanges {
+                                task_id,
+                                file_url: params.text_document.uri,
+                            });
+                        },
+                        Ok(LspMessage::TextDocSemanticTokens { id: task_id, params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::SemanticTokens {
+                                task_id,
+                                file_url: params.text_document.uri,
+                                previous_result_id: None,
+                            });
+                        },
+                        Ok(LspMessage::TextDocSemanticTokensDelta { id: task_id, params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::SemanticTokens {
+                                task_id,
+                                file_url: params.text_document.uri,
+                                previous_result_id: Some(params.previous_result_id),
+                            });
+                        },
+                        Ok(LspMessage::WorkspaceSymbols { id: task_id, params }) => {
+                            let _ = send_to_query_channel.send(QueryRequest::WorkspaceSymbols {
+                                task_id,
+                                query: params.query,
+                            });
+                        },
                         Ok(LspMessage::CancelRequest { .. }) => {}
                         Err(_e) => {},
                     }
@@ -0,0 +1,265 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Parts of the language server protocol that `languageserver_types` does
+//! not (yet) provide
+
+use serde::{
+    Serialize,
+    Deserialize,
+};
+
+use oraide_actor::{
+    Position,
+    SemanticToken,
+    SemanticTokenType,
+};
+
+/// The token modifiers in the legend sent to a language client
+const SEMANTIC_TOKEN_MODIFIERS: &[&str] = &[
+    "declaration",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensParams {
+    pub text_document: languageserver_types::TextDocumentIdentifier,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensDeltaParams {
+    pub text_document: languageserver_types::TextDocumentIdentifier,
+    pub previous_result_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensLegend {
+    pub token_types: Vec<String>,
+    pub token_modifiers: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensFullOptions {
+    pub delta: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensOptions {
+    pub legend: SemanticTokensLegend,
+    pub full: SemanticTokensFullOptions,
+}
+
+/// The options that describe the semantic tokens this server provides
+impl Default for SemanticTokensOptions {
+    fn default() -> Self {
+        Self {
+            legend: SemanticTokensLegend {
+                token_types: SemanticTokenType::ALL.iter()
+                    .map(|token_type| token_type.legend_name().to_owned())
+                    .collect(),
+                token_modifiers: SEMANTIC_TOKEN_MODIFIERS.iter()
+                    .map(|modifier| (*modifier).to_owned())
+                    .collect(),
+            },
+            full: SemanticTokensFullOptions {
+                delta: true,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokens {
+    pub result_id: Option<String>,
+    pub data: Vec<u32>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensEdit {
+    pub start: usize,
+    pub delete_count: usize,
+    pub data: Vec<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensDelta {
+    pub result_id: Option<String>,
+    pub edits: Vec<SemanticTokensEdit>,
+}
+
+/// Encode `tokens`, which must be in document order, into the relative
+/// 5-integers-per-token format that is sent to a language client
+pub(crate) fn encode_semantic_tokens(tokens: &[SemanticToken]) -> Vec<u32> {
+    let mut data = Vec::with_capacity(tokens.len() * 5);
+    let mut prev_start = Position::default();
+
+    for token in tokens {
+        let start = token.range.start;
+        let delta_line = start.line_idx - prev_start.line_idx;
+
+        let delta_start = if delta_line == 0 {
+            start.character_idx - prev_start.character_idx
+        } else {
+            start.character_idx
+        };
+
+        let length = token.range.end_exclusive.character_idx.saturating_sub(start.character_idx);
+
+        let token_type = SemanticTokenType::ALL.iter()
+            .position(|token_type| *token_type == token.token_type)
+            .unwrap_or_default();
+
+        let modifiers = if token.is_declaration { 1 } else { 0 };
+
+        data.extend_from_slice(&[
+            delta_line as u32,
+            delta_start as u32,
+            length as u32,
+            token_type as u32,
+            modifiers,
+        ]);
+
+        prev_start = start;
+    }
+
+    data
+}
+
+/// Compute the edits that transform `previous` into `current`, which are
+/// both encoded semantic tokens
+///
+/// Everything between the common prefix and the common suffix is replaced by
+/// a single edit, which is cheap to compute and small when one area of a
+/// document changes (such as when the user is typing).
+pub(crate) fn semantic_tokens_edits(previous: &[u32], current: &[u32]) -> Vec<SemanticTokensEdit> {
+    let prefix_len = previous.iter().zip(current.iter())
+        .take_while(|(prev, curr)| prev == curr)
+        .count();
+
+    if prefix_len == previous.len() && prefix_len == current.len() {
+        return vec![];
+    }
+
+    let max_suffix_len = previous.len().min(current.len()) - prefix_len;
+    let suffix_len = previous.iter().rev().zip(current.iter().rev())
+        .take(max_suffix_len)
+        .take_while(|(prev, curr)| prev == curr)
+        .count();
+
+    vec![SemanticTokensEdit {
+        start: prefix_len,
+        delete_count: previous.len() - prefix_len - suffix_len,
+        data: current[prefix_len..current.len() - suffix_len].to_vec(),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use oraide_actor::{
+        Position,
+        Range,
+        SemanticToken,
+        SemanticTokenType,
+    };
+
+    use super::{
+        encode_semantic_tokens,
+        semantic_tokens_edits,
+        SemanticTokensEdit,
+    };
+
+    fn token(line_idx: usize, character_idx: usize, len: usize, token_type: SemanticTokenType) -> SemanticToken {
+        SemanticToken {
+            range: Range {
+                start: Position::new(line_idx, character_idx),
+                end_exclusive: Position::new(line_idx, character_idx + len),
+            },
+            token_type,
+            is_declaration: false,
+        }
+    }
+
+    #[test]
+    fn tokens_are_encoded_relative_to_the_previous_token() {
+        // Arrange
+        let mut definition = token(0, 0, 2, SemanticTokenType::Actor);
+        definition.is_declaration = true;
+
+        let tokens = vec![
+            definition,
+            token(1, 1, 6, SemanticTokenType::Trait),
+            token(2, 2, 2, SemanticTokenType::Property),
+            token(2, 6, 4, SemanticTokenType::Number),
+        ];
+
+        // Act
+        let actual = encode_semantic_tokens(&tokens);
+
+        // Assert
+        assert_eq!(actual, vec![
+            0, 0, 2, 0, 1,
+            1, 1, 6, 2, 0,
+            1, 2, 2, 4, 0,
+            0, 4, 4, 7, 0,
+        ]);
+    }
+
+    #[test]
+    fn unchanged_tokens_have_no_edits() {
+        let data = vec![0, 0, 2, 0, 1];
+        assert!(semantic_tokens_edits(&data, &data).is_empty());
+    }
+
+    #[test]
+    fn only_the_changed_middle_is_replaced() {
+        // Arrange
+        let previous = vec![0, 0, 2, 0, 1, 1, 1, 6, 2, 0, 1, 2, 2, 4, 0];
+        let current = vec![0, 0, 2, 0, 1, 1, 1, 8, 8, 0, 1, 2, 2, 4, 0];
+
+        // Act
+        let actual = semantic_tokens_edits(&previous, &current);
+
+        // Assert
+        assert_eq!(actual, vec![SemanticTokensEdit {
+            start: 7,
+            delete_count: 2,
+            data: vec![8, 8],
+        }]);
+    }
+
+    #[test]
+    fn appended_tokens_are_inserted_at_the_end() {
+        // Arrange
+        let previous = vec![0, 0, 2, 0, 1];
+        let current = vec![0, 0, 2, 0, 1, 1, 1, 6, 2, 0];
+
+        // Act
+        let actual = semantic_tokens_edits(&previous, &current);
+
+        // Assert
+        assert_eq!(actual, vec![SemanticTokensEdit {
+            start: 5,
+            delete_count: 0,
+            data: vec![1, 1, 6, 2, 0],
+        }]);
+    }
+}
\ No newline at end of file
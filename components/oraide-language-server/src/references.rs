@@ -0,0 +1,271 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Finding the places that rule definitions are referred to by name
+
+use oraide_span::{
+    ByteIndex,
+    FileSpan,
+};
+
+use oraide_sdk::{
+    RuleNode,
+};
+
+use crate::{
+    validation,
+};
+
+/// What the items of a reference property's value are the names of
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum ReferenceKind {
+    Actor,
+    Weapon,
+}
+
+/// Properties whose values are the names of actors or weapons, as
+/// `(trait, property, kind)`
+///
+/// Type-data does not say which properties refer to definitions, so this is
+/// the only place that decides it.  Values may be comma-separated lists.
+const KNOWN_REFERENCE_PROPERTIES: &[(&str, &str, ReferenceKind)] = &[
+    ("AirstrikePower", "UnitType", ReferenceKind::Actor),
+    ("Armament", "Weapon", ReferenceKind::Weapon),
+    ("Buildable", "Prerequisites", ReferenceKind::Actor),
+    ("Cargo", "InitialUnits", ReferenceKind::Actor),
+    ("Explodes", "EmptyWeapon", ReferenceKind::Weapon),
+    ("Explodes", "Weapon", ReferenceKind::Weapon),
+    ("FreeActor", "Actor", ReferenceKind::Actor),
+    ("GiveUnitCrateAction", "Units", ReferenceKind::Actor),
+    ("LeavesHusk", "HuskActor", ReferenceKind::Actor),
+    ("ParatroopersPower", "DropItems", ReferenceKind::Actor),
+    ("ParatroopersPower", "UnitType", ReferenceKind::Actor),
+    ("ProductionAirdrop", "ActorType", ReferenceKind::Actor),
+    ("ProductionParadrop", "ActorType", ReferenceKind::Actor),
+    ("SpawnActorOnDeath", "Actor", ReferenceKind::Actor),
+    ("ThrowsShrapnel", "Weapons", ReferenceKind::Weapon),
+    ("Transforms", "IntoActor", ReferenceKind::Actor),
+];
+
+/// What the values of `property_name` on `trait_name` are the names of, if
+/// they are references at all
+pub(crate) fn reference_kind(trait_name: &str, property_name: &str) -> Option<ReferenceKind> {
+    KNOWN_REFERENCE_PROPERTIES.iter()
+        .find(|(known_trait, known_property, _)| *known_trait == trait_name && *known_property == property_name)
+        .map(|(_, _, kind)| *kind)
+}
+
+/// Whether the values of `property_name` on `trait_name` are the names of
+/// actors or weapons
+pub(crate) fn is_reference_property(trait_name: &str, property_name: &str) -> bool {
+    reference_kind(trait_name, property_name).is_some()
+}
+
+/// Split a comma-separated `value` into its items, along with the span of
+/// each item
+///
+/// Prerequisite modifiers (`~` and `!`) are not considered part of an item.
+pub(crate) fn value_items(value: &str, value_span: FileSpan) -> Vec<(String, FileSpan)> {
+    let mut items = vec![];
+    let mut item_start = 0;
+
+    for item in value.split(',') {
+        let leading_len = item.len() - item.trim_start_matches(|ch: char| ch.is_whitespace() || ch == '~' || ch == '!').len();
+        let item_text = item[leading_len..].trim_end();
+
+        if !item_text.is_empty() {
+            let start = value_span.start().to_usize() + item_start + leading_len;
+            let span = FileSpan::new(value_span.source(), start, start + item_text.len());
+            items.push((item_text.to_owned(), span));
+        }
+
+        // Skip past this item and its trailing comma
+        item_start += item.len() + 1;
+    }
+
+    items
+}
+
+/// Find the value item, and its span, of `node` that contains `byte_index`
+pub(crate) fn value_item_at(node: &RuleNode, byte_index: ByteIndex) -> Option<(String, FileSpan)> {
+    let (value, value_span) = match (&node.value, node.value_span) {
+        (Some(value), Some(span)) => (value, span),
+        _ => return None,
+    };
+
+    value_items(value, value_span).into_iter()
+        .find(|(_, span)| span.contains(byte_index))
+}
+
+/// Whether `name` and `other_name` name the same definition
+///
+/// Like OpenRA, which looks actors and weapons up by their lowercased names,
+/// names are compared case-insensitively so `barr` refers to `BARR`.
+pub(crate) fn is_same_definition_name(name: &str, other_name: &str) -> bool {
+    name.eq_ignore_ascii_case(other_name)
+}
+
+/// Find the spans, in `definitions`, of every reference to `name`, see
+/// [`is_same_definition_name`]
+///
+/// [`is_same_definition_name`]: fn.is_same_definition_name.html
+pub(crate) fn reference_spans(definitions: &[RuleNode], name: &str) -> Vec<FileSpan> {
+    reference_items(definitions).into_iter()
+        .filter(|(item_text, _)| is_same_definition_name(item_text, name))
+        .map(|(_, span)| span)
+        .collect()
+}
+
+/// Find every reference, in `definitions`, to a definition, along with the
+/// span of each reference
+pub(crate) fn reference_items(definitions: &[RuleNode]) -> Vec<(String, FileSpan)> {
+    let mut referencing_nodes = vec![];
+
+    for definition in definitions {
+        for trait_node in definition.children.iter() {
+            if trait_node.is_inherits() {
+                referencing_nodes.push(trait_node);
+                continue;
+            }
+
+            let trait_name = validation::trait_name(&trait_node.key);
+
+            let property_nodes = trait_node.children.iter()
+                .filter(|property_node| is_reference_property(trait_name, &property_node.key));
+
+            referencing_nodes.extend(property_nodes);
+        }
+    }
+
+    referencing_nodes.into_iter()
+        .filter_map(|node| Some(value_items(node.value.as_ref()?, node.value_span?)))
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use oraide_span::{
+        FileId,
+        FileSpan,
+    };
+
+    use oraide_parser_miniyaml::Parse;
+
+    use oraide_sdk::{
+        RuleNode,
+        rule_nodes_from_tree,
+    };
+
+    use super::{
+        ReferenceKind,
+        is_reference_property,
+        reference_items,
+        reference_kind,
+        reference_spans,
+        value_items,
+    };
+
+    fn definitions(text: &str) -> Vec<RuleNode> {
+        let parse = Parse::new(FileId(0), text);
+        rule_nodes_from_tree(&parse.tree(), text)
+    }
+
+    fn span_of(text: &str, needle: &str) -> FileSpan {
+        let start = text.find(needle).unwrap();
+        FileSpan::new(FileId(0), start, start + needle.len())
+    }
+
+    #[test]
+    fn value_items_have_spans_without_modifiers_or_whitespace() {
+        // Arrange
+        let value = "barr, ~techlevel.low,!tent";
+        let value_span = FileSpan::new(FileId(0), 10, 10 + value.len());
+
+        // Act
+        let actual = value_items(value, value_span);
+
+        // Assert
+        assert_eq!(actual, vec![
+            ("barr".to_owned(), FileSpan::new(FileId(0), 10, 14)),
+            ("techlevel.low".to_owned(), FileSpan::new(FileId(0), 17, 30)),
+            ("tent".to_owned(), FileSpan::new(FileId(0), 32, 36)),
+        ]);
+    }
+
+    #[test]
+    fn only_known_properties_are_references() {
+        assert!(is_reference_property("Armament", "Weapon"));
+        assert!(is_reference_property("Buildable", "Prerequisites"));
+        assert!(!is_reference_property("Armament", "LocalOffset"));
+        assert!(!is_reference_property("Buildable", "Queue"));
+    }
+
+    #[test]
+    fn reference_properties_know_what_they_refer_to() {
+        assert_eq!(reference_kind("Armament", "Weapon"), Some(ReferenceKind::Weapon));
+        assert_eq!(reference_kind("Cargo", "InitialUnits"), Some(ReferenceKind::Actor));
+        assert_eq!(reference_kind("Armament", "LocalOffset"), None);
+    }
+
+    #[test]
+    fn every_item_of_a_list_valued_property_is_a_reference() {
+        // Arrange
+        let text = "APC:\n\tBuildable:\n\t\tQueue: Vehicle\n\t\tPrerequisites: weap, ~techlevel.low\n\tCargo:\n\t\tInitialUnits: E1, E3,E1\n";
+        let definitions = definitions(text);
+
+        // Act
+        let actual = reference_items(&definitions);
+
+        // Assert
+        let initial_units_start = text.find("E1, E3").unwrap();
+        assert_eq!(actual, vec![
+            ("weap".to_owned(), span_of(text, "weap")),
+            ("techlevel.low".to_owned(), span_of(text, "techlevel.low")),
+            ("E1".to_owned(), FileSpan::new(FileId(0), initial_units_start, initial_units_start + 2)),
+            ("E3".to_owned(), span_of(text, "E3")),
+            ("E1".to_owned(), FileSpan::new(FileId(0), initial_units_start + 7, initial_units_start + 9)),
+        ]);
+    }
+
+    #[test]
+    fn reference_spans_include_each_occurrence_of_the_name_in_a_list() {
+        // Arrange
+        let text = "APC:\n\tCargo:\n\t\tInitialUnits: E1, E3,E1\n";
+        let definitions = definitions(text);
+
+        // Act
+        let actual = reference_spans(&definitions, "E1");
+
+        // Assert
+        let start = text.find("E1").unwrap();
+        assert_eq!(actual, vec![
+            FileSpan::new(FileId(0), start, start + 2),
+            FileSpan::new(FileId(0), start + 7, start + 9),
+        ]);
+    }
+
+    #[test]
+    fn reference_spans_ignore_the_case_of_the_name() {
+        // Arrange
+        let text = "BARR:\n\tHealth:\nE1:\n\tBuildable:\n\t\tPrerequisites: barr\n";
+        let definitions = definitions(text);
+
+        // Act
+        let actual = reference_spans(&definitions, "BARR");
+
+        // Assert
+        assert_eq!(actual, vec![span_of(text, "barr")]);
+    }
+}
\ No newline at end of file
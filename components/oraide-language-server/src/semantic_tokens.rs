@@ -0,0 +1,444 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Classifying the spans of a file by what they mean, for semantic highlighting
+
+use oraide_span::FileSpan;
+
+use oraide_parser_miniyaml::{
+    Token,
+    TokenKind,
+};
+
+use oraide_sdk::{
+    ManifestSection,
+    RuleNode,
+};
+
+use oraide_actor::SemanticTokenType;
+
+use crate::{
+    types::TraitDetail,
+    highlights::{
+        self,
+        IdentifierKind,
+    },
+    references::{
+        self,
+        ReferenceKind,
+    },
+    validation,
+};
+
+/// A span of a file and what it means
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ClassifiedSpan {
+    pub(crate) span: FileSpan,
+    pub(crate) token_type: SemanticTokenType,
+    pub(crate) is_declaration: bool,
+}
+
+impl ClassifiedSpan {
+    fn new(span: FileSpan, token_type: SemanticTokenType) -> Self {
+        Self {
+            span,
+            token_type,
+            is_declaration: false,
+        }
+    }
+}
+
+/// Whether definitions in files listed in `section` are actors, which is
+/// assumed of files that no manifest lists
+fn is_rules_section(section: Option<ManifestSection>) -> bool {
+    match section {
+        None | Some(ManifestSection::Rules) => true,
+        _ => false,
+    }
+}
+
+/// The token type of the definition `name`, or of an `Inherits` reference to
+/// it, in a file listed in `section`
+///
+/// # Returns
+/// `None` if definitions in `section` are not classified
+fn definition_token_type(section: Option<ManifestSection>, name: &str) -> Option<SemanticTokenType> {
+    let token_type = match section {
+        _ if is_rules_section(section) => return Some(reference_token_type(ReferenceKind::Actor, name)),
+        Some(ManifestSection::Weapons) => SemanticTokenType::Weapon,
+        Some(ManifestSection::Sequences) => SemanticTokenType::Sequence,
+        Some(ManifestSection::Chrome)
+        | Some(ManifestSection::ChromeLayout) => SemanticTokenType::ChromeWidget,
+        _ => return None,
+    };
+
+    Some(token_type)
+}
+
+/// The token type of a reference, of `kind`, to the definition `name`
+fn reference_token_type(kind: ReferenceKind, name: &str) -> SemanticTokenType {
+    match kind {
+        ReferenceKind::Weapon => SemanticTokenType::Weapon,
+        ReferenceKind::Actor if name.starts_with('^') => SemanticTokenType::Abstract,
+        ReferenceKind::Actor => SemanticTokenType::Actor,
+    }
+}
+
+/// Whether `trait_name` is a trait, which is assumed when there is no type-data
+fn is_known_trait(type_data: Option<&[TraitDetail]>, trait_name: &str) -> bool {
+    match type_data {
+        Some(type_data) => type_data.iter().any(|detail| detail.name == trait_name),
+        _ => true,
+    }
+}
+
+/// Whether `property_name` is a property of `trait_name`, which is assumed
+/// when there is no type-data or `trait_name` is not a known trait
+fn is_known_property(type_data: Option<&[TraitDetail]>, trait_name: &str, property_name: &str) -> bool {
+    let detail = type_data.unwrap_or_default().iter()
+        .find(|detail| detail.name == trait_name);
+
+    match detail {
+        Some(detail) => detail.properties.iter().any(|property| property.name == property_name),
+        _ => true,
+    }
+}
+
+/// Classify the spans of `definitions`, which are all of the top-level nodes
+/// of a file listed in `section` (if any), in document order
+///
+/// `tokens` must be the tokens of the same file, in document order, and are
+/// used to classify values.  If `type_data` is given then traits and
+/// properties that it does not know of are classified as unknown.
+///
+/// Only the children of definitions in rules files are traits, the children
+/// of other definitions are classified as properties.
+pub(crate) fn classify(
+    section: Option<ManifestSection>,
+    type_data: Option<&[TraitDetail]>,
+    definitions: &[RuleNode],
+    tokens: &[Token],
+) -> Vec<ClassifiedSpan> {
+    let mut spans = vec![];
+
+    for definition in definitions {
+        if let Some(token_type) = definition_token_type(section, &definition.key) {
+            let start = definition.key_span.start().to_usize();
+
+            spans.push(ClassifiedSpan {
+                span: FileSpan::new(definition.key_span.source(), start, start + definition.key.len()),
+                token_type,
+                is_declaration: true,
+            });
+        }
+
+        for child in definition.children.iter() {
+            if is_rules_section(section) {
+                classify_trait_node(section, type_data, child, tokens, &mut spans);
+            } else if child.is_inherits() {
+                classify_key(child, SemanticTokenType::Property, &mut spans);
+                classify_reference_values(child, &mut spans, |name| definition_token_type(section, name));
+            } else {
+                classify_nested_node(child, tokens, &mut spans);
+            }
+        }
+    }
+
+    spans.sort_by_key(|classified| classified.span);
+    spans
+}
+
+fn classify_trait_node(
+    section: Option<ManifestSection>,
+    type_data: Option<&[TraitDetail]>,
+    trait_node: &RuleNode,
+    tokens: &[Token],
+    spans: &mut Vec<ClassifiedSpan>,
+) {
+    classify_removal(trait_node, spans);
+
+    let trait_name = validation::trait_name(trait_node.key.trim_start_matches('-'));
+
+    for occurrence in highlights::trait_key_occurrences(trait_node) {
+        let token_type = match occurrence.kind {
+            IdentifierKind::Suffix => SemanticTokenType::TraitSuffix,
+            _ if trait_node.is_inherits() || is_known_trait(type_data, trait_name) => SemanticTokenType::Trait,
+            _ => SemanticTokenType::Unknown,
+        };
+
+        spans.push(ClassifiedSpan::new(occurrence.span, token_type));
+    }
+
+    if trait_node.is_inherits() {
+        classify_reference_values(trait_node, spans, |name| definition_token_type(section, name));
+    } else {
+        classify_values(trait_node, tokens, spans);
+    }
+
+    for property_node in trait_node.children.iter() {
+        classify_removal(property_node, spans);
+
+        let property_name = property_node.key.trim_start_matches('-');
+        let start = property_node.key_span.start().to_usize() + (property_node.key.len() - property_name.len());
+        let span = FileSpan::new(property_node.key_span.source(), start, start + property_name.len());
+
+        let token_type = if is_known_property(type_data, trait_name, property_name) {
+            SemanticTokenType::Property
+        } else {
+            SemanticTokenType::Unknown
+        };
+
+        spans.push(ClassifiedSpan::new(span, token_type));
+
+        if let Some(kind) = references::reference_kind(trait_name, property_name) {
+            classify_reference_values(property_node, spans, |name| Some(reference_token_type(kind, name)));
+        } else {
+            classify_values(property_node, tokens, spans);
+        }
+
+        for nested_node in property_node.children.iter() {
+            classify_nested_node(nested_node, tokens, spans);
+        }
+    }
+}
+
+/// Classify a node below a property, about which type-data says nothing
+fn classify_nested_node(
+    node: &RuleNode,
+    tokens: &[Token],
+    spans: &mut Vec<ClassifiedSpan>,
+) {
+    classify_key(node, SemanticTokenType::Property, spans);
+    classify_values(node, tokens, spans);
+
+    for child in node.children.iter() {
+        classify_nested_node(child, tokens, spans);
+    }
+}
+
+/// Classify the key of `node` as `token_type`, and its `-` if it is a removal
+fn classify_key(node: &RuleNode, token_type: SemanticTokenType, spans: &mut Vec<ClassifiedSpan>) {
+    classify_removal(node, spans);
+
+    let key = node.key.trim_start_matches('-');
+    let start = node.key_span.start().to_usize() + (node.key.len() - key.len());
+    spans.push(ClassifiedSpan::new(
+        FileSpan::new(node.key_span.source(), start, start + key.len()),
+        token_type,
+    ));
+}
+
+/// Classify the `-` of `node` if it is a removal
+fn classify_removal(node: &RuleNode, spans: &mut Vec<ClassifiedSpan>) {
+    if !node.is_removal() {
+        return;
+    }
+
+    let start = node.key_span.start().to_usize();
+    spans.push(ClassifiedSpan::new(
+        FileSpan::new(node.key_span.source(), start, start + 1),
+        SemanticTokenType::Removal,
+    ));
+}
+
+/// Classify each item of `node`'s value as a reference to a definition,
+/// with `token_type` deciding the token type of each item (if any)
+fn classify_reference_values(
+    node: &RuleNode,
+    spans: &mut Vec<ClassifiedSpan>,
+    token_type: impl Fn(&str) -> Option<SemanticTokenType>,
+) {
+    let (value, value_span) = match (&node.value, node.value_span) {
+        (Some(value), Some(span)) => (value, span),
+        _ => return,
+    };
+
+    let references = references::value_items(value, value_span).into_iter()
+        .filter_map(|(item_text, span)| Some(ClassifiedSpan::new(span, token_type(&item_text)?)));
+
+    spans.extend(references);
+}
+
+/// Classify the boolean and numeric tokens of `node`'s value
+fn classify_values(node: &RuleNode, tokens: &[Token], spans: &mut Vec<ClassifiedSpan>) {
+    let value_span = match node.value_span {
+        Some(span) => span,
+        _ => return,
+    };
+
+    let first_idx = match tokens.binary_search_by_key(&value_span.start(), |token| token.span.start()) {
+        Ok(idx) | Err(idx) => idx,
+    };
+
+    let value_tokens = tokens[first_idx..].iter()
+        .take_while(|token| token.span.start() < value_span.end_exclusive());
+
+    for token in value_tokens {
+        let token_type = match token.kind {
+            TokenKind::True
+            | TokenKind::Yes
+            | TokenKind::False
+            | TokenKind::No => SemanticTokenType::Boolean,
+            TokenKind::IntLiteral
+            | TokenKind::FloatLiteral => SemanticTokenType::Number,
+            _ => continue,
+        };
+
+        spans.push(ClassifiedSpan::new(token.span, token_type));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oraide_span::FileId;
+
+    use oraide_parser_miniyaml::Parse;
+
+    use oraide_sdk::{
+        ManifestSection,
+        rule_nodes_from_tree,
+    };
+
+    use oraide_actor::SemanticTokenType;
+
+    use crate::types::TraitDetail;
+
+    use super::classify;
+
+    fn classify_text(
+        section: Option<ManifestSection>,
+        type_data: Option<&[TraitDetail]>,
+        text: &str,
+    ) -> Vec<(String, SemanticTokenType, bool)> {
+        let parse = Parse::new(FileId(0), text);
+        let definitions = rule_nodes_from_tree(&parse.tree(), text);
+
+        classify(section, type_data, &definitions, &parse.tokens())
+            .into_iter()
+            .map(|classified| (
+                classified.span.text(text).unwrap().to_owned(),
+                classified.token_type,
+                classified.is_declaration,
+            ))
+            .collect()
+    }
+
+    fn type_data() -> Vec<TraitDetail> {
+        vec![TraitDetail {
+            defining_assembly_name: "OpenRA.Mods.Common".into(),
+            is_conditional: false,
+            required_traits: vec![],
+            properties: vec![],
+            doc_lines: None,
+            namespace: "OpenRA.Mods.Common.Traits".into(),
+            name: "Health".into(),
+        }]
+    }
+
+    #[test]
+    fn definitions_traits_and_references_are_classified() {
+        // Arrange
+        let text = "E1:\n\tInherits: ^Infantry\n\tArmament@PRIMARY:\n\t\tWeapon: M1Carbine\n\t-Selectable:\n";
+
+        // Act
+        let actual = classify_text(None, None, text);
+
+        // Assert
+        assert_eq!(actual, vec![
+            ("E1".to_owned(), SemanticTokenType::Actor, true),
+            ("Inherits".to_owned(), SemanticTokenType::Trait, false),
+            ("^Infantry".to_owned(), SemanticTokenType::Abstract, false),
+            ("Armament".to_owned(), SemanticTokenType::Trait, false),
+            ("@PRIMARY".to_owned(), SemanticTokenType::TraitSuffix, false),
+            ("Weapon".to_owned(), SemanticTokenType::Property, false),
+            ("M1Carbine".to_owned(), SemanticTokenType::Weapon, false),
+            ("-".to_owned(), SemanticTokenType::Removal, false),
+            ("Selectable".to_owned(), SemanticTokenType::Trait, false),
+        ]);
+    }
+
+    #[test]
+    fn booleans_and_numbers_are_classified() {
+        // Arrange
+        let text = "E1:\n\tHealth:\n\t\tHP: 5000\n\t\tNotifyAppliedDamage: False\n";
+
+        // Act
+        let actual = classify_text(None, None, text);
+
+        // Assert
+        assert_eq!(actual, vec![
+            ("E1".to_owned(), SemanticTokenType::Actor, true),
+            ("Health".to_owned(), SemanticTokenType::Trait, false),
+            ("HP".to_owned(), SemanticTokenType::Property, false),
+            ("5000".to_owned(), SemanticTokenType::Number, false),
+            ("NotifyAppliedDamage".to_owned(), SemanticTokenType::Property, false),
+            ("False".to_owned(), SemanticTokenType::Boolean, false),
+        ]);
+    }
+
+    #[test]
+    fn traits_and_properties_missing_from_type_data_are_unknown() {
+        // Arrange
+        let type_data = type_data();
+        let text = "E1:\n\tHealth:\n\t\tHitPoints: 5000\n\tHeatlh:\n";
+
+        // Act
+        let actual = classify_text(None, Some(type_data.as_slice()), text);
+
+        // Assert
+        assert_eq!(actual, vec![
+            ("E1".to_owned(), SemanticTokenType::Actor, true),
+            ("Health".to_owned(), SemanticTokenType::Trait, false),
+            ("HitPoints".to_owned(), SemanticTokenType::Unknown, false),
+            ("5000".to_owned(), SemanticTokenType::Number, false),
+            ("Heatlh".to_owned(), SemanticTokenType::Unknown, false),
+        ]);
+    }
+
+    #[test]
+    fn definitions_in_weapons_files_are_weapons_with_properties() {
+        // Arrange
+        let text = "M1Carbine:\n\tInherits: ^LightMG\n\tValidTargets: Ground\n\tProjectile: Bullet\n";
+
+        // Act
+        let actual = classify_text(Some(ManifestSection::Weapons), None, text);
+
+        // Assert
+        assert_eq!(actual, vec![
+            ("M1Carbine".to_owned(), SemanticTokenType::Weapon, true),
+            ("Inherits".to_owned(), SemanticTokenType::Property, false),
+            ("^LightMG".to_owned(), SemanticTokenType::Weapon, false),
+            ("ValidTargets".to_owned(), SemanticTokenType::Property, false),
+            ("Projectile".to_owned(), SemanticTokenType::Property, false),
+        ]);
+    }
+
+    #[test]
+    fn definitions_in_sequences_files_are_sequences() {
+        // Arrange
+        let text = "e1:\n\tstand:\n\t\tFacings: 8\n";
+
+        // Act
+        let actual = classify_text(Some(ManifestSection::Sequences), None, text);
+
+        // Assert
+        assert_eq!(actual, vec![
+            ("e1".to_owned(), SemanticTokenType::Sequence, true),
+            ("stand".to_owned(), SemanticTokenType::Property, false),
+            ("Facings".to_owned(), SemanticTokenType::Property, false),
+            ("8".to_owned(), SemanticTokenType::Number, false),
+        ]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,407 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Validation of rule definitions against the trait information found in
+//! a workspace's `type-data.json`
+
+use oraide_parser_miniyaml::{
+    Diagnostic,
+    Label,
+};
+
+use oraide_sdk::{
+    RuleNode,
+};
+
+use crate::types::{
+    TraitDetail,
+    TraitProperty,
+    TraitPropertyKind,
+};
+
+/// The name of the trait that `key` refers to, without any `@suffix`
+///
+/// For example, both `Armament` and `Armament@PRIMARY` refer to `Armament`.
+pub(crate) fn trait_name(key: &str) -> &str {
+    key.split('@').next().unwrap_or(key)
+}
+
+/// Check that each trait of `definition` exists, that each property of those
+/// traits exists, and that each property's value can be parsed
+pub(crate) fn validate_definition(type_data: &[TraitDetail], definition: &RuleNode) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    let trait_nodes = definition.children.iter()
+        .filter(|node| !node.is_inherits() && !node.is_removal());
+
+    for trait_node in trait_nodes {
+        let name = trait_name(&trait_node.key);
+
+        let trait_detail = match type_data.iter().find(|detail| detail.name == name) {
+            Some(detail) => detail,
+            _ => {
+                diagnostics.push(
+                    Diagnostic::new_error(format!("Unknown trait `{}`", name))
+                        .with_code("S:E0006")
+                        .with_label(Label::new_primary(trait_node.key_span))
+                );
+
+                continue;
+            },
+        };
+
+        let property_nodes = trait_node.children.iter()
+            .filter(|node| !node.is_removal());
+
+        for property_node in property_nodes {
+            let property = match trait_detail.properties.iter().find(|prop| prop.name == property_node.key) {
+                Some(prop) => prop,
+                _ => {
+                    diagnostics.push(
+                        Diagnostic::new_error(format!("Unknown property `{}` on trait `{}`", property_node.key, name))
+                            .with_code("S:E0007")
+                            .with_label(Label::new_primary(property_node.key_span))
+                    );
+
+                    continue;
+                },
+            };
+
+            let (value, value_span) = match (&property_node.value, property_node.value_span) {
+                (Some(value), Some(span)) => (value, span),
+                _ => continue,
+            };
+
+            if is_valid_value(property, value) {
+                continue;
+            }
+
+            let help = match &property.valid_values {
+                Some(valid_values) => format!("Expected one of: {}", valid_values.join(", ")),
+                _ => format!("Expected a value of type `{}`", property.human_friendly_type_name),
+            };
+
+            diagnostics.push(
+                Diagnostic::new_error(format!("Invalid value `{}` for property `{}`", value, property.name))
+                    .with_code("S:E0008")
+                    .with_label(Label::new_primary(value_span))
+                    .with_help(help)
+            );
+        }
+    }
+
+    diagnostics
+}
+
+/// Check that every trait required by a trait of `resolved_definition` is
+/// also present on `resolved_definition`
+///
+/// Only requirements that are themselves known traits are checked, since
+/// some traits require interfaces rather than specific traits.
+pub(crate) fn validate_required_traits(type_data: &[TraitDetail], resolved_definition: &RuleNode) -> Vec<Diagnostic> {
+    missing_required_traits(type_data, resolved_definition).into_iter()
+        .map(|(trait_node, missing_name)|
+            Diagnostic::new_error(format!(
+                "`{}` requires the `{}` trait, which `{}` does not have",
+                trait_name(&trait_node.key),
+                missing_name,
+                resolved_definition.key,
+            ))
+                .with_code("S:E0009")
+                .with_label(Label::new_primary(resolved_definition.key_span))
+                .with_label(
+                    Label::new_secondary(trait_node.key_span)
+                        .with_message(format!("`{}` is required by this trait", missing_name))
+                )
+        )
+        .collect()
+}
+
+/// Find the traits that are required by a trait of `resolved_definition`
+/// but are not present on `resolved_definition`
+///
+/// # Returns
+/// Each requiring trait node along with the name of the trait it requires
+pub(crate) fn missing_required_traits<'a>(
+    type_data: &'a [TraitDetail],
+    resolved_definition: &'a RuleNode,
+) -> Vec<(&'a RuleNode, &'a str)> {
+    let present_names: Vec<_> = resolved_definition.children.iter()
+        .map(|node| trait_name(&node.key))
+        .collect();
+
+    let mut missing = vec![];
+
+    for trait_node in resolved_definition.children.iter() {
+        let name = trait_name(&trait_node.key);
+
+        let trait_detail = match type_data.iter().find(|detail| detail.name == name) {
+            Some(detail) => detail,
+            _ => continue,
+        };
+
+        let missing_names = trait_detail.required_traits.iter()
+            .map(|required| required.name.as_str())
+            .filter(|required_name| type_data.iter().any(|detail| detail.name == *required_name))
+            .filter(|required_name| !present_names.contains(required_name));
+
+        for missing_name in missing_names {
+            missing.push((trait_node, missing_name));
+        }
+    }
+
+    missing
+}
+
+/// Whether `value` can be parsed in the same way that OpenRA's `FieldLoader`
+/// would parse it for `property`
+///
+/// Values of types that are not understood are assumed to be valid.
+fn is_valid_value(property: &TraitProperty, value: &str) -> bool {
+    if let Some(valid_values) = &property.valid_values {
+        // Enums are parsed case-insensitively and flags may be combined
+        return value.split(',')
+            .map(str::trim)
+            .all(|part| valid_values.iter().any(|valid| valid.eq_ignore_ascii_case(part)));
+    }
+
+    if property.kind != TraitPropertyKind::Single {
+        return true;
+    }
+
+    match property.type_name.as_str() {
+        "SByte" | "Int16" | "Int32" | "Int64" => value.parse::<i64>().is_ok(),
+        "Byte" | "UInt16" | "UInt32" | "UInt64" => value.parse::<u64>().is_ok(),
+        // Like OpenRA's `FieldLoader`, `yes` and `no` are also booleans
+        "Boolean" => ["true", "false", "yes", "no"].iter().any(|valid| valid.eq_ignore_ascii_case(value)),
+        "WDist" => is_valid_wdist(value),
+        "CVec" => is_valid_cvec(value),
+        _ => true,
+    }
+}
+
+/// Whether `value` is a `WDist`, such as `1024`, `1c0`, or `-2c512`
+fn is_valid_wdist(value: &str) -> bool {
+    let components: Vec<_> = value.split('c').collect();
+
+    match components.as_slice() {
+        [length] => length.parse::<i32>().is_ok(),
+        [cells, subcells] => cells.parse::<i32>().is_ok() && subcells.parse::<i32>().is_ok(),
+        _ => false,
+    }
+}
+
+/// Whether `value` is a `CVec`, such as `1,-2`
+fn is_valid_cvec(value: &str) -> bool {
+    let components: Vec<_> = value.split(',').map(str::trim).collect();
+    components.len() == 2 && components.iter().all(|component| component.parse::<i32>().is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use oraide_span::{
+        FileId,
+        FileSpan,
+    };
+
+    use oraide_parser_miniyaml::Parse;
+
+    use oraide_sdk::{
+        RuleNode,
+        rule_nodes_from_tree,
+    };
+
+    use super::{
+        is_valid_value,
+        trait_name,
+        validate_definition,
+        validate_required_traits,
+    };
+
+    use crate::types::{
+        NamespacedType,
+        TraitDetail,
+        TraitProperty,
+        TraitPropertyKind,
+    };
+
+    fn property(type_name: &str, valid_values: Option<Vec<&str>>) -> TraitProperty {
+        TraitProperty {
+            kind: TraitPropertyKind::Single,
+            type_name: type_name.into(),
+            human_friendly_type_name: type_name.into(),
+            name: "Prop".into(),
+            doc_lines: None,
+            default_value: None,
+            valid_values: valid_values.map(|values| values.into_iter().map(String::from).collect()),
+        }
+    }
+
+    fn trait_detail(name: &str, properties: Vec<TraitProperty>, required_trait_names: &[&str]) -> TraitDetail {
+        TraitDetail {
+            defining_assembly_name: "OpenRA.Mods.Common".into(),
+            is_conditional: false,
+            required_traits: required_trait_names.iter()
+                .map(|required_name| NamespacedType {
+                    namespace: "OpenRA.Mods.Common.Traits".into(),
+                    name: (*required_name).into(),
+                })
+                .collect(),
+            properties,
+            doc_lines: None,
+            namespace: "OpenRA.Mods.Common.Traits".into(),
+            name: name.into(),
+        }
+    }
+
+    /// `Health` with an `Int32` `HP` property, and `Mobile` which requires
+    /// `Health` and `IPositionable` (an interface, not a trait)
+    fn type_data() -> Vec<TraitDetail> {
+        vec![
+            trait_detail("Health", vec![TraitProperty { name: "HP".into(), ..property("Int32", None) }], &[]),
+            trait_detail("Mobile", vec![], &["Health", "IPositionable"]),
+        ]
+    }
+
+    fn definition(text: &str) -> RuleNode {
+        let parse = Parse::new(FileId(0), text);
+        rule_nodes_from_tree(&parse.tree(), text).remove(0)
+    }
+
+    /// The span of the first occurrence of `needle` in `text`
+    fn span_of(text: &str, needle: &str) -> FileSpan {
+        let start = text.find(needle).expect("TEST LOGIC ERROR: `needle` is not in `text`");
+        FileSpan::new(FileId(0), start, start + needle.len())
+    }
+
+    #[test]
+    fn trait_name_ignores_suffix() {
+        assert_eq!(trait_name("Armament@PRIMARY"), "Armament");
+        assert_eq!(trait_name("Health"), "Health");
+    }
+
+    #[test]
+    fn primitive_values() {
+        assert!(is_valid_value(&property("Int32", None), "-50"));
+        assert!(!is_valid_value(&property("Int32", None), "fifty"));
+        assert!(is_valid_value(&property("Boolean", None), "True"));
+        assert!(is_valid_value(&property("Boolean", None), "yes"));
+        assert!(is_valid_value(&property("Boolean", None), "No"));
+        assert!(!is_valid_value(&property("Boolean", None), "maybe"));
+    }
+
+    #[test]
+    fn wdist_and_cvec_values() {
+        assert!(is_valid_value(&property("WDist", None), "1c512"));
+        assert!(is_valid_value(&property("WDist", None), "-2c0"));
+        assert!(is_valid_value(&property("WDist", None), "1024"));
+        assert!(!is_valid_value(&property("WDist", None), "1c"));
+        assert!(is_valid_value(&property("CVec", None), "1,-2"));
+        assert!(!is_valid_value(&property("CVec", None), "1"));
+    }
+
+    #[test]
+    fn enum_values_are_case_insensitive_and_may_be_combined() {
+        let prop = property("TargetType", Some(vec!["Ground", "Water", "Air"]));
+
+        assert!(is_valid_value(&prop, "ground, Water"));
+        assert!(!is_valid_value(&prop, "Space"));
+    }
+
+    #[test]
+    fn known_traits_properties_and_values_are_valid() {
+        // Arrange
+        let text = "E1:\n\tInherits: ^Infantry\n\tHealth@BASE:\n\t\tHP: 50\n\t\t-Shape:\n\t-Mobile:\n";
+
+        // Act
+        let actual = validate_definition(&type_data(), &definition(text));
+
+        // Assert
+        assert_eq!(actual, vec![]);
+    }
+
+    #[test]
+    fn unknown_trait_is_an_error() {
+        // Arrange
+        let text = "E1:\n\tHeatlh:\n\t\tHP: 50\n";
+
+        // Act
+        let actual = validate_definition(&type_data(), &definition(text));
+
+        // Assert
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].code, Some("S:E0006".to_owned()));
+        assert_eq!(actual[0].message, "Unknown trait `Heatlh`");
+        assert_eq!(actual[0].primary_span(), Some(span_of(text, "Heatlh")));
+    }
+
+    #[test]
+    fn unknown_property_is_an_error() {
+        // Arrange
+        let text = "E1:\n\tHealth:\n\t\tHitPoints: 50\n";
+
+        // Act
+        let actual = validate_definition(&type_data(), &definition(text));
+
+        // Assert
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].code, Some("S:E0007".to_owned()));
+        assert_eq!(actual[0].message, "Unknown property `HitPoints` on trait `Health`");
+        assert_eq!(actual[0].primary_span(), Some(span_of(text, "HitPoints")));
+    }
+
+    #[test]
+    fn value_of_the_wrong_type_is_an_error() {
+        // Arrange
+        let text = "E1:\n\tHealth:\n\t\tHP: fifty\n";
+
+        // Act
+        let actual = validate_definition(&type_data(), &definition(text));
+
+        // Assert
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].code, Some("S:E0008".to_owned()));
+        assert_eq!(actual[0].message, "Invalid value `fifty` for property `HP`");
+        assert_eq!(actual[0].primary_span(), Some(span_of(text, "fifty")));
+        assert_eq!(actual[0].help_notes, vec!["Expected a value of type `Int32`".to_owned()]);
+    }
+
+    #[test]
+    fn missing_required_trait_is_an_error() {
+        // Arrange
+        let text = "E1:\n\tMobile:\n";
+
+        // Act
+        let actual = validate_required_traits(&type_data(), &definition(text));
+
+        // Assert
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].code, Some("S:E0009".to_owned()));
+        assert_eq!(actual[0].message, "`Mobile` requires the `Health` trait, which `E1` does not have");
+        assert_eq!(actual[0].primary_span(), Some(span_of(text, "E1")));
+        assert_eq!(actual[0].labels[1].span, span_of(text, "Mobile"));
+    }
+
+    #[test]
+    fn present_required_traits_and_unknown_requirements_are_not_errors() {
+        // Arrange
+        let text = "E1:\n\tMobile:\n\tHealth@BASE:\n";
+
+        // Act
+        let actual = validate_required_traits(&type_data(), &definition(text));
+
+        // Assert
+        assert_eq!(actual, vec![]);
+    }
+}
\ No newline at end of file
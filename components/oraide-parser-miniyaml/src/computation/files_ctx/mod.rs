@@ -22,6 +22,9 @@ use {
     oraide_actor::{
         Position,
     },
+    crate::{
+        Parse,
+    },
 };
 
 mod queries;
@@ -72,6 +75,21 @@ pub trait TextFilesCtx: FilesCtx {
         file_id: FileId,
     ) -> Option<String>;
 
+    /// [`Parse`] of the file that was assigned a given [`FileId`], if it was
+    /// computed incrementally when the file's text was last edited
+    ///
+    /// This must be set to `None` whenever the file's text is set without
+    /// going through [`ParserCtxExt::edit_file_text`].
+    ///
+    /// [`Parse`]: struct.Parse.html
+    /// [`FileId`]: struct.FileId.html
+    /// [`ParserCtxExt::edit_file_text`]: trait.ParserCtxExt.html#method.edit_file_text
+    #[salsa::input]
+    fn file_reparse(
+        &self,
+        file_id: FileId,
+    ) -> Option<Parse>;
+
     /// Compute all line start offsets in byte indices
     #[salsa::invoke(queries::line_start_offsets)]
     fn line_start_offsets(
@@ -137,7 +155,40 @@ pub trait TextFilesCtxExt: TextFilesCtx {
         self.set_file_path(file_id, file_path.into());
         self.set_all_file_ids(all_file_ids);
         self.set_file_text(file_id, file_text.into());
+        self.set_file_reparse(file_id, None);
 
         file_id
     }
+
+    /// Replace all of the text of `file_id`
+    ///
+    /// Prefer [`ParserCtxExt::edit_file_text`] when only part of the text
+    /// has changed.
+    ///
+    /// [`ParserCtxExt::edit_file_text`]: trait.ParserCtxExt.html#method.edit_file_text
+    fn replace_file_text(
+        &mut self,
+        file_id: FileId,
+        file_text: impl Into<String>,
+    ) {
+        self.set_file_text(file_id, Some(file_text.into()));
+        self.set_file_reparse(file_id, None);
+    }
+
+    /// Forget the text of `file_id`, such as when a file that only existed
+    /// in an editor is closed, so that it is no longer part of any
+    /// computation
+    ///
+    /// The [`FileId`] stays assigned to its path, so the file can be given
+    /// text again with [`replace_file_text`].
+    ///
+    /// [`FileId`]: ../oraide-span/struct.FileId.html
+    /// [`replace_file_text`]: #method.replace_file_text
+    fn clear_file_text(
+        &mut self,
+        file_id: FileId,
+    ) {
+        self.set_file_text(file_id, None);
+        self.set_file_reparse(file_id, None);
+    }
 }
\ No newline at end of file
@@ -108,9 +108,9 @@ pub(crate) fn convert_byte_index_to_location(
             // We found something in the middle
             let line_start_idx = line_start_offsets[line_idx];
 
-            // Count utf-8 chars to determine column
+            // Count utf-8 chars to determine column, which is 1-based
             let file_text = db.file_text(file_id)?;
-            let column = file_text[line_start_idx..byte_index].chars().count();
+            let column = file_text[line_start_idx..byte_index].chars().count() + 1;
 
             Location::new(next_line_num, column)
         },
@@ -13,15 +13,22 @@
 // along with oraide.  If not, see <https://www.gnu.org/licenses/>.
 
 use {
+    std::{
+        ops::Range,
+    },
     oraide_span::{
         FileId,
         ByteIndex,
     },
     crate::{
+        Parse,
         Token,
         Node,
         Tree,
+        Diagnostic,
         TextFilesCtx,
+        FormatOptions,
+        IndentStyle,
     },
 };
 
@@ -30,6 +37,27 @@ mod queries;
 /// Provides MiniYaml-parsing inputs & queries
 #[salsa::query_group(ParserCtxStorage)]
 pub trait ParserCtx: TextFilesCtx {
+    /// Compute the [`Parse`] of a [`FileId`]
+    ///
+    /// This is the incrementally-computed [`Parse`] if the file was last
+    /// changed via [`ParserCtxExt::edit_file_text`], otherwise the entire
+    /// file is parsed.
+    ///
+    /// [`Parse`]: struct.Parse.html
+    /// [`FileId`]: struct.FileId.html
+    /// [`ParserCtxExt::edit_file_text`]: trait.ParserCtxExt.html#method.edit_file_text
+    #[salsa::invoke(queries::file_parse)]
+    fn file_parse(&self, file_id: FileId) -> Option<Parse>;
+
+    /// Compute all of the [`Token`]s in a [`FileId`] along with any
+    /// [`Diagnostic`]s produced while tokenizing
+    ///
+    /// [`Token`]: struct.Token.html
+    /// [`FileId`]: struct.FileId.html
+    /// [`Diagnostic`]: struct.Diagnostic.html
+    #[salsa::invoke(queries::file_tokens_with_diagnostics)]
+    fn file_tokens_with_diagnostics(&self, file_id: FileId) -> Option<(Vec<Token>, Vec<Diagnostic>)>;
+
     /// Compute all of the [`Token`]s in a [`FileId`]
     ///
     /// [`Token`]: struct.Token.html
@@ -37,6 +65,15 @@ pub trait ParserCtx: TextFilesCtx {
     #[salsa::invoke(queries::file_tokens)]
     fn file_tokens(&self, file_id: FileId) -> Option<Vec<Token>>;
 
+    /// Compute all of the [`Node`]s in a [`FileId`] along with any
+    /// [`Diagnostic`]s produced while nodeizing
+    ///
+    /// [`Node`]: struct.Node.html
+    /// [`FileId`]: struct.FileId.html
+    /// [`Diagnostic`]: struct.Diagnostic.html
+    #[salsa::invoke(queries::file_nodes_with_diagnostics)]
+    fn file_nodes_with_diagnostics(&self, file_id: FileId) -> Option<(Vec<Node>, Vec<Diagnostic>)>;
+
     /// Compute all of the [`Node`]s in a [`FileId`]
     ///
     /// [`Node`]: struct.Node.html
@@ -44,6 +81,15 @@ pub trait ParserCtx: TextFilesCtx {
     #[salsa::invoke(queries::file_nodes)]
     fn file_nodes(&self, file_id: FileId) -> Option<Vec<Node>>;
 
+    /// Compute the [`Tree`] of a [`FileId`] along with any
+    /// [`Diagnostic`]s produced while treeizing
+    ///
+    /// [`Tree`]: struct.Tree.html
+    /// [`FileId`]: struct.FileId.html
+    /// [`Diagnostic`]: struct.Diagnostic.html
+    #[salsa::invoke(queries::file_tree_with_diagnostics)]
+    fn file_tree_with_diagnostics(&self, file_id: FileId) -> Option<(Tree, Vec<Diagnostic>)>;
+
     /// Compute the [`Tree`] of a [`FileId`]
     ///
     /// [`Tree`]: struct.Tree.html
@@ -51,6 +97,15 @@ pub trait ParserCtx: TextFilesCtx {
     #[salsa::invoke(queries::file_tree)]
     fn file_tree(&self, file_id: FileId) -> Option<Tree>;
 
+    /// Compute all of the [`Diagnostic`]s produced while parsing a [`FileId`]
+    ///
+    /// This includes diagnostics from tokenizing, nodeizing, and treeizing.
+    ///
+    /// [`Diagnostic`]: struct.Diagnostic.html
+    /// [`FileId`]: struct.FileId.html
+    #[salsa::invoke(queries::file_diagnostics)]
+    fn file_diagnostics(&self, file_id: FileId) -> Option<Vec<Diagnostic>>;
+
     /// Find the top-level `Node` in `file_id` with `key`
     #[salsa::invoke(queries::top_level_node_by_key_in_file)]
     fn top_level_node_by_key_in_file(
@@ -93,4 +148,54 @@ pub trait ParserCtx: TextFilesCtx {
         file_id: FileId,
         byte_index: ByteIndex,
     ) -> Option<Node>;
+
+    /// Determine the [`IndentStyle`] already used in `file_id`, if any
+    ///
+    /// [`IndentStyle`]: enum.IndentStyle.html
+    #[salsa::invoke(queries::indent_style_of_file)]
+    fn indent_style_of_file(&self, file_id: FileId) -> Option<IndentStyle>;
+
+    /// Compute the formatted text of `file_id`
+    ///
+    /// # Returns
+    /// `None` if `file_id` has error diagnostics, since its structure can
+    /// not be known for certain
+    #[salsa::invoke(queries::formatted_file_text)]
+    fn formatted_file_text(&self, file_id: FileId, options: FormatOptions) -> Option<String>;
+}
+
+pub trait ParserCtxExt: ParserCtx {
+    /// Replace `range` (byte indices) of `file_id`'s text with `new_text`
+    ///
+    /// Only the lines touched by the edit are reparsed, see [`Parse::reparse`].
+    ///
+    /// # Returns
+    /// `None` if `file_id` has no text or `range` is not a valid range of
+    /// its text
+    ///
+    /// [`Parse::reparse`]: struct.Parse.html#method.reparse
+    fn edit_file_text(
+        &mut self,
+        file_id: FileId,
+        range: Range<usize>,
+        new_text: &str,
+    ) -> Option<()> {
+        let mut file_text = self.file_text(file_id)?;
+
+        if range.start > range.end
+            || !file_text.is_char_boundary(range.start)
+            || !file_text.is_char_boundary(range.end) {
+            return None;
+        }
+
+        let parse = self.file_parse(file_id)?;
+
+        file_text.replace_range(range.clone(), new_text);
+        let reparse = parse.reparse(file_id, &file_text, range, new_text.len());
+
+        self.set_file_text(file_id, Some(file_text));
+        self.set_file_reparse(file_id, Some(reparse));
+
+        Some(())
+    }
 }
\ No newline at end of file
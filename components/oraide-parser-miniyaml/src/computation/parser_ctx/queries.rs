@@ -18,42 +18,90 @@ use {
         ByteIndex,
     },
     crate::{
+        Parse,
         Token,
-        Tokenizer,
         Node,
-        Nodeizer,
         Tree,
-        Treeizer,
+        Diagnostic,
         ParserCtx,
+        FormatOptions,
+        IndentStyle,
+        format_nodes,
     },
 };
 
+pub(crate) fn file_parse(
+    db: &impl ParserCtx,
+    file_id: FileId,
+) -> Option<Parse> {
+    if let Some(parse) = db.file_reparse(file_id) {
+        return Some(parse);
+    }
+
+    let file_text = db.file_text(file_id)?;
+    Parse::new(file_id, &file_text).into()
+}
+
+pub(crate) fn file_tokens_with_diagnostics(
+    db: &impl ParserCtx,
+    file_id: FileId,
+) -> Option<(Vec<Token>, Vec<Diagnostic>)> {
+    let parse = db.file_parse(file_id)?;
+    (parse.tokens(), parse.tokenizer_diagnostics()).into()
+}
+
 pub(crate) fn file_tokens(
     db: &impl ParserCtx,
     file_id: FileId,
 ) -> Option<Vec<Token>> {
-    let file_text = db.file_text(file_id)?;
-    let mut tokenizer = Tokenizer::new(file_id, &file_text);
-    tokenizer.run().into()
+    let (tokens, _diagnostics) = db.file_tokens_with_diagnostics(file_id)?;
+    tokens.into()
+}
+
+pub(crate) fn file_nodes_with_diagnostics(
+    db: &impl ParserCtx,
+    file_id: FileId,
+) -> Option<(Vec<Node>, Vec<Diagnostic>)> {
+    let parse = db.file_parse(file_id)?;
+    (parse.nodes(), parse.nodeizer_diagnostics()).into()
 }
 
 pub(crate) fn file_nodes(
     db: &impl ParserCtx,
     file_id: FileId,
 ) -> Option<Vec<Node>> {
-    let tokens = db.file_tokens(file_id)?;
-    let mut nodeizer = Nodeizer::new(tokens.into_iter());
-    nodeizer.run().into()
+    let (nodes, _diagnostics) = db.file_nodes_with_diagnostics(file_id)?;
+    nodes.into()
+}
+
+pub(crate) fn file_tree_with_diagnostics(
+    db: &impl ParserCtx,
+    file_id: FileId,
+) -> Option<(Tree, Vec<Diagnostic>)> {
+    let parse = db.file_parse(file_id)?;
+    (parse.tree(), parse.treeizer_diagnostics()).into()
 }
 
 pub(crate) fn file_tree(
     db: &impl ParserCtx,
     file_id: FileId,
 ) -> Option<Tree> {
-    let nodes = db.file_nodes(file_id)?;
-    let file_text = db.file_text(file_id)?;
-    let mut treeizer = Treeizer::new(nodes.into_iter(), &file_text);
-    treeizer.run().into()
+    let (tree, _diagnostics) = db.file_tree_with_diagnostics(file_id)?;
+    tree.into()
+}
+
+pub(crate) fn file_diagnostics(
+    db: &impl ParserCtx,
+    file_id: FileId,
+) -> Option<Vec<Diagnostic>> {
+    let (_tokens, token_diags) = db.file_tokens_with_diagnostics(file_id)?;
+    let (_nodes, node_diags) = db.file_nodes_with_diagnostics(file_id)?;
+    let (_tree, tree_diags) = db.file_tree_with_diagnostics(file_id)?;
+
+    let mut diagnostics = token_diags;
+    diagnostics.extend(node_diags);
+    diagnostics.extend(tree_diags);
+    diagnostics.into()
 }
 
 pub(crate) fn top_level_node_by_key_in_file(
@@ -111,4 +159,28 @@ pub(crate) fn node_spanning_byte_index_in_file(
     let nodes = db.file_nodes(file_id)?;
     nodes.into_iter()
         .find(|node| node.span().map(|span| span.contains(byte_index)).unwrap_or(false))
+}
+
+pub(crate) fn indent_style_of_file(
+    db: &impl ParserCtx,
+    file_id: FileId,
+) -> Option<IndentStyle> {
+    let file_text = db.file_text(file_id)?;
+    let nodes = db.file_nodes(file_id)?;
+    IndentStyle::detect(&nodes, &file_text)
+}
+
+pub(crate) fn formatted_file_text(
+    db: &impl ParserCtx,
+    file_id: FileId,
+    options: FormatOptions,
+) -> Option<String> {
+    let diagnostics = db.file_diagnostics(file_id)?;
+    if diagnostics.iter().any(Diagnostic::is_error) {
+        return None;
+    }
+
+    let file_text = db.file_text(file_id)?;
+    let nodes = db.file_nodes(file_id)?;
+    format_nodes(&nodes, &file_text, &options).into()
 }
\ No newline at end of file
@@ -0,0 +1,201 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+//! # `diagnostic`
+//!
+//! Types used to report problems found in a text document to the user.
+//!
+//! The shape of these types is modeled after those found in the
+//! `language-reporting` crate (which `oraide-miniyaml` uses) so that
+//! diagnostics can be built up in the same builder-like fashion:
+//!
+//! ```rust
+//! # use oraide_span::{FileId,FileSpan};
+//! # use oraide_parser_miniyaml::{Diagnostic,Label,Severity};
+//! let span = FileSpan::new(FileId(0), 0, 2);
+//!
+//! let diag = Diagnostic::new_error("Found a problem")
+//!     .with_code("A:E0001")
+//!     .with_label(Label::new_primary(span))
+//!     .with_help("Consider fixing it");
+//!
+//! assert_eq!(diag.severity, Severity::Error);
+//! assert_eq!(diag.primary_span(), Some(span));
+//! ```
+
+use std::{
+    fmt,
+};
+
+use oraide_span::{
+    FileSpan,
+};
+
+/// How severe a [`Diagnostic`] is
+///
+/// Variants are ordered from least to most severe so they can be compared.
+///
+/// [`Diagnostic`]: struct.Diagnostic.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Additional information that may be of interest to the user
+    Note,
+
+    /// Something that is valid, but probably not what the user intended
+    Warning,
+
+    /// Something that is invalid and will cause the game to misbehave or fail to load
+    Error,
+
+    /// Something went wrong inside of oraide itself, not in the user's document
+    Bug,
+}
+
+impl Severity {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Bug => "bug",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
+/// Used to indicate whether a [`Label`] points at the cause of a [`Diagnostic`]
+/// or merely at some related text
+///
+/// [`Label`]: struct.Label.html
+/// [`Diagnostic`]: struct.Diagnostic.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LabelStyle {
+    Primary,
+    Secondary,
+}
+
+/// A (potentially-messaged) span of text that a [`Diagnostic`] refers to
+///
+/// [`Diagnostic`]: struct.Diagnostic.html
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Label {
+    pub style: LabelStyle,
+    pub span: FileSpan,
+    pub message: Option<String>,
+}
+
+impl Label {
+    pub fn new(style: LabelStyle, span: FileSpan) -> Self {
+        Self {
+            style,
+            span,
+            message: None,
+        }
+    }
+
+    pub fn new_primary(span: FileSpan) -> Self {
+        Self::new(LabelStyle::Primary, span)
+    }
+
+    pub fn new_secondary(span: FileSpan) -> Self {
+        Self::new(LabelStyle::Secondary, span)
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+/// A problem found in (or related to) a text document
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Diagnostic {
+    pub severity: Severity,
+
+    /// A short identifier, such as `A:E0001`, that is unique to the kind
+    /// of problem being reported
+    pub code: Option<String>,
+
+    pub message: String,
+
+    /// The spans of text this diagnostic refers to, typically with the
+    /// first being [`LabelStyle::Primary`]
+    ///
+    /// [`LabelStyle::Primary`]: enum.LabelStyle.html#variant.Primary
+    pub labels: Vec<Label>,
+
+    /// Suggestions that may help the user address this diagnostic
+    pub help_notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            code: None,
+            message: message.into(),
+            labels: vec![],
+            help_notes: vec![],
+        }
+    }
+
+    pub fn new_bug(message: impl Into<String>) -> Self {
+        Self::new(Severity::Bug, message)
+    }
+
+    pub fn new_error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn new_warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn new_note(message: impl Into<String>) -> Self {
+        Self::new(Severity::Note, message)
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    pub fn with_label(mut self, label: Label) -> Self {
+        self.labels.push(label);
+        self
+    }
+
+    pub fn with_help(mut self, help_note: impl Into<String>) -> Self {
+        self.help_notes.push(help_note.into());
+        self
+    }
+
+    /// The span of the first [`LabelStyle::Primary`] label, if any
+    ///
+    /// [`LabelStyle::Primary`]: enum.LabelStyle.html#variant.Primary
+    pub fn primary_span(&self) -> Option<FileSpan> {
+        self.labels.iter()
+            .find(|label| label.style == LabelStyle::Primary)
+            .map(|label| label.span)
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity >= Severity::Error
+    }
+}
\ No newline at end of file
@@ -0,0 +1,250 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+//! # `formatter`
+//!
+//! Pretty-print MiniYaml text in a consistent style
+//!
+//! ---
+//!
+//! The formatter works line-by-line on the `Node`s produced by the
+//! `nodeizer`, so it never drops text, and:
+//!
+//! - normalizes indentation to tabs or 4-space levels
+//! - trims trailing whitespace
+//! - normalizes the spacing around `:` (`Key: Value`)
+//! - collapses runs of blank lines into a single blank line
+//! - keeps comments on the line they were written on
+//!
+//! Text that does not parse cleanly should not be formatted since its
+//! structure (and so its indentation) is not known for certain.
+
+use std::{
+    ops::Range,
+};
+
+use crate::{
+    Node,
+    TokenKind,
+};
+
+/// How indentation is written by the formatter
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum IndentStyle {
+    /// 1 tab per indentation level (what OpenRA's own files use)
+    Tabs,
+
+    /// 4 spaces per indentation level
+    Spaces,
+}
+
+impl IndentStyle {
+    /// Determine the indentation style used by `nodes`, which were
+    /// created from `text`, by looking at the first indented node
+    ///
+    /// # Returns
+    /// `None` if no nodes are indented
+    pub fn detect(nodes: &[Node], text: &str) -> Option<IndentStyle> {
+        let indentation_text = nodes.iter()
+            .filter(|node| !node.is_whitespace_only())
+            .filter_map(|node| node.indentation_token.as_ref())
+            .filter_map(|token| token.span.text(text))
+            .next()?;
+
+        if indentation_text.starts_with('\t') {
+            Some(IndentStyle::Tabs)
+        } else {
+            Some(IndentStyle::Spaces)
+        }
+    }
+
+    /// The indentation of a line at `level` in this style
+    pub fn indentation(self, level: usize) -> String {
+        match self {
+            IndentStyle::Tabs => "\t".repeat(level),
+            IndentStyle::Spaces => " ".repeat(level * 4),
+        }
+    }
+}
+
+/// Options that control how text is formatted
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FormatOptions {
+    pub indent_style: IndentStyle,
+
+    /// The (0-based) lines to format, with all other lines being left as-is,
+    /// or `None` to format every line
+    pub line_range: Option<Range<usize>>,
+}
+
+impl FormatOptions {
+    pub fn new(indent_style: IndentStyle) -> Self {
+        Self {
+            indent_style,
+            line_range: None,
+        }
+    }
+
+    pub fn with_line_range(mut self, line_range: Range<usize>) -> Self {
+        self.line_range = Some(line_range);
+        self
+    }
+
+    fn includes_line(&self, line_idx: usize) -> bool {
+        self.line_range.as_ref().map_or(true, |range| range.contains(&line_idx))
+    }
+}
+
+/// Format `nodes`, which must have been created from `text`
+///
+/// # Example
+/// ```rust
+/// # use oraide_span::{FileId};
+/// # use oraide_parser_miniyaml::{Tokenizer,Nodeizer,FormatOptions,IndentStyle,format_nodes};
+/// let text = "E1:  \n  \n\n    Inherits:^Soldier   # the basic infantry\n";
+///
+/// let mut tokenizer = Tokenizer::new(FileId(0), text);
+/// let mut nodeizer = Nodeizer::new(tokenizer.run().into_iter());
+/// let nodes = nodeizer.run();
+///
+/// let formatted = format_nodes(&nodes, text, &FormatOptions::new(IndentStyle::Tabs));
+/// assert_eq!(formatted, "E1:\n\n\tInherits: ^Soldier # the basic infantry\n");
+/// ```
+pub fn format_nodes(nodes: &[Node], text: &str, options: &FormatOptions) -> String {
+    let line_ending = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let line_start_offsets = line_start_offsets(text);
+    let mut lines = Vec::<String>::with_capacity(nodes.len());
+    let mut is_last_line_included = true;
+
+    for node in nodes.iter() {
+        let line_idx = line_idx_of_node(node, &line_start_offsets);
+        is_last_line_included = options.includes_line(line_idx);
+
+        if !is_last_line_included {
+            lines.push(node_text(node, text).to_owned());
+            continue;
+        }
+
+        let formatted = format_node(node, text, options.indent_style);
+
+        // Never start the text with, or repeat, blank lines
+        if formatted.is_empty() && lines.last().map_or(true, String::is_empty) {
+            continue;
+        }
+
+        lines.push(formatted);
+    }
+
+    if is_last_line_included {
+        while lines.last().map_or(false, String::is_empty) {
+            lines.pop();
+        }
+    }
+
+    let mut formatted_text = lines.join(line_ending);
+
+    // Only formatting the whole text adds a missing end-of-line to the last
+    // line, since that is outside of any range that was given
+    let ends_with_line_ending = text.ends_with('\n') || options.line_range.is_none();
+
+    if !formatted_text.is_empty() && ends_with_line_ending {
+        formatted_text.push_str(line_ending);
+    }
+
+    formatted_text
+}
+
+/// The byte offset that each line of `text` starts at
+fn line_start_offsets(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+        .collect()
+}
+
+/// The (0-based) line that `node` is on, given the `line_start_offsets` of
+/// the text it was created from
+///
+/// Nodes are not assumed to be one per line, so the line is found from the
+/// position of the node's first token.
+fn line_idx_of_node(node: &Node, line_start_offsets: &[usize]) -> usize {
+    let start = match node.tokens().next() {
+        Some(token) => token.span.start().to_usize(),
+        None => return line_start_offsets.len() - 1,
+    };
+
+    match line_start_offsets.binary_search(&start) {
+        Ok(line_idx) => line_idx,
+        Err(next_line_idx) => next_line_idx - 1,
+    }
+}
+
+/// The unmodified text of `node`, without its end-of-line
+fn node_text<'text>(node: &Node, text: &'text str) -> &'text str {
+    node.span()
+        .and_then(|span| span.text(text))
+        .unwrap_or("")
+}
+
+/// The indentation level of `node`, counting each tab, or 4 spaces, as a level
+fn indentation_level(node: &Node, text: &str) -> usize {
+    let indentation_text = node.indentation_token.as_ref()
+        .filter(|token| token.kind == TokenKind::Whitespace)
+        .and_then(|token| token.span.text(text))
+        .unwrap_or("");
+
+    let tab_count = indentation_text.chars().filter(|ch| *ch == '\t').count();
+    let space_count = indentation_text.chars().filter(|ch| *ch == ' ').count();
+
+    tab_count + space_count / 4
+}
+
+fn format_node(node: &Node, text: &str, indent_style: IndentStyle) -> String {
+    let key_text = node.key_text(text).map(str::trim_end);
+    let value_text = node.value_text(text);
+    let comment_text = node.comment_token.as_ref()
+        .and_then(|token| token.span.text(text))
+        .map(str::trim_end);
+
+    if key_text.is_none() && value_text.is_none() && comment_text.is_none() {
+        return String::new();
+    }
+
+    let mut line = indent_style.indentation(indentation_level(node, text));
+
+    if let Some(key_text) = key_text {
+        line.push_str(key_text);
+    }
+
+    if node.key_terminator_token.is_some() {
+        line.push(':');
+    }
+
+    if let Some(value_text) = value_text {
+        line.push(' ');
+        line.push_str(value_text);
+    }
+
+    if let Some(comment_text) = comment_text {
+        if key_text.is_some() || value_text.is_some() {
+            line.push(' ');
+        }
+
+        line.push_str(comment_text);
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod tests;
\ No newline at end of file
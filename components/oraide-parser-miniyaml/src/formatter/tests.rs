@@ -0,0 +1,159 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+use pretty_assertions::assert_eq;
+
+use oraide_span::{
+    FileId,
+};
+
+use crate::{
+    Tokenizer,
+    Nodeizer,
+    FormatOptions,
+    IndentStyle,
+    format_nodes,
+};
+
+fn format(src: &str, options: &FormatOptions) -> String {
+    let mut tokenizer = Tokenizer::new(FileId(0), src);
+    let tokens = tokenizer.run();
+
+    let mut nodeizer = Nodeizer::new(tokens.into_iter());
+    let nodes = nodeizer.run();
+
+    format_nodes(&nodes, src, options)
+}
+
+#[test]
+fn indentation_is_normalized() {
+    // Arrange
+    let src = "E1:\n\tInherits: ^Soldier\n\tHealth:\n\t\tHP: 50\n";
+
+    // Act
+    let actual = format(src, &FormatOptions::new(IndentStyle::Spaces));
+
+    // Assert
+    assert_eq!(actual, "E1:\n    Inherits: ^Soldier\n    Health:\n        HP: 50\n");
+}
+
+#[test]
+fn key_value_spacing_and_trailing_whitespace_are_normalized() {
+    // Arrange
+    let src = "E1 :   \n    Tooltip:Name:   Rifle Infantry  \n    Valued:\n        Cost:100#cheap  \n";
+
+    // Act
+    let actual = format(src, &FormatOptions::new(IndentStyle::Spaces));
+
+    // Assert
+    assert_eq!(actual, "E1:\n    Tooltip: Name:   Rifle Infantry\n    Valued:\n        Cost: 100 #cheap\n");
+}
+
+#[test]
+fn blank_lines_are_collapsed_and_comments_are_kept() {
+    // Arrange
+    let src = "\n\n# Infantry\nE1:\n\n\n\n    # Shoots things\n    Armament:\n\n\n";
+
+    // Act
+    let actual = format(src, &FormatOptions::new(IndentStyle::Tabs));
+
+    // Assert
+    assert_eq!(actual, "# Infantry\nE1:\n\n\t# Shoots things\n\tArmament:\n");
+}
+
+#[test]
+fn last_line_without_end_of_line_is_formatted() {
+    // Arrange
+    let src = "E1:\n    Armament:Weapon:M1Carbine";
+
+    // Act
+    let actual = format(src, &FormatOptions::new(IndentStyle::Tabs));
+
+    // Assert
+    assert_eq!(actual, "E1:\n\tArmament: Weapon:M1Carbine\n");
+}
+
+#[test]
+fn lines_outside_of_range_are_unchanged() {
+    // Arrange
+    let src = "E1 :\n    Health :\n        HP:  50\n";
+
+    // Act
+    let actual = format(src, &FormatOptions::new(IndentStyle::Tabs).with_line_range(1..2));
+
+    // Assert
+    assert_eq!(actual, "E1 :\n\tHealth:\n        HP:  50\n");
+}
+
+#[test]
+fn range_ending_at_last_line_does_not_add_end_of_line() {
+    // Arrange
+    let src = "E1 :\n    Health :\n        HP:  50";
+
+    // Act
+    let actual = format(src, &FormatOptions::new(IndentStyle::Tabs).with_line_range(1..3));
+
+    // Assert
+    assert_eq!(actual, "E1 :\n\tHealth:\n\t\tHP: 50");
+}
+
+#[test]
+fn range_before_last_line_does_not_add_end_of_line() {
+    // Arrange
+    let src = "E1 :\n    Health :\n        HP:  50";
+
+    // Act
+    let actual = format(src, &FormatOptions::new(IndentStyle::Tabs).with_line_range(0..1));
+
+    // Assert
+    assert_eq!(actual, "E1:\n    Health :\n        HP:  50");
+}
+
+#[test]
+fn crlf_line_endings_are_kept() {
+    // Arrange
+    let src = "E1 :\r\n    Health :\r\n\r\n\r\n        HP:  50\r\n";
+
+    // Act
+    let actual = format(src, &FormatOptions::new(IndentStyle::Tabs));
+
+    // Assert
+    assert_eq!(actual, "E1:\r\n\tHealth:\r\n\r\n\t\tHP: 50\r\n");
+}
+
+#[test]
+fn range_in_crlf_text_formats_only_its_lines() {
+    // Arrange
+    let src = "E1 :\r\n    Health :\r\n        HP:  50\r\n";
+
+    // Act
+    let actual = format(src, &FormatOptions::new(IndentStyle::Tabs).with_line_range(2..3));
+
+    // Assert
+    assert_eq!(actual, "E1 :\r\n    Health :\r\n\t\tHP: 50\r\n");
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    // Arrange
+    let src = "^Infantry:\n    Health:\n        HP: 50 # default\n\n\nE1:\n    Inherits: ^Infantry\n";
+    let options = FormatOptions::new(IndentStyle::Tabs);
+
+    // Act
+    let once = format(src, &options);
+    let twice = format(&once, &options);
+
+    // Assert
+    assert_eq!(once, twice);
+}
\ No newline at end of file
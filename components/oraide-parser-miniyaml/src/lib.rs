@@ -12,6 +12,21 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with oraide.  If not, see <https://www.gnu.org/licenses/>.
 
+mod diagnostic;
+pub use diagnostic::{
+    Diagnostic,
+    Label,
+    LabelStyle,
+    Severity,
+};
+
+mod formatter;
+pub use formatter::{
+    FormatOptions,
+    IndentStyle,
+    format_nodes,
+};
+
 mod parser;
 pub use parser::{
     Token,
@@ -25,6 +40,7 @@ pub use parser::{
     ArenaNodeId,
     Tree,
     Treeizer,
+    Parse,
 };
 
 mod computation;
@@ -39,6 +55,7 @@ pub use computation::{
     },
     parser_ctx::{
         ParserCtx,
+        ParserCtxExt,
         ParserCtxStorage,
     },
 };
\ No newline at end of file
@@ -26,6 +26,9 @@
 //!     - input: collection of `Node`s
 //!     - output: a `Tree`
 //!
+//! The `reparser` sub-module combines all 3 into a `Parse` that can be
+//! updated after an edit without reparsing the entire text.
+//!
 //! It also contains types used by the previously-mentioned sub-modules
 //! and other components of this project such as `Token`, `Node`, `Tree`, etc.
 //!
@@ -33,6 +36,7 @@
 mod tokenizer;
 mod nodeizer;
 mod treeizer;
+mod reparser;
 
 pub use tokenizer::{
     Token,
@@ -52,4 +56,8 @@ pub use treeizer::{
     IndentLevelDelta,
     Arena,
     ArenaNodeId,
+};
+
+pub use reparser::{
+    Parse,
 };
\ No newline at end of file
@@ -31,6 +31,8 @@ use crate::{
     Token,
     TokenKind,
     TokenCollectionExts as _,
+    Diagnostic,
+    Label,
 };
 
 /// A [`Node`] is, in the context of textual MiniYaml, a structured collection
@@ -59,6 +61,12 @@ pub struct Node {
 
     /// The comment token, if any
     pub comment_token: Option<Token>,
+
+    /// The token that terminates this node's line, if any
+    /// (the last line of a file may not be terminated)
+    ///
+    /// This is not part of a node's [`span`](#method.span).
+    pub end_of_line_token: Option<Token>,
 }
 
 impl Node {
@@ -69,6 +77,7 @@ impl Node {
             key_terminator_token: None,
             value_tokens: vec![],
             comment_token: None,
+            end_of_line_token: None,
         }
     }
 
@@ -116,11 +125,20 @@ impl Node {
         }
 
         let end_exclusive = span.end_exclusive().to_usize();
-        if end_exclusive >= text.len() {
-            return None;
-        }
+        text.get(start..end_exclusive)
+    }
+
+    /// Get the span of a Node's value, excluding surrounding whitespace, if any exists
+    pub fn value_span(&self) -> Option<FileSpan> {
+        let tokens = self.value_tokens.skip_leading_whitespace();
+        let last_idx = tokens.iter().rposition(|token| token.kind != TokenKind::Whitespace)?;
+        tokens[..=last_idx].span()
+    }
 
-        Some(&text[start..end_exclusive])
+    /// Get the value-portion of a Node's text, excluding surrounding whitespace, if any exists
+    pub fn value_text<'text>(&self, text: &'text str) -> Option<&'text str> {
+        let span = self.value_span()?;
+        text.get(span.start().to_usize()..span.end_exclusive().to_usize())
     }
 
     pub fn is_top_level(&self) -> bool {
@@ -221,9 +239,27 @@ impl Node {
             tokens.push(t);
         }
 
+        if let Some(t) = self.end_of_line_token {
+            tokens.push(t);
+        }
+
         tokens
     }
 
+    /// Iterate over every [`Token`] in this [`Node`], in source order,
+    /// including the end-of-line token
+    ///
+    /// [`Node`]: struct.Node.html
+    /// [`Token`]: struct.Token.html
+    pub fn tokens(&self) -> impl Iterator<Item = &Token> {
+        self.indentation_token.iter()
+            .chain(self.key_tokens.iter())
+            .chain(self.key_terminator_token.iter())
+            .chain(self.value_tokens.iter())
+            .chain(self.comment_token.iter())
+            .chain(self.end_of_line_token.iter())
+    }
+
     //// Compute the single [`Token`] that contains the given `span`, if any
     // pub fn token_containing_span(&self, span: FileSpan) -> Option<Token> {
     //     if let Some(cmp_span) = self.indentation_token.clone().map(|token| token.span) {
@@ -276,6 +312,9 @@ impl Node {
 /// ```
 pub struct Nodeizer<I: Iterator<Item = Token>> {
     tokens: MultiPeek<I>,
+
+    /// Diagnostics accumulated during nodeization
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl<I: Iterator<Item = Token>> Nodeizer<I> {
@@ -293,12 +332,48 @@ impl<I: Iterator<Item = Token>> Nodeizer<I> {
     pub fn new(tokens: I) -> Nodeizer<I> {
         Self {
             tokens: itertools::multipeek(tokens),
+            diagnostics: vec![],
         }
     }
 
     pub fn run(&mut self) -> Vec<Node> {
         self.by_ref().collect()
     }
+
+    /// Record a diagnostic
+    fn add_diagnostic(&mut self, diagnostic: Diagnostic) {
+        log::debug!("diagnostic added ({}): {:?}", diagnostic.severity, diagnostic.message);
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Take the diagnostics from the nodeizer, leaving an empty collection
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::replace(&mut self.diagnostics, Vec::new())
+    }
+
+    /// Compute the span starting at `start_span` and ending just before the
+    /// next end-of-line token (or at the last token if there is no next
+    /// end-of-line token)
+    ///
+    /// This does not consume any tokens.
+    fn span_until_end_of_line(&mut self, start_span: FileSpan) -> FileSpan {
+        let mut end_exclusive = start_span.end_exclusive();
+
+        loop {
+            match self.tokens.peek() {
+                Some(tok) if tok.kind == TokenKind::EndOfLine => {
+                    end_exclusive = tok.span.start();
+                    break;
+                },
+                Some(tok) => end_exclusive = tok.span.end_exclusive(),
+                None => break,
+            }
+        }
+
+        self.tokens.reset_peek();
+
+        start_span.with_end_exclusive(end_exclusive)
+    }
 }
 
 impl<I: Iterator<Item = Token>> Iterator for Nodeizer<I> {
@@ -319,7 +394,8 @@ impl<I: Iterator<Item = Token>> Iterator for Nodeizer<I> {
                         key_tokens,
                         key_terminator_token,
                         value_tokens,
-                        comment_token
+                        comment_token,
+                        end_of_line_token: Some(token),
                     };
 
                     if node.indentation_token.is_some() || !node.key_tokens.is_empty() || !node.value_tokens.is_empty() || node.comment_token.is_some() {
@@ -332,10 +408,10 @@ impl<I: Iterator<Item = Token>> Iterator for Nodeizer<I> {
                 }
                 TokenKind::Comment => comment_token = Some(token),
                 TokenKind::Whitespace => {
-                    if key_tokens.is_empty() {
-                        indentation_token = Some(token);
-                    } else if key_terminator_token.is_some() {
+                    if key_terminator_token.is_some() {
                         value_tokens.push(token);
+                    } else if key_tokens.is_empty() && indentation_token.is_none() {
+                        indentation_token = Some(token);
                     } else {
                         key_tokens.push(token);
                     }
@@ -347,7 +423,19 @@ impl<I: Iterator<Item = Token>> Iterator for Nodeizer<I> {
                         // A colon being the first non-whitespace token is invalid
                         // because that is a key-less node (only empty or comment-only nodes can be key-less).
                         if !itertools::any(&key_tokens, |tok| tok.kind != TokenKind::Whitespace) {
-                            // TODO: diagnostic
+                            let span_colon = token.span;
+                            let span_remainder = self.span_until_end_of_line(span_colon);
+
+                            self.add_diagnostic(
+                                Diagnostic::new_error("No key found for this node")
+                                    .with_code("P:E0002")
+                                    .with_label(Label::new_primary(span_colon))
+                                    .with_label(
+                                        Label::new_secondary(span_remainder)
+                                            .with_message("this node is value-only")
+                                    )
+                                    .with_help("Nodes must be entirely empty, have a key, or have a comment, they can not be value-only")
+                            );
                         }
 
                         key_terminator_token = Some(token);
@@ -357,21 +445,40 @@ impl<I: Iterator<Item = Token>> Iterator for Nodeizer<I> {
                     if key_terminator_token.is_some() {
                         value_tokens.push(token);
                     } else {
-                        // TODO: diagnostic
+                        self.add_diagnostic(
+                            Diagnostic::new_error("`!` can not be used in a node's key")
+                                .with_code("P:E0004")
+                                .with_label(Label::new_primary(token.span))
+                                .with_help("Remove this `!` symbol, `!` can be used in strings or in conditionals to negate a boolean value")
+                        );
+
+                        // Keep the token so that no source text is lost
+                        key_tokens.push(token);
                     }
                 },
                 TokenKind::At => {
                     if key_terminator_token.is_some() {
                         value_tokens.push(token);
                     } else {
-                        match self.tokens.peek() {
+                        let opt_bad_span = match self.tokens.peek() {
                             Some(tok_peeked) if tok_peeked.kind != TokenKind::Identifier && !tok_peeked.is_numeric() => {
-                                // TODO: diagnostic
+                                Some(tok_peeked.span)
                             },
-                            _ => {},
-                        }
+                            _ => None,
+                        };
 
                         self.tokens.reset_peek();
+
+                        if let Some(bad_span) = opt_bad_span {
+                            self.add_diagnostic(
+                                Diagnostic::new_error("Expected an identifier or number after `@`")
+                                    .with_code("P:E0003")
+                                    .with_label(Label::new_primary(bad_span))
+                                    .with_label(Label::new_secondary(token.span))
+                                    .with_help("Valid examples: `MyProperty@hello`, `HelloWorld@3`")
+                            );
+                        }
+
                         key_tokens.push(token);
                     }
                 },
@@ -383,29 +490,62 @@ impl<I: Iterator<Item = Token>> Iterator for Nodeizer<I> {
                         //
                         // TODO: Think about how best to handle this, if at all.
 
-                        match self.tokens.peek() {
+                        let opt_peeked_span = match self.tokens.peek() {
                             // A ^ followed by a non-identifier, in value position, is *potentially* a typo
                             // TOOD: Once we have a "symbol table" of sorts we could remove this diag
                             //       as we'd do a lookup and, probably, not find an ident like `^!bar`.
-                            Some(peeked_tok) if peeked_tok.kind != TokenKind::Identifier => {
-                                // TODO: diagnostic
-                            },
-                            _ => {},
-                        }
+                            Some(peeked_tok) if peeked_tok.kind != TokenKind::Identifier => Some(peeked_tok.span),
+                            _ => None,
+                        };
 
                         self.tokens.reset_peek();
+
+                        if let Some(peeked_span) = opt_peeked_span {
+                            self.add_diagnostic(
+                                Diagnostic::new_warning("A caret followed by a non-identifier is potentially a typo")
+                                    .with_code("P:W0001")
+                                    .with_label(Label::new_primary(token.span))
+                                    .with_label(Label::new_secondary(peeked_span))
+                                    .with_help("Consider removing this caret")
+                            );
+                        }
+
                         value_tokens.push(token);
                     } else {
-                        match self.tokens.peek() {
+                        let opt_peeked = match self.tokens.peek() {
                             Some(peeked_tok) if peeked_tok.kind != TokenKind::Identifier => {
-                                // TODO: diagnostic
+                                let peeked_kind_str = match peeked_tok.kind {
+                                    TokenKind::Whitespace => "whitespace",
+                                    TokenKind::EndOfLine => "newline",
+                                    _ if peeked_tok.is_symbol() => "symbol",
+                                    _ if peeked_tok.is_keyword() => "keyword",
+                                    _ => "text",
+                                };
+
+                                Some((peeked_kind_str, peeked_tok.span))
                             },
-                            None => { /* span end is eof */ },
-                            _ => {},
-                        }
+                            None => { /* span end is eof */ None },
+                            _ => None,
+                        };
 
                         self.tokens.reset_peek();
 
+                        if let Some((peeked_kind_str, peeked_span)) = opt_peeked {
+                            let mut diag = Diagnostic::new_error("Expected an identifier after `^`")
+                                .with_code("P:E0001")
+                                .with_label(Label::new_primary(token.span))
+                                .with_label(
+                                    Label::new_secondary(peeked_span)
+                                        .with_message(format!("remove this {}", peeked_kind_str))
+                                );
+
+                            if peeked_kind_str == "keyword" {
+                                diag = diag.with_help("Keywords have special meaning and can not be used as keys");
+                            }
+
+                            self.add_diagnostic(diag);
+                        }
+
                         key_tokens.push(token);
                     }
                 },
@@ -420,7 +560,22 @@ impl<I: Iterator<Item = Token>> Iterator for Nodeizer<I> {
             }
         }
 
-        None
+        // The last line may not be terminated by an end-of-line token
+        let node = Node {
+            indentation_token,
+            key_tokens,
+            key_terminator_token,
+            value_tokens,
+            comment_token,
+            end_of_line_token: None,
+        };
+
+        if node.is_empty() && node.comment_token.is_none() {
+            None
+        } else {
+            log::trace!("emit {:#?}", node);
+            Some(node)
+        }
     }
 }
 
@@ -26,6 +26,7 @@ use crate::{
     Tokenizer,
     Node,
     Nodeizer,
+    Severity,
 };
 
 macro_rules! token {
@@ -70,6 +71,9 @@ fn key_only_without_key_terminator() {
             ),
             value_tokens: vec![],
             comment_token: None,
+            end_of_line_token: Some(
+                token!(file_id, EndOfLine, 9..10),
+            ),
         },
         Node {
             indentation_token: Some(
@@ -81,6 +85,9 @@ fn key_only_without_key_terminator() {
             key_terminator_token: None,
             value_tokens: vec![],
             comment_token: None,
+            end_of_line_token: Some(
+                token!(file_id, EndOfLine, 17..18),
+            ),
         },
         Node {
             indentation_token: Some(
@@ -97,6 +104,9 @@ fn key_only_without_key_terminator() {
                 token!(file_id, Identifier, 27..30),
             ],
             comment_token: None,
+            end_of_line_token: Some(
+                token!(file_id, EndOfLine, 30..31),
+            ),
         },
     ];
 
@@ -155,6 +165,9 @@ fn general_test() {
             comment_token: Some(
                 token!(file_id, Comment, 20..23),
             ),
+            end_of_line_token: Some(
+                token!(file_id, EndOfLine, 23..24),
+            ),
         },
         Node {
             indentation_token: Some(
@@ -172,6 +185,9 @@ fn general_test() {
             comment_token: Some(
                 token!(file_id, Comment, 44..47),
             ),
+            end_of_line_token: Some(
+                token!(file_id, EndOfLine, 47..48),
+            ),
         },
         Node {
             indentation_token: None,
@@ -179,6 +195,9 @@ fn general_test() {
             key_terminator_token: None,
             value_tokens: vec![],
             comment_token: None,
+            end_of_line_token: Some(
+                token!(file_id, EndOfLine, 48..49),
+            ),
         },
         Node {
             indentation_token: None,
@@ -190,6 +209,9 @@ fn general_test() {
             ),
             value_tokens: vec![],
             comment_token: None,
+            end_of_line_token: Some(
+                token!(file_id, EndOfLine, 56..57),
+            ),
         },
         Node {
             indentation_token: Some(
@@ -209,6 +231,9 @@ fn general_test() {
                 token!(file_id, Identifier, 83..89),
             ],
             comment_token: None,
+            end_of_line_token: Some(
+                token!(file_id, EndOfLine, 89..90),
+            ),
         },
     ];
 
@@ -258,4 +283,70 @@ fn key_text() {
     ];
 
     assert_eq!(actual_key_texts, expected_key_texts);
+}
+
+#[test]
+fn value_only_node_is_an_error() {
+    // Arrange
+    let src = "Parent:\n    : foo\n";
+    let file_id = FileId(0);
+
+    let mut tokenizer = Tokenizer::new(file_id, src);
+    let tokens = tokenizer.run();
+
+    let mut nodeizer = Nodeizer::new(tokens.into_iter());
+
+    // Act
+    let _nodes = nodeizer.run();
+    let actual_diagnostics = nodeizer.take_diagnostics();
+
+    // Assert
+    assert_eq!(actual_diagnostics.len(), 1);
+
+    let diag = &actual_diagnostics[0];
+    assert_eq!(diag.code, Some("P:E0002".to_owned()));
+    assert_eq!(diag.severity, Severity::Error);
+    assert_eq!(diag.primary_span(), Some(FileSpan::new(file_id, 12, 13)));
+    assert_eq!(diag.labels[1].span, FileSpan::new(file_id, 12, 17));
+}
+
+#[test]
+fn value_text_excludes_surrounding_whitespace() {
+    // Arrange
+    let src = "Inherits@2:   ^GainsExperience   # comment\n";
+    let file_id = FileId(0);
+
+    let mut tokenizer = Tokenizer::new(file_id, src);
+    let tokens = tokenizer.run();
+
+    let mut nodeizer = Nodeizer::new(tokens.into_iter());
+
+    // Act
+    let nodes = nodeizer.run();
+
+    // Assert
+    assert_eq!(nodes.len(), 1);
+    assert_eq!(nodes[0].key_text(src), Some("Inherits@2"));
+    assert_eq!(nodes[0].value_text(src), Some("^GainsExperience"));
+    assert_eq!(nodes[0].value_span(), Some(FileSpan::new(file_id, 14, 30)));
+}
+
+#[test]
+fn last_line_without_end_of_line_is_kept() {
+    // Arrange
+    let src = "Player:\n    AlwaysVisible";
+    let file_id = FileId(0);
+
+    let mut tokenizer = Tokenizer::new(file_id, src);
+    let tokens = tokenizer.run();
+
+    let nodeizer = Nodeizer::new(tokens.into_iter());
+
+    // Act
+    let actual_key_texts = nodeizer
+        .map(|node| node.key_text(src))
+        .collect::<Vec<_>>();
+
+    // Assert
+    assert_eq!(actual_key_texts, vec![Some("Player"), Some("AlwaysVisible")]);
 }
\ No newline at end of file
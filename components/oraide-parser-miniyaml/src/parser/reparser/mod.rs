@@ -0,0 +1,488 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+//! # `reparser`
+//!
+//! Update a [`Parse`] after an edit without reparsing the entire text
+//!
+//! ---
+//!
+//! A node that is not indented resets the treeizer's state so a document is
+//! stored as a sequence of top-level blocks, each starting at such a node,
+//! that are treeized independently of each other.  A block keeps the spans
+//! it was parsed with, and where it started in the text it was parsed from,
+//! so it does not change when text before it is edited.  Its spans are moved
+//! to where the block now starts when they are read.
+//!
+//! MiniYaml is line-oriented so an edit can only change the block(s)
+//! containing the lines it touches.  Only those blocks are parsed again, all
+//! other blocks are shared (not copied) with the previous [`Parse`].
+//!
+//! The entrypoint to this module is [`Parse::reparse`].
+//!
+//! [`Parse`]: struct.Parse.html
+//! [`Parse::reparse`]: struct.Parse.html#method.reparse
+//!
+
+use std::{
+    ops::Range,
+    sync::Arc,
+};
+
+use oraide_span::{
+    FileId,
+    FileSpan,
+};
+
+use crate::{
+    Token,
+    Tokenizer,
+    Node,
+    Nodeizer,
+    Arena,
+    Tree,
+    Treeizer,
+    Diagnostic,
+};
+
+/// A top-level block of a document, which is a node that is not indented
+/// and every node following it up to the next such node
+///
+/// Every span in a block is relative to the text it was parsed from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Block {
+    /// The byte index, in the text the block was parsed from, that the
+    /// block starts at
+    origin: usize,
+
+    /// The length, in bytes, of the block's text
+    len: usize,
+
+    /// Whether the tokens of `nodes`, in order, cover the block's text
+    /// without gaps, otherwise (such as after a lone `\r`) tokenizing part of
+    /// the text may not give the same tokens as tokenizing all of it
+    is_regular: bool,
+
+    /// The block's nodes, in source order
+    nodes: Vec<Node>,
+
+    /// The position of each of `nodes`' parent in the block's `Tree` (where
+    /// `0` is the sentinel and `n` is `nodes[n - 1]`), if it has one
+    parent_positions: Vec<Option<usize>>,
+
+    tokenizer_diagnostics: Vec<Diagnostic>,
+    nodeizer_diagnostics: Vec<Diagnostic>,
+    treeizer_diagnostics: Vec<Diagnostic>,
+}
+
+impl Block {
+    /// The block's nodes, with their spans moved so that the block starts at
+    /// byte index `start`
+    fn nodes_at(&self, start: usize) -> impl Iterator<Item = Node> + '_ {
+        let shift = moved(self.origin, start);
+        self.nodes.iter().map(move |node| shifted_node(node, &shift))
+    }
+
+    /// `diagnostics`, which must be from this block, with their spans moved
+    /// so that the block starts at byte index `start`
+    fn diagnostics_at<'a>(&self, diagnostics: &'a [Diagnostic], start: usize) -> impl Iterator<Item = Diagnostic> + 'a {
+        let shift = moved(self.origin, start);
+        diagnostics.iter().map(move |diag| shifted_diagnostic(diag, &shift))
+    }
+
+    /// A copy of this block as if it had been parsed starting at byte index
+    /// `start`
+    fn moved_to(&self, start: usize) -> Self {
+        Self {
+            origin: start,
+            nodes: self.nodes_at(start).collect(),
+            tokenizer_diagnostics: self.diagnostics_at(&self.tokenizer_diagnostics, start).collect(),
+            nodeizer_diagnostics: self.diagnostics_at(&self.nodeizer_diagnostics, start).collect(),
+            treeizer_diagnostics: self.diagnostics_at(&self.treeizer_diagnostics, start).collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Everything computed by tokenizing, nodeizing, and treeizing a text document
+///
+/// Cloning a [`Parse`] is cheap since its blocks are shared.
+///
+/// [`Parse`]: struct.Parse.html
+#[derive(Debug, Clone, Eq)]
+pub struct Parse {
+    /// The document's blocks, in source order, along with the byte index
+    /// that each starts at
+    blocks: Vec<(usize, Arc<Block>)>,
+}
+
+impl Parse {
+    /// Tokenize, nodeize, and treeize all of `text`
+    ///
+    /// # Example
+    /// ```rust
+    /// # use oraide_span::{FileId};
+    /// # use oraide_parser_miniyaml::{Parse};
+    /// let parse = Parse::new(FileId(0), "Hello:\n\tWorld:\n");
+    /// assert_eq!(parse.nodes().len(), 2);
+    /// ```
+    pub fn new(file_id: FileId, text: &str) -> Self {
+        Self {
+            blocks: parse_blocks(file_id, text),
+        }
+    }
+
+    /// Compute all of the [`Token`]s in the document, in source order
+    ///
+    /// [`Token`]: struct.Token.html
+    pub fn tokens(&self) -> Vec<Token> {
+        self.nodes().iter()
+            .flat_map(Node::tokens)
+            .cloned()
+            .collect()
+    }
+
+    /// Compute every [`Node`] in the document, in source order
+    ///
+    /// [`Node`]: struct.Node.html
+    pub fn nodes(&self) -> Vec<Node> {
+        let mut nodes = Vec::with_capacity(self.node_count());
+
+        for (start, block) in self.blocks.iter() {
+            nodes.extend(block.nodes_at(*start));
+        }
+
+        nodes
+    }
+
+    /// Compute the document's [`Tree`]
+    ///
+    /// [`Tree`]: struct.Tree.html
+    pub fn tree(&self) -> Tree {
+        let mut arena = Arena::new();
+        let mut node_ids = Vec::with_capacity(self.node_count() + 1);
+        node_ids.push(arena.new_node(Node::new_empty()));
+
+        for (start, block) in self.blocks.iter() {
+            // The position of the block's sentinel, were it in `node_ids`
+            let block_base = node_ids.len() - 1;
+
+            for (node, parent_position) in block.nodes_at(*start).zip(block.parent_positions.iter()) {
+                let node_id = arena.new_node(node);
+
+                match parent_position {
+                    Some(0) => node_ids[0].append(node_id, &mut arena),
+                    Some(position) => node_ids[block_base + position].append(node_id, &mut arena),
+                    None => {},
+                }
+
+                node_ids.push(node_id);
+            }
+        }
+
+        Tree::from(node_ids, arena)
+    }
+
+    /// Compute the diagnostics produced while tokenizing
+    pub fn tokenizer_diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics(|block| &block.tokenizer_diagnostics)
+    }
+
+    /// Compute the diagnostics produced while nodeizing
+    pub fn nodeizer_diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics(|block| &block.nodeizer_diagnostics)
+    }
+
+    /// Compute the diagnostics produced while treeizing
+    pub fn treeizer_diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics(|block| &block.treeizer_diagnostics)
+    }
+
+    fn diagnostics(&self, block_diagnostics: impl Fn(&Block) -> &Vec<Diagnostic>) -> Vec<Diagnostic> {
+        self.blocks.iter()
+            .flat_map(|(start, block)| block.diagnostics_at(block_diagnostics(block), *start))
+            .collect()
+    }
+
+    fn node_count(&self) -> usize {
+        self.blocks.iter().map(|(_, block)| block.nodes.len()).sum()
+    }
+
+    /// The length, in bytes, of the text this [`Parse`] was computed from
+    ///
+    /// [`Parse`]: struct.Parse.html
+    fn len(&self) -> usize {
+        self.blocks.last().map_or(0, |(start, block)| start + block.len)
+    }
+
+    /// Compute the [`Parse`] of `text`, which is the previous text with the
+    /// bytes in `replaced` replaced by `inserted_len` bytes, reusing as much
+    /// of this [`Parse`] as possible
+    ///
+    /// The result is equal to that of `Parse::new(file_id, text)`.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use oraide_span::{FileId};
+    /// # use oraide_parser_miniyaml::{Parse};
+    /// let file_id = FileId(0);
+    /// let parse = Parse::new(file_id, "Hello:\n\tWorld:\n");
+    ///
+    /// // Replace `World` with `Friends`
+    /// let reparse = parse.reparse(file_id, "Hello:\n\tFriends:\n", 8..13, 7);
+    /// assert_eq!(reparse, Parse::new(file_id, "Hello:\n\tFriends:\n"));
+    /// ```
+    ///
+    /// [`Parse`]: struct.Parse.html
+    pub fn reparse(&self, file_id: FileId, text: &str, replaced: Range<usize>, inserted_len: usize) -> Self {
+        let old_len = self.len();
+
+        if self.blocks.iter().any(|(_, block)| !block.is_regular)
+            || replaced.start > replaced.end
+            || replaced.end > old_len
+            || old_len - (replaced.end - replaced.start) + inserted_len != text.len() {
+            return Self::new(file_id, text);
+        }
+
+        // An edit at the very start of a block is considered to also touch
+        // the previous block since it may, for example, join two lines.
+        let mut first = self.blocks.iter()
+            .position(|(start, block)| start + block.len >= replaced.start)
+            .unwrap_or_else(|| self.blocks.len());
+
+        let last_exclusive = self.blocks.iter()
+            .rposition(|(start, _)| *start <= replaced.end)
+            .map_or(first, |idx| (idx + 1).max(first));
+
+        let old_end = if last_exclusive > first {
+            let (start, block) = &self.blocks[last_exclusive - 1];
+            start + block.len
+        } else {
+            self.blocks.get(first).map_or(old_len, |(start, _)| *start)
+        };
+
+        let new_end = old_end - (replaced.end - replaced.start) + inserted_len;
+
+        let (region_start, region_blocks) = loop {
+            let region_start = self.blocks.get(first).map_or(old_len, |(start, _)| *start);
+            let region_blocks = parse_blocks(file_id, &text[region_start..new_end]);
+
+            // If the edit indented the first line of a block then that line
+            // now belongs to the previous block
+            let is_joined_to_previous_block = region_blocks.first()
+                .and_then(|(_, block)| block.nodes.first())
+                .map_or(false, |node| !is_block_start(node));
+
+            if first > 0 && is_joined_to_previous_block {
+                first -= 1;
+                continue;
+            }
+
+            break (region_start, region_blocks);
+        };
+
+        if region_blocks.iter().any(|(_, block)| !block.is_regular) {
+            return Self::new(file_id, text);
+        }
+
+        let mut blocks = Vec::with_capacity(first + region_blocks.len() + self.blocks.len() - last_exclusive);
+        blocks.extend_from_slice(&self.blocks[..first]);
+        blocks.extend(region_blocks.into_iter().map(|(start, block)| (region_start + start, block)));
+        blocks.extend(self.blocks[last_exclusive..].iter().map(|(start, block)| (start - old_end + new_end, block.clone())));
+
+        Self {
+            blocks,
+        }
+    }
+}
+
+impl PartialEq for Parse {
+    /// Whether both [`Parse`]s have the same blocks at the same byte indices,
+    /// regardless of where each block was parsed from
+    ///
+    /// [`Parse`]: struct.Parse.html
+    fn eq(&self, other: &Self) -> bool {
+        self.blocks.len() == other.blocks.len()
+            && self.blocks.iter().zip(other.blocks.iter()).all(|((start, block), (other_start, other_block))| {
+                start == other_start
+                    && (Arc::ptr_eq(block, other_block)
+                        || if block.origin == other_block.origin {
+                            block == other_block
+                        } else {
+                            block.moved_to(*start) == other_block.moved_to(*start)
+                        })
+            })
+    }
+}
+
+/// Tokenize, nodeize, and treeize `text`, splitting it into [`Block`]s
+///
+/// # Returns
+/// Each block along with the byte index, in `text`, that it starts at
+///
+/// [`Block`]: struct.Block.html
+fn parse_blocks(file_id: FileId, text: &str) -> Vec<(usize, Arc<Block>)> {
+    let mut tokenizer = Tokenizer::new(file_id, text);
+    let tokens = tokenizer.run();
+    let tokenizer_diagnostics = tokenizer.take_diagnostics();
+
+    let mut nodeizer = Nodeizer::new(tokens.into_iter());
+    let nodes = nodeizer.run();
+    let nodeizer_diagnostics = nodeizer.take_diagnostics();
+
+    if nodes.is_empty() {
+        return vec![];
+    }
+
+    // The index, in `nodes`, and byte index of the start of each block
+    let mut block_starts = vec![(0, 0)];
+
+    block_starts.extend(
+        nodes.iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, node)| is_block_start(node))
+            // A node's tokens are not necessarily in source order
+            .filter_map(|(idx, node)| Some((idx, node.tokens().map(|token| token.span.start().to_usize()).min()?)))
+    );
+
+    let block_ends = block_starts.iter()
+        .skip(1)
+        .cloned()
+        .chain(std::iter::once((nodes.len(), text.len())));
+
+    // Diagnostics before the first block, or without a span, belong to the
+    // first block
+    let block_idx_of = |diag: &Diagnostic| match block_starts.binary_search_by_key(&diagnostic_start(diag).unwrap_or(0), |(_, start)| *start) {
+        Ok(idx) => idx,
+        Err(idx) => idx.saturating_sub(1),
+    };
+
+    let mut block_tokenizer_diagnostics = vec![vec![]; block_starts.len()];
+    for diag in tokenizer_diagnostics {
+        block_tokenizer_diagnostics[block_idx_of(&diag)].push(diag);
+    }
+
+    let mut block_nodeizer_diagnostics = vec![vec![]; block_starts.len()];
+    for diag in nodeizer_diagnostics {
+        block_nodeizer_diagnostics[block_idx_of(&diag)].push(diag);
+    }
+
+    let mut nodes = nodes.into_iter();
+
+    block_starts.iter()
+        .zip(block_ends)
+        .zip(block_tokenizer_diagnostics.into_iter().zip(block_nodeizer_diagnostics))
+        .map(|((&(start_idx, start), (end_idx, end)), (tokenizer_diagnostics, nodeizer_diagnostics))| {
+            let block_nodes = nodes.by_ref()
+                .take(end_idx - start_idx)
+                .collect();
+
+            let block = treeized_block(text, start..end, block_nodes, tokenizer_diagnostics, nodeizer_diagnostics);
+            (start, Arc::new(block))
+        })
+        .collect()
+}
+
+/// Treeize `nodes`, which make up the `range` of `text`, into a [`Block`]
+///
+/// [`Block`]: struct.Block.html
+fn treeized_block(
+    text: &str,
+    range: Range<usize>,
+    nodes: Vec<Node>,
+    tokenizer_diagnostics: Vec<Diagnostic>,
+    nodeizer_diagnostics: Vec<Diagnostic>,
+) -> Block {
+    let mut treeizer = Treeizer::new(nodes.clone().into_iter(), text);
+    let tree = treeizer.run();
+    let treeizer_diagnostics = treeizer.take_diagnostics();
+
+    // The treeizer creates nodes in source order so `node_ids` is sorted
+    let parent_positions = tree.node_ids.iter()
+        .skip(1) // skip the sentinel
+        .filter_map(|node_id| tree.arena.get(*node_id))
+        .map(|shrd_arena_node| shrd_arena_node.parent().and_then(|parent_id| tree.node_ids.binary_search(&parent_id).ok()))
+        .collect();
+
+    let is_regular = nodes.iter()
+        .flat_map(Node::tokens)
+        .try_fold(range.start, |expected_start, token| if token.span.start().to_usize() == expected_start {
+            Some(token.span.end_exclusive().to_usize())
+        } else {
+            None
+        }) == Some(range.end);
+
+    Block {
+        origin: range.start,
+        len: range.end - range.start,
+        is_regular,
+        nodes,
+        parent_positions,
+        tokenizer_diagnostics,
+        nodeizer_diagnostics,
+        treeizer_diagnostics,
+    }
+}
+
+/// Whether `node` resets the treeizer's state (it is not indented and
+/// is not empty)
+fn is_block_start(node: &Node) -> bool {
+    !node.is_empty() && node.indentation_token.is_none()
+}
+
+/// Move spans, which are at or after byte index `from`, by as many bytes as
+/// `to` is from `from`
+fn moved(from: usize, to: usize) -> impl Fn(FileSpan) -> FileSpan {
+    move |span: FileSpan| FileSpan::new(
+        span.source(),
+        span.start().to_usize() - from + to,
+        span.end_exclusive().to_usize() - from + to,
+    )
+}
+
+fn shifted_node(node: &Node, shift: &impl Fn(FileSpan) -> FileSpan) -> Node {
+    let mut node = node.clone();
+    let tokens = node.indentation_token.iter_mut()
+        .chain(node.key_tokens.iter_mut())
+        .chain(node.key_terminator_token.iter_mut())
+        .chain(node.value_tokens.iter_mut())
+        .chain(node.comment_token.iter_mut())
+        .chain(node.end_of_line_token.iter_mut());
+
+    for token in tokens {
+        token.span = shift(token.span);
+    }
+
+    node
+}
+
+fn shifted_diagnostic(diagnostic: &Diagnostic, shift: &impl Fn(FileSpan) -> FileSpan) -> Diagnostic {
+    let mut diagnostic = diagnostic.clone();
+    for label in diagnostic.labels.iter_mut() {
+        label.span = shift(label.span);
+    }
+
+    diagnostic
+}
+
+/// The byte index that determines which block `diagnostic` belongs to
+fn diagnostic_start(diagnostic: &Diagnostic) -> Option<usize> {
+    diagnostic.primary_span()
+        .or_else(|| diagnostic.labels.first().map(|label| label.span))
+        .map(|span| span.start().to_usize())
+}
+
+#[cfg(test)]
+mod tests;
\ No newline at end of file
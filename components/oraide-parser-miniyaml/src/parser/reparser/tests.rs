@@ -0,0 +1,198 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+
+use pretty_assertions::assert_eq;
+use proptest::prelude::*;
+
+use oraide_span::{
+    FileId,
+    FileSpan,
+};
+
+use std::sync::Arc;
+
+use crate::{
+    Parse,
+    Tokenizer,
+    Nodeizer,
+    Treeizer,
+};
+
+/// Replace `range` of `old_text` with `inserted`, asserting that reparsing
+/// gives the same result as parsing the new text from scratch
+fn assert_reparse_matches_parse(old_text: &str, range: std::ops::Range<usize>, inserted: &str) -> Parse {
+    let file_id = FileId(0);
+
+    let mut new_text = old_text.to_owned();
+    new_text.replace_range(range.clone(), inserted);
+
+    let parse = Parse::new(file_id, old_text);
+    let reparse = parse.reparse(file_id, &new_text, range, inserted.len());
+
+    assert_eq!(reparse, Parse::new(file_id, &new_text));
+    assert_eq!(reparse.tree().to_source(&new_text), new_text);
+
+    reparse
+}
+
+const SRC: &str = "^Base:\n    Health:\n        HP: 100\n\nE1:\n    Inherits: ^Base\n    Tooltip:\n        Name: Rifleman\n";
+
+#[test]
+fn editing_a_value_shifts_the_following_nodes() {
+    // Arrange
+    let range = 31..34; // `100`
+    let new_text = SRC.replace("100", "2500");
+
+    // Act
+    let reparse = assert_reparse_matches_parse(SRC, range, "2500");
+
+    // Assert
+    let e1 = reparse.nodes().into_iter()
+        .find(|node| node.key_text(&new_text) == Some("E1"))
+        .expect("E1 node to exist");
+
+    assert_eq!(e1.key_span(), Some(FileSpan::new(FileId(0), 37, 39)));
+}
+
+#[test]
+fn inserting_a_new_top_level_block() {
+    // Arrange
+    let range = 36..36; // after the empty line
+
+    // Act & Assert
+    assert_reparse_matches_parse(SRC, range, "E2:\n    Inherits: ^Base\n\n");
+}
+
+#[test]
+fn joining_two_lines() {
+    // Arrange
+    let range = 18..27; // `\n        ` before `HP`
+
+    // Act & Assert
+    assert_reparse_matches_parse(SRC, range, " ");
+}
+
+#[test]
+fn unindenting_a_child_changes_the_tree() {
+    // Arrange
+    let range = 60..64; // indentation before `Tooltip`
+
+    // Act & Assert
+    assert_reparse_matches_parse(SRC, range, "");
+}
+
+#[test]
+fn introducing_errors_keeps_the_diagnostics_of_other_lines() {
+    // Arrange
+    let src = "E1:\n  Bad:\n    Health:\n\r    !Key:\n";
+    let range = 0..2; // `E1`
+
+    // Act
+    let reparse = assert_reparse_matches_parse(src, range, "E3");
+
+    // Assert
+    assert!(!reparse.tokenizer_diagnostics().is_empty());
+    assert!(!reparse.nodeizer_diagnostics().is_empty());
+    assert!(!reparse.treeizer_diagnostics().is_empty());
+}
+
+#[test]
+fn editing_a_block_shares_the_other_blocks() {
+    // Arrange
+    let range = 31..34; // `100`
+    let new_text = SRC.replace("100", "2500");
+    let parse = Parse::new(FileId(0), SRC);
+
+    // Act
+    let reparse = parse.reparse(FileId(0), &new_text, range, 4);
+
+    // Assert
+    assert_eq!(reparse.blocks.len(), 2);
+    assert!(!Arc::ptr_eq(&parse.blocks[0].1, &reparse.blocks[0].1));
+    assert!(Arc::ptr_eq(&parse.blocks[1].1, &reparse.blocks[1].1));
+}
+
+#[test]
+fn indenting_the_start_of_a_block_joins_it_to_the_previous_block() {
+    // Arrange
+    let range = 36..36; // before `E1`
+
+    // Act
+    let reparse = assert_reparse_matches_parse(SRC, range, "    ");
+
+    // Assert
+    assert_eq!(reparse.blocks.len(), 1);
+}
+
+#[test]
+fn editing_empty_text() {
+    assert_reparse_matches_parse("", 0..0, "Hello:\n\tWorld:");
+}
+
+#[test]
+fn deleting_everything() {
+    assert_reparse_matches_parse(SRC, 0..SRC.len(), "");
+}
+
+proptest! {
+    #[test]
+    fn reparse_matches_parse(
+        old_text in "[A-Za-z0-9 \t\r\n:#^@!-]{0,128}",
+        a in any::<usize>(),
+        b in any::<usize>(),
+        inserted in "[A-Za-z0-9 \t\r\n:#^@!-]{0,16}",
+    ) {
+        let len = old_text.len() + 1;
+        let (start, end) = (a % len, b % len);
+        let range = start.min(end)..start.max(end);
+
+        let file_id = FileId(0);
+
+        let mut new_text = old_text.clone();
+        new_text.replace_range(range.clone(), &inserted);
+
+        let parse = Parse::new(file_id, &old_text);
+        let reparse = parse.reparse(file_id, &new_text, range, inserted.len());
+
+        prop_assert_eq!(reparse, Parse::new(file_id, &new_text));
+    }
+
+    #[test]
+    fn parse_matches_parsing_the_entire_text_at_once(
+        text in "[A-Za-z0-9 \t\r\n:#^@!-]{0,128}",
+    ) {
+        let file_id = FileId(0);
+
+        let mut tokenizer = Tokenizer::new(file_id, &text);
+        let tokens = tokenizer.run();
+        let tokenizer_diagnostics = tokenizer.take_diagnostics();
+
+        let mut nodeizer = Nodeizer::new(tokens.into_iter());
+        let nodes = nodeizer.run();
+        let nodeizer_diagnostics = nodeizer.take_diagnostics();
+
+        let mut treeizer = Treeizer::new(nodes.clone().into_iter(), &text);
+        let tree = treeizer.run();
+        let treeizer_diagnostics = treeizer.take_diagnostics();
+
+        let parse = Parse::new(file_id, &text);
+
+        prop_assert_eq!(parse.tokenizer_diagnostics(), tokenizer_diagnostics);
+        prop_assert_eq!(parse.nodes(), nodes);
+        prop_assert_eq!(parse.nodeizer_diagnostics(), nodeizer_diagnostics);
+        prop_assert_eq!(parse.tree(), tree);
+        prop_assert_eq!(parse.treeizer_diagnostics(), treeizer_diagnostics);
+    }
+}
\ No newline at end of file
@@ -28,6 +28,7 @@
 
 use std::{
     mem,
+    ops::Range,
     str::{
         FromStr,
         Chars,
@@ -41,6 +42,11 @@ use oraide_span::{
     ByteCount,
 };
 
+use crate::{
+    Diagnostic,
+    Label,
+};
+
 /// Used to indicate which type of [`Token`] this is
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TokenKind {
@@ -126,7 +132,7 @@ impl Token {
             || self.kind == TokenKind::Comment
     }
 
-    fn is_symbol(&self) -> bool {
+    pub(crate) fn is_symbol(&self) -> bool {
         match self.kind {
               TokenKind::Symbol
             | TokenKind::Tilde
@@ -144,7 +150,7 @@ impl Token {
         self.kind == TokenKind::IntLiteral || self.kind == TokenKind::FloatLiteral
     }
 
-    fn is_keyword(&self) -> bool {
+    pub(crate) fn is_keyword(&self) -> bool {
         match self.kind {
               TokenKind::True
             | TokenKind::Yes
@@ -276,6 +282,9 @@ pub struct Tokenizer<'text> {
 
     /// The end position (+ 1 byte) of the next token to be emitted
     token_end_exclusive: ByteIndex,
+
+    /// Diagnostics accumulated during tokenization
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl<'text> Tokenizer<'text> {
@@ -299,7 +308,36 @@ impl<'text> Tokenizer<'text> {
     /// [`Tokenizer`]: struct.Tokenizer.html
     /// [`FileId`]: struct.FileId.html
     pub fn new(file_id: FileId, text: &'text str) -> Tokenizer<'text> {
-        let mut chars = text.chars();
+        Self::new_in_range(file_id, text, 0..text.len())
+    }
+
+    /// Create a new [`Tokenizer`] that only tokenizes `range` (byte indices)
+    /// of `text`
+    ///
+    /// The spans of the resulting [`Token`]s are relative to the start of
+    /// `text`, not the start of `range`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use oraide_span::{FileId,FileSpan};
+    /// # use oraide_parser_miniyaml::{Token,Tokenizer};
+    /// let src = "Hello:\nWorld:\n";
+    /// let mut tokenizer = Tokenizer::new_in_range(FileId(0), src, 7..14);
+    /// let tokens: Vec<Token> = tokenizer.run();
+    ///
+    /// assert_eq!(tokens.len(), 3);
+    /// assert_eq!(tokens[0].span, FileSpan::new(FileId(0), 7, 12));
+    /// ```
+    ///
+    /// # Panics
+    /// This function will panic if `range` is out of bounds of `text` or
+    /// if either end of `range` is not on a character boundary
+    ///
+    /// [`Tokenizer`]: struct.Tokenizer.html
+    /// [`Token`]: struct.Token.html
+    pub fn new_in_range(file_id: FileId, text: &'text str, range: Range<usize>) -> Tokenizer<'text> {
+        let mut chars = text[range.clone()].chars();
         let peeked = chars.next();
 
         Self {
@@ -307,8 +345,9 @@ impl<'text> Tokenizer<'text> {
             file_id,
             chars,
             peeked,
-            token_start: ByteIndex(0),
-            token_end_exclusive: ByteIndex(0),
+            token_start: ByteIndex(range.start),
+            token_end_exclusive: ByteIndex(range.start),
+            diagnostics: vec![],
         }
     }
 
@@ -316,6 +355,17 @@ impl<'text> Tokenizer<'text> {
         self.by_ref().collect()
     }
 
+    /// Record a diagnostic
+    fn add_diagnostic(&mut self, diagnostic: Diagnostic) {
+        log::debug!("diagnostic added ({}): {:?}", diagnostic.severity, diagnostic.message);
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Take the diagnostics from the tokenizer, leaving an empty collection
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::replace(&mut self.diagnostics, Vec::new())
+    }
+
     fn consume_token(&mut self) -> Option<TokenKind> {
         self.advance().map(|ch| match ch {
             // We put non-composite symbols here (instead of in `consume_symbol`)
@@ -351,7 +401,14 @@ impl<'text> Tokenizer<'text> {
             },
             '\r' => {
                 // A `\r` not followed by `\n` is an invalid newline sequence
-                // TODO: diagnostic
+                self.add_diagnostic(
+                    // warning, not error, because we can continue tokenizing
+                    Diagnostic::new_warning("Invalid newline sequence")
+                        .with_code("L:W0001")
+                        .with_label(Label::new_primary(self.token_span()))
+                        .with_help("Use either `\\n` or `\\r\\n` to end a line")
+                );
+
                 TokenKind::Error
             },
             _ if is_symbol(ch) => self.consume_symbol(),
@@ -412,7 +469,16 @@ impl<'text> Tokenizer<'text> {
             // i.e. when the peeked token wasn't actually a symbol
             // (as defined by `is_symbol`).
             slice if slice.is_empty() => {
-                // TODO: diagnostic
+                self.add_diagnostic(
+                    Diagnostic::new_bug(format!(
+                        "{}::{} invoked with invalid {} state, expected next character to be a symbol",
+                        stringify!(Tokenizer),
+                        stringify!(consume_symbol),
+                        stringify!(Tokenizer),
+                    )).with_code("L:B0001")
+                      .with_label(Label::new_primary(self.token_span()))
+                );
+
                 TokenKind::Error
             },
             _ => TokenKind::Symbol,
@@ -444,7 +510,18 @@ impl<'text> Tokenizer<'text> {
             // the above predicate which means we called this function
             // when we shouldn't have, this is an implementation bug.
 
-            // TODO: diagnostic
+            self.add_diagnostic(
+                Diagnostic::new_bug(format!(
+                    "{}::{} invoked with invalid {} state, expected next character(s) to satisfy `{}` or `{}`",
+                    stringify!(Tokenizer),
+                    stringify!(consume_identifier_or_decimal_literal),
+                    stringify!(Tokenizer),
+                    stringify!(is_identifier_continue),
+                    stringify!(is_dec_digit_continue),
+                )).with_code("L:B0002")
+                  .with_label(Label::new_primary(self.token_span()))
+            );
+
             return TokenKind::Error;
         }
 
@@ -27,6 +27,8 @@ pub use indextree::{
 
 use crate::{
     Node,
+    Diagnostic,
+    Label,
 };
 
 pub type Arena = indextree::Arena<Node>;
@@ -39,6 +41,11 @@ const TABS_PER_INDENT_LEVEL: usize = 1;
 
 /// A [`Tree`] groups an [`indextree::Arena`] with all of its [`indextree::NodeId`]s
 ///
+/// Every line of the source text, including empty, whitespace-only, and
+/// comment-only lines, is represented by a node (in source order) in
+/// `node_ids` so a [`Tree`] is lossless, see [`Tree::to_source`].
+///
+/// [`Tree::to_source`]: struct.Tree.html#method.to_source
 /// [`Tree`]: struct.Tree.html
 /// [`indextree::Arena`]: ../indextree/struct.Arena.html
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -58,6 +65,41 @@ impl Tree {
             arena,
         }
     }
+
+    /// Reproduce, byte-for-byte, the text this tree was built from
+    ///
+    /// `text` must be the same text that was given to the [`Tokenizer`]
+    /// that produced this tree's tokens.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use oraide_span::{FileId};
+    /// # use oraide_parser_miniyaml::{Tokenizer,Nodeizer,Treeizer};
+    /// let src = "Hello:\r\n\n    # a comment\n\tWorld:  yes  \n    ";
+    /// let mut tokenizer = Tokenizer::new(FileId(0), src);
+    /// let tokens = tokenizer.run();
+    /// let mut nodeizer = Nodeizer::new(tokens.into_iter());
+    /// let nodes = nodeizer.run();
+    /// let mut treeizer = Treeizer::new(nodes.into_iter(), src);
+    /// let tree = treeizer.run();
+    ///
+    /// assert_eq!(tree.to_source(src), src);
+    /// ```
+    ///
+    /// [`Tokenizer`]: struct.Tokenizer.html
+    pub fn to_source(&self, text: &str) -> String {
+        let mut source = String::with_capacity(text.len());
+
+        let tokens = self.node_ids.iter()
+            .filter_map(|node_id| self.arena.get(*node_id))
+            .flat_map(|arena_node| arena_node.get().tokens());
+
+        for token in tokens {
+            source.push_str(token.text(text).unwrap_or_default());
+        }
+
+        source
+    }
 }
 
 /// Used to store/calculate indentation level delta between two *thing*s
@@ -169,6 +211,9 @@ pub struct Treeizer<'text, I: Iterator<Item = Node>> {
 
     /// The underlying text that is being treeized
     text: &'text str,
+
+    /// Diagnostics accumulated during treeization
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl<'text, I: Iterator<Item = Node>> Treeizer<'text, I> {
@@ -176,9 +221,21 @@ impl<'text, I: Iterator<Item = Node>> Treeizer<'text, I> {
         Self {
             nodes,
             text,
+            diagnostics: vec![],
         }
     }
 
+    /// Record a diagnostic
+    fn add_diagnostic(&mut self, diagnostic: Diagnostic) {
+        log::debug!("diagnostic added ({}): {:?}", diagnostic.severity, diagnostic.message);
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Take the diagnostics from the treeizer, leaving an empty collection
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::replace(&mut self.diagnostics, Vec::new())
+    }
+
     /// Build a [`Tree`]
     ///
     /// [`Tree`]: struct.Tree.html
@@ -190,22 +247,33 @@ impl<'text, I: Iterator<Item = Node>> Treeizer<'text, I> {
         let mut all_node_ids = vec![ parentless_sentinel_node_id ];
 
         while let Some(node) = self.nodes.next() {
-            if node.is_whitespace_only() {
-                // TODO:diag let span = node.span().expect(&format!(
-                // TODO:diag     "`{}` *should* guarantee that the node has a non-`None` span",
-                // TODO:diag     stringify!(Node::is_whitespace_only)
-                // TODO:diag ));
+            if node.is_empty() {
+                // Empty lines must not influence the parent/child relationships
+                // of the surrounding nodes, so keep track of them but don't
+                // allow them to become a parent.
+                let node_id = arena.new_node(node);
+                all_node_ids.push(node_id);
+                continue;
+            }
 
-                // TODO:diag self.add_diagnostic(
-                // TODO:diag     Diagnostic::new_warning("Found a whitespace-only line")
-                // TODO:diag         .with_code("A:W0001")
-                // TODO:diag         .with_label(Label::new_primary(span))
-                // TODO:diag );
+            if node.is_whitespace_only() {
+                let span = node.span().expect(&format!(
+                    "`{}` *should* guarantee that the node has a non-`None` span",
+                    stringify!(Node::is_whitespace_only)
+                ));
 
-                // TODO:diag self.add_diagnostic(Diagnostic::new_help("Consider making the line empty"));
+                self.add_diagnostic(
+                    Diagnostic::new_warning("Found a whitespace-only line")
+                        .with_code("A:W0001")
+                        .with_label(Label::new_primary(span))
+                        .with_help("Consider making the line empty")
+                );
 
                 // We can't do anything intelligent with a whitespace-only
-                // node so continue on to the next node.
+                // node so, like an empty node, keep track of it (so that no
+                // source text is lost) but don't allow it to become a parent.
+                let node_id = arena.new_node(node);
+                all_node_ids.push(node_id);
                 continue;
             }
 
@@ -223,14 +291,15 @@ impl<'text, I: Iterator<Item = Node>> Treeizer<'text, I> {
                 let is_all_tab = node_indent_slice.chars().all(|ch| ch == '\t');
 
                 if !is_all_space && !is_all_tab {
-                    // TODO:diag self.add_diagnostic(
-                    // TODO:diag     Diagnostic::new_error("Indentation must be entirely made up of either spaces or tabs, but not both")
-                    // TODO:diag         .with_code("A:E0001")
-                    // TODO:diag         .with_label(Label::new_primary(shrd_node_indent_token.span.clone()))
-                    // TODO:diag );
+                    self.add_diagnostic(
+                        Diagnostic::new_error("Indentation must be entirely made up of either spaces or tabs, but not both")
+                            .with_code("A:E0001")
+                            .with_label(Label::new_primary(shrd_node_indent_token.span.clone()))
+                    );
 
                     let node_id = arena.new_node(node);
                     parentless_sentinel_node_id.append(node_id, &mut arena);
+                    all_node_ids.push(node_id);
 
                     // Since the indentation is bogus there is no reason
                     // to attempt to determine the parent (it'd just be a waste of cycles
@@ -238,20 +307,17 @@ impl<'text, I: Iterator<Item = Node>> Treeizer<'text, I> {
                     // the next node.
                     continue;
                 } else if is_all_space && node_indent_slice_len % SPACES_PER_INDENT_LEVEL != 0 {
-                    // TODO:diag self.add_diagnostic(
-                    // TODO:diag     Diagnostic::new_error(format!(
-                    // TODO:diag         "Column number must be a multiple of {} when using spaces",
-                    // TODO:diag         SPACES_PER_INDENT_LEVEL
-                    // TODO:diag     )).with_code("A:E0002")
-                    // TODO:diag       .with_label(Label::new_primary(shrd_node_indent_token.span.clone()))
-                    // TODO:diag );
-
-                    // TODO:diag self.add_diagnostic(
-                    // TODO:diag     Diagnostic::new_help(format!(
-                    // TODO:diag         "Column number is currently {}",
-                    // TODO:diag         node_indent_slice_len
-                    // TODO:diag     ))
-                    // TODO:diag );
+                    self.add_diagnostic(
+                        Diagnostic::new_error(format!(
+                            "Column number must be a multiple of {} when using spaces",
+                            SPACES_PER_INDENT_LEVEL
+                        )).with_code("A:E0002")
+                          .with_label(Label::new_primary(shrd_node_indent_token.span.clone()))
+                          .with_help(format!(
+                              "Column number is currently {}",
+                              node_indent_slice_len
+                          ))
+                    );
                 }
 
                 match parent_node_ids.last() {
@@ -268,20 +334,27 @@ impl<'text, I: Iterator<Item = Node>> Treeizer<'text, I> {
                                     stringify!(arena)
                                 );
 
-                                // TODO:diag self.add_diagnostic(
-                                // TODO:diag     Diagnostic::new_bug(msg.clone())
-                                // TODO:diag         .with_code("A:E0003")
-                                // TODO:diag         .with_label(Label::new_primary(node.span().unwrap()))
-                                // TODO:diag );
+                                log::error!("{}", msg);
+
+                                self.add_diagnostic(
+                                    Diagnostic::new_bug(msg)
+                                        .with_code("A:E0003")
+                                        .with_label(Label::new_primary(node.span().unwrap()))
+                                );
 
-                                panic!("{}", msg)
+                                // We have no way to determine this node's parent
+                                // so don't attempt to guess.
+                                let node_id = arena.new_node(node);
+                                parentless_sentinel_node_id.append(node_id, &mut arena);
+                                all_node_ids.push(node_id);
+                                continue;
                             },
                         };
 
                         match IndentLevelDelta::nodes(&shrd_last_parent_node.get(), &node) {
                             IndentLevelDelta::NoChange => {
                                 let _sibling_id = parent_node_ids.pop(); // remove the sibling's ID
-                                // TODO:diag let node_span_opt = node.span();
+                                let node_span_opt = node.span();
                                 let node_id = arena.new_node(node);
 
                                 match parent_node_ids.last() {
@@ -296,13 +369,13 @@ impl<'text, I: Iterator<Item = Node>> Treeizer<'text, I> {
 
                                             log::error!("{}", err_msg);
 
-                                            // TODO:diag let mut diag = Diagnostic::new_bug(err_msg).with_code("A:E0006");
+                                            let mut diag = Diagnostic::new_bug(err_msg).with_code("A:E0006");
 
-                                            // TODO:diag if let Some(span) = node_span_opt {
-                                            // TODO:diag     diag = diag.with_label(Label::new_primary(span));
-                                            // TODO:diag }
+                                            if let Some(span) = node_span_opt {
+                                                diag = diag.with_label(Label::new_primary(span));
+                                            }
 
-                                            // TODO:diag self.add_diagnostic(diag);
+                                            self.add_diagnostic(diag);
                                         }
                                     },
                                     None => {
@@ -313,13 +386,13 @@ impl<'text, I: Iterator<Item = Node>> Treeizer<'text, I> {
 
                                         log::error!("{}", err_msg);
 
-                                        // TODO:diag let mut diag = Diagnostic::new_bug(err_msg).with_code("A:E0007");
+                                        let mut diag = Diagnostic::new_bug(err_msg).with_code("A:E0007");
 
-                                        // TODO:diag if let Some(span) = node_span_opt {
-                                        // TODO:diag     diag = diag.with_label(Label::new_primary(span));
-                                        // TODO:diag }
+                                        if let Some(span) = node_span_opt {
+                                            diag = diag.with_label(Label::new_primary(span));
+                                        }
 
-                                        // TODO:diag self.add_diagnostic(diag);
+                                        self.add_diagnostic(diag);
                                     },
                                 }
 
@@ -328,32 +401,42 @@ impl<'text, I: Iterator<Item = Node>> Treeizer<'text, I> {
                             },
                             IndentLevelDelta::MoreIndented(col_num_diff) => {
                                 if is_all_space && col_num_diff != SPACES_PER_INDENT_LEVEL {
-                                    // TODO:diag let mut diag = Diagnostic::new_error(format!(
-                                    // TODO:diag     "Indentation difference must be {} spaces",
-                                    // TODO:diag     SPACES_PER_INDENT_LEVEL
-                                    // TODO:diag )).with_code("A:E0004");
-
-                                    // TODO:diag if let Some(node_span) = node.indentation_token.as_ref().map(|token| token.span) {
-                                    // TODO:diag     diag = diag.with_label(
-                                    // TODO:diag         Label::new_primary(node_span)
-                                    // TODO:diag     );
-                                    // TODO:diag }
-
-                                    // TODO:diag self.add_diagnostic(diag);
-
-                                    // TODO:diag self.add_diagnostic(
-                                    // TODO:diag     Diagnostic::new_help(format!(
-                                    // TODO:diag         "Consider deleting {} space(s)",
-                                    // TODO:diag         col_num_diff
-                                    // TODO:diag     ))
-                                    // TODO:diag );
+                                    let mut diag = Diagnostic::new_error(format!(
+                                        "Indentation difference must be {} spaces",
+                                        SPACES_PER_INDENT_LEVEL
+                                    )).with_code("A:E0004");
+
+                                    if let Some(node_span) = node.indentation_token.as_ref().map(|token| token.span) {
+                                        diag = diag.with_label(
+                                            Label::new_primary(node_span)
+                                        );
+                                    }
+
+                                    if col_num_diff > SPACES_PER_INDENT_LEVEL {
+                                        diag = diag.with_help(format!(
+                                            "Consider deleting {} space(s)",
+                                            col_num_diff - SPACES_PER_INDENT_LEVEL
+                                        ));
+                                    } else {
+                                        diag = diag.with_help(format!(
+                                            "Consider adding {} space(s)",
+                                            SPACES_PER_INDENT_LEVEL - col_num_diff
+                                        ));
+                                    }
+
+                                    self.add_diagnostic(diag);
                                 } else if is_all_tab && col_num_diff != TABS_PER_INDENT_LEVEL {
-                                    // TODO:diag self.add_diagnostic(
-                                    // TODO:diag     Diagnostic::new_error(format!(
-                                    // TODO:diag         "Indentation difference must be {} tab(s)",
-                                    // TODO:diag         TABS_PER_INDENT_LEVEL
-                                    // TODO:diag     )).with_code("A:E0005")
-                                    // TODO:diag );
+                                    self.add_diagnostic(
+                                        Diagnostic::new_error(format!(
+                                            "Indentation difference must be {} tab(s)",
+                                            TABS_PER_INDENT_LEVEL
+                                        )).with_code("A:E0005")
+                                          .with_label(Label::new_primary(shrd_node_indent_token.span.clone()))
+                                          .with_help(format!(
+                                              "Consider deleting {} tab(s)",
+                                              col_num_diff - TABS_PER_INDENT_LEVEL
+                                          ))
+                                    );
                                 }
 
                                 let node_id = arena.new_node(node);
@@ -399,7 +482,7 @@ impl<'text, I: Iterator<Item = Node>> Treeizer<'text, I> {
                                     // so we must add 1 to the position when truncating.
                                     parent_node_ids.truncate(parent_node_id_pos + 1);
 
-                                    // TODO:diag let node_span_opt = node.span();
+                                    let node_span_opt = node.span();
                                     let node_id = arena.new_node(node);
                                     parent_node_ids.push(node_id);
                                     all_node_ids.push(node_id);
@@ -414,30 +497,34 @@ impl<'text, I: Iterator<Item = Node>> Treeizer<'text, I> {
 
                                         log::error!("{}", err_msg);
 
-                                        // TODO:diag let mut diag = Diagnostic::new_bug(err_msg).with_code("A:E0008");
+                                        let mut diag = Diagnostic::new_bug(err_msg).with_code("A:E0008");
 
-                                        // TODO:diag if let Some(span) = node_span_opt {
-                                        // TODO:diag     diag = diag.with_label(Label::new_primary(span));
-                                        // TODO:diag }
+                                        if let Some(span) = node_span_opt {
+                                            diag = diag.with_label(Label::new_primary(span));
+                                        }
 
-                                        // TODO:diag self.add_diagnostic(diag);
+                                        self.add_diagnostic(diag);
                                     }
                                 } else {
-                                    // TODO:diag self.add_diagnostic(
-                                    // TODO:diag     Diagnostic::new_error("Unable to determine parent node due to indentation")
-                                    // TODO:diag         .with_code("A:E0009")
-                                    // TODO:diag         .with_label(Label::new_primary(node.span().unwrap()))
-                                    // TODO:diag );
+                                    self.add_diagnostic(
+                                        Diagnostic::new_error("Unable to determine parent node due to indentation")
+                                            .with_code("A:E0009")
+                                            .with_label(Label::new_primary(node.span().unwrap()))
+                                    );
+
+                                    let node_id = arena.new_node(node);
+                                    parentless_sentinel_node_id.append(node_id, &mut arena);
+                                    all_node_ids.push(node_id);
                                 }
                             },
                         }
                     },
                     None => {
-                        // TODO:diag self.add_diagnostic(
-                        // TODO:diag     Diagnostic::new_error("Unable to determine parent node due to indentation")
-                        // TODO:diag         .with_code("A:E0010")
-                        // TODO:diag         .with_label(Label::new_primary(node.span().unwrap()))
-                        // TODO:diag );
+                        self.add_diagnostic(
+                            Diagnostic::new_error("Unable to determine parent node due to indentation")
+                                .with_code("A:E0010")
+                                .with_label(Label::new_primary(node.span().unwrap()))
+                        );
 
                         let node_id = arena.new_node(node);
                         parent_node_ids.push(node_id);
@@ -480,4 +567,7 @@ impl<'text, I: Iterator<Item = Node>> Treeizer<'text, I> {
         let tree = Tree::from(all_node_ids, arena);
         tree
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests;
\ No newline at end of file
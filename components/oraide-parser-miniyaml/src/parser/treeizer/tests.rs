@@ -0,0 +1,186 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+
+use pretty_assertions::assert_eq;
+use proptest::prelude::*;
+
+use oraide_span::{
+    FileId,
+    FileSpan,
+};
+
+use crate::{
+    Tokenizer,
+    Nodeizer,
+    Tree,
+    Treeizer,
+    Diagnostic,
+};
+
+fn treeize(file_id: FileId, src: &str) -> (Tree, Vec<Diagnostic>) {
+    let mut tokenizer = Tokenizer::new(file_id, src);
+    let tokens = tokenizer.run();
+
+    let mut nodeizer = Nodeizer::new(tokens.into_iter());
+    let nodes = nodeizer.run();
+
+    let mut treeizer = Treeizer::new(nodes.into_iter(), src);
+    let tree = treeizer.run();
+    let diagnostics = treeizer.take_diagnostics();
+
+    (tree, diagnostics)
+}
+
+fn codes_of(diagnostics: &[Diagnostic]) -> Vec<Option<String>> {
+    diagnostics.iter()
+        .map(|diag| diag.code.clone())
+        .collect()
+}
+
+fn child_key_texts_of<'src>(tree: &Tree, src: &'src str, parent_key: &str) -> Vec<Option<&'src str>> {
+    let parent_id = tree.node_ids.iter()
+        .find(|id| tree.arena[**id].get().key_text(src) == Some(parent_key))
+        .expect("parent node to exist");
+
+    parent_id.children(&tree.arena)
+        .map(|id| tree.arena[id].get().key_text(src))
+        .collect()
+}
+
+#[test]
+fn empty_line_does_not_detach_children() {
+    // Arrange
+    let src = "Parent:\n\n    Child:\n";
+    let file_id = FileId(0);
+
+    // Act
+    let (tree, diagnostics) = treeize(file_id, src);
+
+    // Assert
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(child_key_texts_of(&tree, src, "Parent"), vec![Some("Child")]);
+}
+
+#[test]
+fn whitespace_only_line_is_a_warning() {
+    // Arrange
+    let src = "Parent:\n    \n    Child:\n";
+    let file_id = FileId(0);
+
+    // Act
+    let (tree, diagnostics) = treeize(file_id, src);
+
+    // Assert
+    assert_eq!(codes_of(&diagnostics), vec![Some("A:W0001".into())]);
+    assert_eq!(diagnostics[0].primary_span(), Some(FileSpan::new(file_id, 8, 12)));
+    assert_eq!(diagnostics[0].help_notes, vec!["Consider making the line empty".to_owned()]);
+    assert_eq!(child_key_texts_of(&tree, src, "Parent"), vec![Some("Child")]);
+}
+
+#[test]
+fn mixed_indentation_is_an_error() {
+    // Arrange
+    let src = "Parent:\n \tChild:\n";
+    let file_id = FileId(0);
+
+    // Act
+    let (tree, diagnostics) = treeize(file_id, src);
+
+    // Assert
+    assert_eq!(codes_of(&diagnostics), vec![Some("A:E0001".into())]);
+    assert_eq!(diagnostics[0].primary_span(), Some(FileSpan::new(file_id, 8, 10)));
+    assert!(diagnostics[0].is_error());
+
+    // The node is still tracked, but it has no parent
+    assert_eq!(tree.node_ids.len(), 3);
+    assert_eq!(child_key_texts_of(&tree, src, "Parent"), Vec::<Option<&str>>::new());
+}
+
+#[test]
+fn space_indentation_not_a_multiple_of_four_is_an_error() {
+    // Arrange
+    let src = "Parent:\n  Child:\n";
+    let file_id = FileId(0);
+
+    // Act
+    let (_tree, diagnostics) = treeize(file_id, src);
+
+    // Assert
+    assert_eq!(codes_of(&diagnostics), vec![
+        Some("A:E0002".into()),
+        Some("A:E0004".into()),
+    ]);
+
+    assert_eq!(diagnostics[0].help_notes, vec!["Column number is currently 2".to_owned()]);
+    assert_eq!(diagnostics[1].help_notes, vec!["Consider adding 2 space(s)".to_owned()]);
+}
+
+#[test]
+fn over_indented_child_is_an_error() {
+    // Arrange
+    let src = "Parent:\n        Child:\n";
+    let file_id = FileId(0);
+
+    // Act
+    let (tree, diagnostics) = treeize(file_id, src);
+
+    // Assert
+    assert_eq!(codes_of(&diagnostics), vec![Some("A:E0004".into())]);
+    assert_eq!(diagnostics[0].primary_span(), Some(FileSpan::new(file_id, 8, 16)));
+    assert_eq!(diagnostics[0].help_notes, vec!["Consider deleting 4 space(s)".to_owned()]);
+    assert_eq!(child_key_texts_of(&tree, src, "Parent"), vec![Some("Child")]);
+}
+
+#[test]
+fn indented_first_node_is_an_error() {
+    // Arrange
+    let src = "    Orphan:\n";
+    let file_id = FileId(0);
+
+    // Act
+    let (tree, diagnostics) = treeize(file_id, src);
+
+    // Assert
+    assert_eq!(codes_of(&diagnostics), vec![Some("A:E0010".into())]);
+    assert_eq!(diagnostics[0].primary_span(), Some(FileSpan::new(file_id, 0, 11)));
+    assert_eq!(tree.node_ids.len(), 2);
+}
+
+#[test]
+fn to_source_reproduces_the_input() {
+    // Arrange
+    let src = "# leading comment\r\n\r\nParent:  # trailing comment\n    \n\tChild: a, b ,c\n\n  : !@^\nLast:  ";
+    let file_id = FileId(0);
+
+    // Act
+    let (tree, _diagnostics) = treeize(file_id, src);
+
+    // Assert
+    assert_eq!(tree.to_source(src), src);
+}
+
+proptest! {
+    #[test]
+    fn to_source_round_trips_miniyaml_like_text(src in "[A-Za-z0-9_ \t\r\n:#^@~!&|,.-]{0,256}") {
+        let (tree, _diagnostics) = treeize(FileId(0), &src);
+        prop_assert_eq!(tree.to_source(&src), src);
+    }
+
+    #[test]
+    fn to_source_round_trips_arbitrary_text(src in any::<String>()) {
+        let (tree, _diagnostics) = treeize(FileId(0), &src);
+        prop_assert_eq!(tree.to_source(&src), src);
+    }
+}
\ No newline at end of file
@@ -13,7 +13,10 @@
 // along with oraide.  If not, see <https://www.gnu.org/licenses/>.
 
 use std::{
-    collections::VecDeque,
+    collections::{
+        HashSet,
+        VecDeque,
+    },
     sync::mpsc::Sender,
     thread,
 };
@@ -23,6 +26,10 @@ use salsa::{
     Snapshot,
 };
 
+use oraide_span::{
+    FileId,
+};
+
 use oraide_actor::{
     RangedFilePosition,
     Actor,
@@ -37,18 +44,25 @@ use oraide_parser_miniyaml::{
     TextFilesCtx,
     TextFilesCtxExt,
     TextFilesCtxStorage,
+    ParserCtxExt,
     ParserCtxStorage,
 };
 
 use oraide_language_server::{
     LanguageServerCtx,
     LanguageServerCtxStorage,
+    workspace_symbols,
 };
 
 use oraide_sdk::{
+    GameId,
+    SdkCtx,
+    SdkCtxExt,
     SdkCtxStorage,
 };
 
+use url::Url;
+
 /// Entrypoint into MiniYaml parsing
 ///
 /// Contains inputs and memoized computation results
@@ -57,6 +71,7 @@ use oraide_sdk::{
 /// ```rust
 /// # use oraide_query_system::OraideDatabase;
 /// use oraide_parser_miniyaml::{
+///     TextFilesCtxExt,
 ///     ParserCtx,
 ///     ParserCtxExt,
 ///     Tree,
@@ -64,7 +79,8 @@ use oraide_sdk::{
 ///
 /// let mut db = OraideDatabase::default();
 /// let file_id = db.add_text_file("example.yaml", "Hello:\n");
-/// let tree: Tree = db.file_tree(file_id);
+/// db.edit_file_text(file_id, 0..5, "World");
+/// let tree: Option<Tree> = db.file_tree(file_id);
 /// ```
 #[salsa::database(
     FilesCtxStorage,
@@ -96,6 +112,8 @@ impl Default for OraideDatabase {
 
 impl FilesCtxExt for OraideDatabase {}
 impl TextFilesCtxExt for OraideDatabase {}
+impl ParserCtxExt for OraideDatabase {}
+impl SdkCtxExt for OraideDatabase {}
 
 impl ParallelDatabase for OraideDatabase {
     fn snapshot(&self) -> Snapshot<Self> {
@@ -105,11 +123,25 @@ impl ParallelDatabase for OraideDatabase {
     }
 }
 
+/// How many levels of children to include below each top-level document
+/// symbol when the client does not say, kept low so that we don't crowd the
+/// user's UI with a ton of data
+const DEFAULT_DOCUMENT_SYMBOL_DEPTH: usize = 1;
+
 pub struct QuerySystem {
     /// The channel used to send messages to a client
     send_channel: Sender<QueryResponse>,
     db: OraideDatabase,
     needs_run_diags: bool,
+
+    /// The files that have been opened or changed since diagnostics were last published
+    changed_file_ids: HashSet<FileId>,
+
+    /// The files that the client has open
+    open_file_ids: HashSet<FileId>,
+
+    /// How many levels of children to include below each top-level document symbol
+    document_symbol_depth: usize,
 }
 
 impl Actor for QuerySystem {
@@ -132,6 +164,10 @@ impl Actor for QuerySystem {
             self.needs_run_diags = true;
         }
 
+        if self.needs_run_diags {
+            self.publish_diagnostics();
+        }
+
         // All the mutations are processed, now process the next non-mutation.
         if let Some(message) = messages.pop_front() {
             assert!(!message.will_mutate_server_state());
@@ -146,17 +182,110 @@ impl QuerySystem {
             send_channel,
             db: OraideDatabase::default(),
             needs_run_diags: false,
+            changed_file_ids: HashSet::new(),
+            open_file_ids: HashSet::new(),
+            document_symbol_depth: DEFAULT_DOCUMENT_SYMBOL_DEPTH,
         }
     }
 
+    /// Compute the diagnostics for each open file, and each file that has
+    /// changed since the last time diagnostics were published, and send them
+    /// to the client
+    ///
+    /// A change to one file can fix (or cause) problems in others, such as
+    /// those that inherit from a definition in it, so every open file is
+    /// checked again rather than only those that changed.  Unchanged results
+    /// are memoized, so this is cheap.
+    fn publish_diagnostics(&mut self) {
+        self.needs_run_diags = false;
+
+        let mut changed_file_ids = std::mem::replace(&mut self.changed_file_ids, HashSet::new());
+        changed_file_ids.extend(self.open_file_ids.iter().cloned());
+
+        if changed_file_ids.is_empty() {
+            return;
+        }
+
+        thread::spawn({
+            let db = self.db.snapshot();
+            let chan = self.send_channel.clone();
+
+            move || {
+                for file_id in changed_file_ids {
+                    let file_url = match db.file_url(file_id) {
+                        Some(url) => url,
+                        _ => {
+                            log::error!("Failed to determine the URL of `FileId` `{}`", file_id.0);
+                            continue;
+                        },
+                    };
+
+                    let diagnostics = db.lsp_diagnostics_in_file(file_id).unwrap_or_default();
+
+                    send(chan.clone(), QueryResponse::Diagnostics {
+                        file_url,
+                        diagnostics,
+                    });
+                }
+            }
+        });
+    }
+
+    /// Stop overriding the text on disk of `file_id`, which the client has
+    /// closed, with the text of the client's buffer
+    ///
+    /// Files that are part of a game go back to the text on disk, since other
+    /// files still refer to what they define, others are forgotten.
+    fn close_file(&mut self, file_id: FileId, file_url: &Url) {
+        let disk_text = match file_url.to_file_path() {
+            Ok(path) if self.is_game_file(file_id) => std::fs::read_to_string(path).ok(),
+            _ => None,
+        };
+
+        match disk_text {
+            Some(text) => self.db.replace_file_text(file_id, text),
+            _ => self.db.clear_file_text(file_id),
+        }
+    }
+
+    /// Whether `file_id` is a game's manifest, is listed in one, or is a map
+    /// in one of a game's `MapFolders`
+    fn is_game_file(&self, file_id: FileId) -> bool {
+        let games = self.db.all_games().unwrap_or_default();
+
+        let is_manifest = games.into_iter()
+            .filter_map(|game| self.db.manifest_path(GameId::from(game.id().to_owned())))
+            .any(|manifest_path| self.db.file_id_of_disk_path(manifest_path) == Some(file_id));
+
+        let is_listed = self.db.manifest_listed_files()
+            .unwrap_or_default()
+            .iter()
+            .any(|(listed_file_id, _, _)| *listed_file_id == file_id);
+
+        is_manifest
+            || is_listed
+            || !self.db.game_ids_of_map_file(file_id).unwrap_or_default().is_empty()
+    }
+
     fn process_message(&mut self, message: QueryRequest) {
         match message {
-            QueryRequest::Initialize { task_id, workspace_root_url } => {
+            QueryRequest::Initialize { task_id, workspace_root_url, document_symbol_depth } => {
                 let chan = self.send_channel.clone();
                 send(chan, QueryResponse::AckInitialize { task_id });
 
-                if let Some(workspace_root_path) = workspace_root_url.and_then(|url| url.to_file_path().ok()) {
-                    self.db.set_workspace_root(workspace_root_path.into());
+                if let Some(depth) = document_symbol_depth {
+                    self.document_symbol_depth = depth;
+                }
+
+                let workspace_root_path = workspace_root_url.and_then(|url| url.to_file_path().ok());
+                let has_workspace_root = workspace_root_path.is_some();
+                self.db.set_workspace_root(workspace_root_path);
+
+                // Load everything up-front so that cross-file features, such
+                // as go-to-definition, work before the relevant files are opened.
+                if has_workspace_root {
+                    let file_ids = self.db.load_all_game_files();
+                    log::info!("Loaded {} game file(s)", file_ids.len());
                 }
             },
             QueryRequest::HoverAtPosition { task_id, file_url, file_pos } => {
@@ -207,53 +336,190 @@ impl QuerySystem {
                     }
                 });
             },
-            QueryRequest::FileOpened { file_url, file_text } => {
-                // TODO: How will we handle duplicates?
-                let _ = self.db.add_text_file(
-                    file_url.as_str(),
-                    file_text,
-                );
+            QueryRequest::Completion { task_id, file_url, file_pos } => {
+                thread::spawn({
+                    let db = self.db.snapshot();
+                    let chan = self.send_channel.clone();
+
+                    move || {
+                        match db.completions_for_position_in_file_path(
+                            file_url.to_string(),
+                            file_pos.into(),
+                        ) {
+                            Some(items) => send(chan, QueryResponse::Completions {
+                                task_id,
+                                items,
+                            }),
+                            _ => send(chan, QueryResponse::Nothing { task_id }),
+                        }
+                    }
+                });
             },
-            QueryRequest::FileChanged { file_url, changes } => {
-                let file_url = file_url.to_string();
+            QueryRequest::References { task_id, file_url, file_pos, include_declarations } => {
+                thread::spawn({
+                    let db = self.db.snapshot();
+                    let chan = self.send_channel.clone();
 
-                let file_id = self.db.file_id_of_file_path(file_url).unwrap();
-                let mut current_contents = self.db.file_text(file_id).unwrap();
+                    move || {
+                        let ranged_file_positions = db.reference_positions_in_file_path(
+                            file_url.to_string(),
+                            file_pos.into(),
+                            include_declarations,
+                        ).unwrap_or_default();
 
-                for (range, text) in changes {
-                    let start_offset = {
-                        let pos = range.start.into();
-                        let byte_index = self.db.convert_position_to_byte_index(
-                            file_id,
-                            pos,
-                        ).unwrap();
+                        send(chan, QueryResponse::References {
+                            task_id,
+                            ranged_file_positions,
+                        });
+                    }
+                });
+            },
+            QueryRequest::PrepareRename { task_id, file_url, file_pos } => {
+                thread::spawn({
+                    let db = self.db.snapshot();
+                    let chan = self.send_channel.clone();
 
-                        byte_index.to_usize()
-                    };
+                    move || {
+                        match db.rename_range_in_file_path(
+                            file_url.to_string(),
+                            file_pos.into(),
+                        ) {
+                            Some((range, placeholder)) => send(chan, QueryResponse::RenameRange {
+                                task_id,
+                                range,
+                                placeholder,
+                            }),
+                            _ => send(chan, QueryResponse::Nothing { task_id }),
+                        }
+                    }
+                });
+            },
+            QueryRequest::Rename { task_id, file_url, file_pos, new_name } => {
+                thread::spawn({
+                    let db = self.db.snapshot();
+                    let chan = self.send_channel.clone();
 
-                    let end_offset = {
-                        let pos = range.end.into();
-                        let byte_index = self.db.convert_position_to_byte_index(
-                            file_id,
-                            pos,
-                        ).unwrap();
+                    move || {
+                        match db.workspace_edit_for_rename_in_file_path(
+                            file_url.to_string(),
+                            file_pos.into(),
+                            new_name,
+                        ) {
+                            Ok(edit) => send(chan, QueryResponse::WorkspaceEdit {
+                                task_id,
+                                edit,
+                            }),
+                            Err(message) => send(chan, QueryResponse::Error {
+                                task_id,
+                                message,
+                            }),
+                        }
+                    }
+                });
+            },
+            QueryRequest::Format { task_id, file_url, use_spaces, range } => {
+                thread::spawn({
+                    let db = self.db.snapshot();
+                    let chan = self.send_channel.clone();
+
+                    move || {
+                        // A range ending at the start of a line does not include that line
+                        let line_range = range.map(|range| {
+                            let end_line_idx = if range.end.character == 0 && range.end.line > range.start.line {
+                                range.end.line
+                            } else {
+                                range.end.line + 1
+                            };
+
+                            (range.start.line as usize)..(end_line_idx as usize)
+                        });
+
+                        match db.formatting_edits_in_file_path(
+                            file_url.to_string(),
+                            use_spaces,
+                            line_range,
+                        ) {
+                            Some(edits) => send(chan, QueryResponse::TextEdits {
+                                task_id,
+                                edits,
+                            }),
+                            _ => send(chan, QueryResponse::Nothing { task_id }),
+                        }
+                    }
+                });
+            },
+            QueryRequest::FileOpened { file_url, file_text } => {
+                // The file may have already been loaded from disk, in which
+                // case the editor's buffer takes precedence.
+                let file_id = match self.db.file_id_of_url(file_url.clone()) {
+                    Some(file_id) => {
+                        self.db.replace_file_text(file_id, file_text);
+                        file_id
+                    },
+                    _ => self.db.add_text_file(
+                        file_url.as_str(),
+                        file_text,
+                    ),
+                };
+
+                self.changed_file_ids.insert(file_id);
+                self.open_file_ids.insert(file_id);
+            },
+            QueryRequest::FileChanged { file_url, changes } => {
+                let file_id = match self.db.file_id_of_url(file_url.clone()) {
+                    Some(file_id) => file_id,
+                    _ => {
+                        log::error!("Received changes for `{}` which has not been opened", file_url);
+                        return;
+                    },
+                };
+
+                // Each change is relative to the text produced by the
+                // previous change so they must be applied in order.
+                for (range, text) in changes {
+                    let start_byte_index = self.db.convert_position_to_byte_index(file_id, range.start.into());
+                    let end_byte_index = self.db.convert_position_to_byte_index(file_id, range.end.into());
 
-                        byte_index.to_usize()
+                    let applied = match (start_byte_index, end_byte_index) {
+                        (Some(start), Some(end)) => self.db.edit_file_text(
+                            file_id,
+                            start.to_usize()..end.to_usize(),
+                            &text,
+                        ),
+                        _ => None,
                     };
 
-                    current_contents.drain(start_offset..end_offset);
-                    current_contents.insert_str(start_offset, &text);
+                    if applied.is_none() {
+                        log::error!("Unable to apply change to `{}` at {:?}", file_url, range);
+                        break;
+                    }
                 }
 
-                self.db.set_file_text(file_id, current_contents.into());
+                self.changed_file_ids.insert(file_id);
+            },
+            QueryRequest::FileClosed { file_url } => {
+                if let Some(file_id) = self.db.file_id_of_url(file_url.clone()) {
+                    self.changed_file_ids.remove(&file_id);
+                    self.open_file_ids.remove(&file_id);
+                    self.close_file(file_id, &file_url);
+                }
+
+                // Problems in a closed file are no longer shown
+                send(self.send_channel.clone(), QueryResponse::Diagnostics {
+                    file_url: file_url.clone(),
+                    diagnostics: vec![],
+                });
+
+                send(self.send_channel.clone(), QueryResponse::FileClosed { file_url });
             },
             QueryRequest::FileSymbols { task_id, file_url } => {
                 thread::spawn({
                     let db = self.db.snapshot();
                     let chan = self.send_channel.clone();
+                    let max_depth = self.document_symbol_depth;
 
                     move || {
-                        let file_id = match db.file_id_of_file_path(file_url.to_string()) {
+                        let file_id = match db.file_id_of_url(file_url) {
                             Some(id) => id,
                             _ => {
                                 send(chan, QueryResponse::Nothing { task_id });
@@ -261,7 +527,7 @@ impl QuerySystem {
                             },
                         };
 
-                        match db.symbols_in_file(file_id, false) {
+                        match db.symbols_in_file(file_id, max_depth) {
                             Some(symbols) => send(chan, QueryResponse::DocumentSymbols {
                                 task_id,
                                 symbols,
@@ -271,6 +537,118 @@ impl QuerySystem {
                     }
                 });
             },
+            QueryRequest::FoldingRanges { task_id, file_url } => {
+                thread::spawn({
+                    let db = self.db.snapshot();
+                    let chan = self.send_channel.clone();
+
+                    move || {
+                        let file_id = match db.file_id_of_url(file_url) {
+                            Some(id) => id,
+                            _ => {
+                                send(chan, QueryResponse::Nothing { task_id });
+                                return;
+                            },
+                        };
+
+                        match db.folding_ranges_in_file(file_id) {
+                            Some(ranges) => send(chan, QueryResponse::FoldingRanges {
+                                task_id,
+                                ranges,
+                            }),
+                            _ => send(chan, QueryResponse::Nothing { task_id }),
+                        }
+                    }
+                });
+            },
+            QueryRequest::DocumentHighlights { task_id, file_url, file_pos } => {
+                thread::spawn({
+                    let db = self.db.snapshot();
+                    let chan = self.send_channel.clone();
+
+                    move || {
+                        let file_id = match db.file_id_of_url(file_url) {
+                            Some(id) => id,
+                            _ => {
+                                send(chan, QueryResponse::Nothing { task_id });
+                                return;
+                            },
+                        };
+
+                        match db.document_highlights_in_file(file_id, file_pos.into()) {
+                            Some(highlights) => send(chan, QueryResponse::DocumentHighlights {
+                                task_id,
+                                highlights,
+                            }),
+                            _ => send(chan, QueryResponse::Nothing { task_id }),
+                        }
+                    }
+                });
+            },
+            QueryRequest::CodeActions { task_id, file_url, range } => {
+                thread::spawn({
+                    let db = self.db.snapshot();
+                    let chan = self.send_channel.clone();
+
+                    move || {
+                        let file_id = match db.file_id_of_url(file_url) {
+                            Some(id) => id,
+                            _ => {
+                                send(chan, QueryResponse::Nothing { task_id });
+                                return;
+                            },
+                        };
+
+                        let range = oraide_actor::Range {
+                            start: range.start.into(),
+                            end_exclusive: range.end.into(),
+                        };
+
+                        match db.code_actions_in_file(file_id, range) {
+                            Some(actions) => send(chan, QueryResponse::CodeActions {
+                                task_id,
+                                actions,
+                            }),
+                            _ => send(chan, QueryResponse::Nothing { task_id }),
+                        }
+                    }
+                });
+            },
+            QueryRequest::SemanticTokens { task_id, file_url, previous_result_id } => {
+                thread::spawn({
+                    let db = self.db.snapshot();
+                    let chan = self.send_channel.clone();
+
+                    move || {
+                        let tokens = db.file_id_of_url(file_url.clone())
+                            .and_then(|file_id| db.semantic_tokens_in_file(file_id));
+
+                        match tokens {
+                            Some(tokens) => send(chan, QueryResponse::SemanticTokens {
+                                task_id,
+                                file_url,
+                                tokens,
+                                previous_result_id,
+                            }),
+                            _ => send(chan, QueryResponse::Nothing { task_id }),
+                        }
+                    }
+                });
+            },
+            QueryRequest::WorkspaceSymbols { task_id, query } => {
+                thread::spawn({
+                    let db = self.db.snapshot();
+                    let chan = self.send_channel.clone();
+
+                    move || {
+                        let symbols = workspace_symbols(&*db, &query);
+                        send(chan, QueryResponse::WorkspaceSymbols {
+                            task_id,
+                            symbols,
+                        });
+                    }
+                });
+            },
         }
     }
 }
@@ -18,6 +18,26 @@ use oraide_span::{
 
 use crate::OraideDatabase;
 
+mod workspace;
+
+mod code_actions;
+
+mod completions;
+
+mod documents;
+
+mod hover;
+
+mod manifest;
+
+mod maps;
+
+mod formatting;
+
+mod rename;
+
+mod symbols;
+
 /// Compute the `ByteIndex` of the `n`-th (1-based) `ch` in `s`
 ///
 /// # Example
@@ -0,0 +1,211 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+use oraide_span::{
+    FileId,
+};
+
+use oraide_actor::{
+    CodeAction,
+    Position,
+    Range,
+};
+
+use oraide_parser_miniyaml::{
+    TextFilesCtx,
+};
+
+use oraide_language_server::{
+    LanguageServerCtx,
+};
+
+use crate::OraideDatabase;
+
+use super::workspace::TempWorkspace;
+
+const RULES_PATH: &str = "mods/ra/rules/infantry.yaml";
+
+/// A workspace with type-data whose only game, `ra`, has `rules` as its
+/// rules file and `base` as the rules file listed before it
+fn ra_workspace(name: &str, base: &str, rules: &str) -> TempWorkspace {
+    TempWorkspace::new(name)
+        .with_type_data()
+        .with_file("mods/ra/mod.yaml", "Rules:\n    ra|rules/base.yaml\n    ra|rules/infantry.yaml\n")
+        .with_file("mods/ra/rules/base.yaml", base)
+        .with_file(RULES_PATH, rules)
+}
+
+/// Compute the code actions for the whole of `file_id`
+fn code_actions(db: &OraideDatabase, file_id: FileId) -> Vec<CodeAction> {
+    let whole_file = Range {
+        start: Position::new(0, 0),
+        end_exclusive: Position::new(usize::max_value(), 0),
+    };
+
+    db.code_actions_in_file(file_id, whole_file).unwrap_or_default()
+}
+
+/// Apply the edits of the code action titled `title` to the text of `file_id`
+///
+/// # Returns
+/// `None` if no code action is titled `title`
+fn apply_action(db: &OraideDatabase, file_id: FileId, title: &str) -> Option<String> {
+    let action = code_actions(db, file_id).into_iter()
+        .find(|action| action.title == title)?;
+
+    let mut text = db.file_text(file_id).expect("TEST LOGIC ERROR: the file has no text");
+    let mut byte_edits = vec![];
+
+    for (_, edits) in action.edit.changes {
+        for edit in edits {
+            let start = db.convert_position_to_byte_index(file_id, edit.range.start)
+                .expect("the start of an edit should be in the file")
+                .to_usize();
+
+            let end = db.convert_position_to_byte_index(file_id, edit.range.end_exclusive)
+                .expect("the end of an edit should be in the file")
+                .to_usize();
+
+            byte_edits.push((start, end, edit.new_text));
+        }
+    }
+
+    // Edits are relative to the original text, so apply the last one first
+    byte_edits.sort_by_key(|(start, _, _)| *start);
+
+    for (start, end, new_text) in byte_edits.into_iter().rev() {
+        text.replace_range(start..end, &new_text);
+    }
+
+    Some(text)
+}
+
+#[test]
+fn duplicate_is_removed() {
+    // Arrange
+    let rules = "E1:\n\tHealth:\n\t\tHP: 50\n\tMobile:\n\t\tSpeed: 10\n\tHealth:\n\t\tHP: 60\n";
+    let workspace = ra_workspace("code-actions-remove", "", rules);
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, RULES_PATH);
+
+    // Act
+    let actual = apply_action(&db, file_id, "Remove duplicate `Health`");
+
+    // Assert
+    assert_eq!(actual.as_ref().map(String::as_str), Some("E1:\n\tHealth:\n\t\tHP: 50\n\tMobile:\n\t\tSpeed: 10\n"));
+}
+
+#[test]
+fn merge_inserts_children_into_first_node_then_removes_duplicate() {
+    // Arrange
+    let rules = "E1:\n\tHealth:\n\t\tHP: 50\n\tMobile:\n\t\tSpeed: 10\n\tHealth:\n\t\tShape: Circle\n";
+    let workspace = ra_workspace("code-actions-merge", "", rules);
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, RULES_PATH);
+
+    // Act
+    let actual = apply_action(&db, file_id, "Merge into first `Health`");
+
+    // Assert
+    assert_eq!(actual.as_ref().map(String::as_str), Some("E1:\n\tHealth:\n\t\tHP: 50\n\t\tShape: Circle\n\tMobile:\n\t\tSpeed: 10\n"));
+}
+
+#[test]
+fn merge_of_adjacent_duplicate_removes_only_its_key() {
+    // Arrange
+    let rules = "E1:\n\tHealth:\n\t\tHP: 50\n\tHealth:\n\t\tShape: Circle\n";
+    let workspace = ra_workspace("code-actions-merge-adjacent", "", rules);
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, RULES_PATH);
+
+    // Act
+    let actual = apply_action(&db, file_id, "Merge into first `Health`");
+
+    // Assert
+    assert_eq!(actual.as_ref().map(String::as_str), Some("E1:\n\tHealth:\n\t\tHP: 50\n\t\tShape: Circle\n"));
+}
+
+#[test]
+fn merge_is_not_offered_when_children_would_be_duplicated() {
+    // Arrange
+    let rules = "E1:\n\tHealth:\n\t\tHP: 50\n\tMobile:\n\tHealth:\n\t\tHP: 60\n";
+    let workspace = ra_workspace("code-actions-merge-conflict", "", rules);
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, RULES_PATH);
+
+    // Act
+    let actual = apply_action(&db, file_id, "Merge into first `Health`");
+
+    // Assert
+    assert_eq!(actual, None);
+}
+
+#[test]
+fn missing_required_trait_is_inserted_after_the_definitions_key() {
+    // Arrange
+    let rules = "E1:\n\tMobile:\n";
+    let workspace = ra_workspace("code-actions-required-trait", "", rules);
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, RULES_PATH);
+
+    // Act
+    let actual = apply_action(&db, file_id, "Add required trait `Health`");
+
+    // Assert
+    assert_eq!(actual.as_ref().map(String::as_str), Some("E1:\n\tHealth:\n\tMobile:\n"));
+}
+
+#[test]
+fn missing_required_trait_is_inserted_after_an_unterminated_key_line() {
+    // Arrange
+    let workspace = ra_workspace("code-actions-required-trait-unterminated", "E1:\n\tMobile:\n", "E1:");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, RULES_PATH);
+
+    // Act
+    let actual = apply_action(&db, file_id, "Add required trait `Health`");
+
+    // Assert
+    assert_eq!(actual.as_ref().map(String::as_str), Some("E1:\n\tHealth:"));
+}
+
+#[test]
+fn space_indentation_is_rounded_to_the_nearest_level() {
+    // Arrange
+    let rules = "E1:\n  Health:\n";
+    let workspace = ra_workspace("code-actions-indentation-spaces", "", rules);
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, RULES_PATH);
+
+    // Act
+    let actual = apply_action(&db, file_id, "Indent with 4 spaces");
+
+    // Assert
+    assert_eq!(actual.as_ref().map(String::as_str), Some("E1:\n    Health:\n"));
+}
+
+#[test]
+fn mixed_indentation_uses_the_files_indentation_style() {
+    // Arrange
+    let rules = "E1:\n\tHealth:\n\t    HP: 50\n";
+    let workspace = ra_workspace("code-actions-indentation-mixed", "", rules);
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, RULES_PATH);
+
+    // Act
+    let actual = apply_action(&db, file_id, "Indent with 2 tabs");
+
+    // Assert
+    assert_eq!(actual.as_ref().map(String::as_str), Some("E1:\n\tHealth:\n\t\tHP: 50\n"));
+}
\ No newline at end of file
@@ -0,0 +1,145 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+use oraide_actor::{
+    CompletionItem,
+    CompletionItemKind,
+    Position,
+};
+
+use oraide_language_server::{
+    LanguageServerCtx,
+};
+
+use super::workspace::TempWorkspace;
+
+fn ra_workspace(name: &str) -> TempWorkspace {
+    TempWorkspace::new(name)
+        .with_type_data()
+        .with_file("mods/ra/mod.yaml", concat!(
+            "Rules:\n",
+            "    ra|rules/infantry.yaml\n",
+            "Weapons:\n",
+            "    ra|weapons/smallcaliber.yaml\n",
+        ))
+        .with_file("mods/ra/rules/infantry.yaml", concat!(
+            "^Infantry:\n",
+            "    Health:\n",
+            "        \n",
+            "E1:\n",
+            "    Inherits: \n",
+            "    \n",
+            "    Targetable:\n",
+            "        TargetTypes: \n",
+        ))
+        .with_file("mods/ra/weapons/smallcaliber.yaml", concat!(
+            "M1Carbine:\n",
+            "    \n",
+            "    Warhead@1Dam: SpreadDamage\n",
+            "        \n",
+        ))
+}
+
+fn labels_and_kinds(items: Vec<CompletionItem>) -> Vec<(String, CompletionItemKind)> {
+    items.into_iter()
+        .map(|item| (item.label, item.kind))
+        .collect()
+}
+
+#[test]
+fn trait_names_are_completed_under_a_definition_in_a_rules_file() {
+    // Arrange
+    let workspace = ra_workspace("completions-traits");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+
+    // Act
+    let actual = db.completions_for_position_in_file(file_id, Position::new(5, 4));
+
+    // Assert
+    assert_eq!(labels_and_kinds(actual.expect("traits should have been completed")), vec![
+        ("Health".to_owned(), CompletionItemKind::Trait),
+        ("Mobile".to_owned(), CompletionItemKind::Trait),
+        ("Targetable".to_owned(), CompletionItemKind::Trait),
+        ("Armament".to_owned(), CompletionItemKind::Trait),
+    ]);
+}
+
+#[test]
+fn property_names_are_completed_under_a_trait_in_a_rules_file() {
+    // Arrange
+    let workspace = ra_workspace("completions-properties");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+
+    // Act
+    let actual = db.completions_for_position_in_file(file_id, Position::new(2, 8));
+
+    // Assert
+    let actual = actual.expect("properties should have been completed");
+    assert_eq!(actual.len(), 1);
+    assert_eq!(actual[0].label, "HP");
+    assert_eq!(actual[0].kind, CompletionItemKind::Property);
+    assert_eq!(actual[0].detail, Some("Integer".to_owned()));
+}
+
+#[test]
+fn valid_values_are_completed_after_a_property_key() {
+    // Arrange
+    let workspace = ra_workspace("completions-values");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+
+    // Act
+    let actual = db.completions_for_position_in_file(file_id, Position::new(7, 21));
+
+    // Assert
+    assert_eq!(labels_and_kinds(actual.expect("values should have been completed")), vec![
+        ("Ground".to_owned(), CompletionItemKind::Value),
+        ("Water".to_owned(), CompletionItemKind::Value),
+        ("Air".to_owned(), CompletionItemKind::Value),
+    ]);
+}
+
+#[test]
+fn other_definitions_are_completed_after_inherits() {
+    // Arrange
+    let workspace = ra_workspace("completions-inherits");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+
+    // Act
+    let actual = db.completions_for_position_in_file(file_id, Position::new(4, 14));
+
+    // Assert
+    assert_eq!(labels_and_kinds(actual.expect("definitions should have been completed")), vec![
+        ("^Infantry".to_owned(), CompletionItemKind::Actor),
+    ]);
+}
+
+#[test]
+fn traits_and_properties_are_not_completed_in_a_weapons_file() {
+    // Arrange
+    let workspace = ra_workspace("completions-weapons");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, "mods/ra/weapons/smallcaliber.yaml");
+
+    // Act
+    let under_definition = db.completions_for_position_in_file(file_id, Position::new(1, 4));
+    let under_warhead = db.completions_for_position_in_file(file_id, Position::new(3, 8));
+
+    // Assert
+    assert_eq!(under_definition, None);
+    assert_eq!(under_warhead, None);
+}
\ No newline at end of file
@@ -0,0 +1,94 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+use std::sync::mpsc;
+
+use url::Url;
+
+use oraide_actor::{
+    QueryRequest,
+    QueryResponse,
+};
+
+use oraide_parser_miniyaml::{
+    TextFilesCtx,
+};
+
+use oraide_sdk::{
+    SdkCtx,
+};
+
+use crate::QuerySystem;
+
+use super::workspace::TempWorkspace;
+
+/// Open `file_url` with `file_text` and then close it
+fn open_and_close(query_system: &mut QuerySystem, file_url: &Url, file_text: &str) {
+    query_system.process_message(QueryRequest::FileOpened {
+        file_url: file_url.clone(),
+        file_text: file_text.to_owned(),
+    });
+
+    query_system.process_message(QueryRequest::FileClosed {
+        file_url: file_url.clone(),
+    });
+}
+
+#[test]
+fn closing_a_file_that_is_not_part_of_a_game_forgets_its_text() {
+    // Arrange
+    let (send_channel, receive_channel) = mpsc::channel();
+    let mut query_system = QuerySystem::new(send_channel);
+    query_system.db.set_workspace_root(None);
+
+    let file_url = Url::parse("file:///oraide/untitled.yaml").unwrap();
+
+    // Act
+    open_and_close(&mut query_system, &file_url, "E1:\n");
+
+    // Assert
+    let file_id = query_system.db.file_id_of_url(file_url.clone()).expect("the file should have been added");
+    assert_eq!(query_system.db.file_text(file_id), None);
+
+    let has_cleared_diagnostics = receive_channel.try_iter().any(|response| match response {
+        QueryResponse::Diagnostics { file_url: diagnostics_file_url, diagnostics } => {
+            diagnostics_file_url == file_url && diagnostics.is_empty()
+        },
+        _ => false,
+    });
+
+    assert!(has_cleared_diagnostics);
+}
+
+#[test]
+fn closing_a_file_of_a_game_restores_its_text_on_disk() {
+    // Arrange
+    let workspace = TempWorkspace::new("documents-close")
+        .with_file("mods/ra/mod.yaml", "Rules:\n    ra|rules/infantry.yaml\n")
+        .with_file("mods/ra/rules/infantry.yaml", "E1:\n");
+
+    let (send_channel, _receive_channel) = mpsc::channel();
+    let mut query_system = QuerySystem::new(send_channel);
+    query_system.db = workspace.load();
+
+    let file_path = workspace.root().join("mods/ra/rules/infantry.yaml");
+    let file_url = Url::from_file_path(&file_path).unwrap();
+
+    // Act
+    open_and_close(&mut query_system, &file_url, "E2:\n");
+
+    // Assert
+    let file_id = workspace.file_id(&query_system.db, "mods/ra/rules/infantry.yaml");
+    assert_eq!(query_system.db.file_text(file_id), Some("E1:\n".to_owned()));
+}
\ No newline at end of file
@@ -0,0 +1,56 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+use oraide_parser_miniyaml::{
+    TextFilesCtxExt,
+};
+
+use oraide_language_server::{
+    LanguageServerCtx,
+};
+
+use crate::OraideDatabase;
+
+fn formatted_text(text: &str, use_spaces: bool) -> Option<String> {
+    let mut db = OraideDatabase::default();
+    db.add_text_file("rules.yaml", text);
+
+    let mut edits = db.formatting_edits_in_file_path("rules.yaml".into(), use_spaces, None)?;
+    assert!(edits.len() <= 1, "{:?}", edits);
+    edits.pop().map(|edit| edit.new_text)
+}
+
+#[test]
+fn formatting_keeps_the_files_own_indentation() {
+    // Arrange
+    let text = "E1:\n\tHealth:\n\t\tHP:  50\n";
+
+    // Act
+    let actual = formatted_text(text, true);
+
+    // Assert
+    assert_eq!(actual, Some("E1:\n\tHealth:\n\t\tHP: 50\n".to_owned()));
+}
+
+#[test]
+fn formatting_keeps_indentation_with_spaces_when_the_client_prefers_tabs() {
+    // Arrange
+    let text = "E1:\n    Health:\n      HP: 50\n";
+
+    // Act
+    let actual = formatted_text(text, false);
+
+    // Assert
+    assert_eq!(actual, Some("E1:\n    Health:\n    HP: 50\n".to_owned()));
+}
\ No newline at end of file
@@ -0,0 +1,85 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+use oraide_actor::{
+    Position,
+};
+
+use oraide_language_server::{
+    LanguageServerCtx,
+};
+
+use super::workspace::TempWorkspace;
+
+fn ra_workspace(name: &str) -> TempWorkspace {
+    TempWorkspace::new(name)
+        .with_file("mods/ra/mod.yaml", concat!(
+            "Rules:\n",
+            "    ra|rules/infantry.yaml\n",
+            "Weapons:\n",
+            "    ra|weapons/smallcaliber.yaml\n",
+        ))
+        .with_file("mods/ra/rules/infantry.yaml", concat!(
+            "^Infantry:\n",
+            "    Crushable:\n",
+            "    Buildable:\n",
+            "        Prerequisites: barr\n",
+            "^Soldier:\n",
+            "    Inherits: ^Infantry\n",
+            "    -Buildable:\n",
+            "    Buildable:\n",
+            "        Prerequisites: tent\n",
+            "E1:\n",
+            "    Inherits: ^Soldier\n",
+            "    -Crushable:\n",
+            "    Buildable:\n",
+            "        -Prerequisites:\n",
+        ))
+        .with_file("mods/ra/weapons/smallcaliber.yaml", concat!(
+            "M1Carbine:\n",
+            "    Range: 5c0\n",
+        ))
+}
+
+#[test]
+fn hover_lists_only_the_actors_own_top_level_removals() {
+    // Arrange
+    let workspace = ra_workspace("hover-removals");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+
+    // Act
+    let markdown = db.resolved_actor_documentation_for_position_in_file(file_id, Position::new(9, 0));
+
+    // Assert
+    let markdown = markdown.expect("`E1` should have been resolved");
+    assert!(markdown.starts_with("**`E1`**"), "{}", markdown);
+    assert!(markdown.contains("- ~~`Crushable`"), "{}", markdown);
+    assert!(!markdown.contains("~~`Buildable`"), "{}", markdown);
+    assert!(!markdown.contains("Prerequisites"), "{}", markdown);
+}
+
+#[test]
+fn hover_does_not_resolve_top_level_keys_of_weapons_files() {
+    // Arrange
+    let workspace = ra_workspace("hover-weapons");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, "mods/ra/weapons/smallcaliber.yaml");
+
+    // Act
+    let markdown = db.resolved_actor_documentation_for_position_in_file(file_id, Position::new(0, 2));
+
+    // Assert
+    assert_eq!(markdown, None);
+}
\ No newline at end of file
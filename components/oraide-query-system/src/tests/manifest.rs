@@ -0,0 +1,159 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+use oraide_parser_miniyaml::{
+    TextFilesCtxExt,
+};
+
+use oraide_sdk::{
+    GameId,
+    SdkCtx,
+};
+
+use super::workspace::TempWorkspace;
+
+/// The code and message of each diagnostic reported for the `ra` game,
+/// whose manifest is `manifest_text`
+fn ra_manifest_problems(name: &str, manifest_text: &str) -> Vec<(Option<String>, String)> {
+    let workspace = TempWorkspace::new(name)
+        .with_file("mods/ra/mod.yaml", manifest_text)
+        .with_file("mods/ra/rules/infantry.yaml", "E1:\n");
+
+    let db = workspace.load();
+
+    db.manifest_diagnostics(GameId::from("ra".to_owned()))
+        .expect("the manifest should have been loaded")
+        .into_iter()
+        .map(|diagnostic| (diagnostic.code, diagnostic.message))
+        .collect()
+}
+
+#[test]
+fn existing_rules_file_is_not_reported() {
+    // Act
+    let actual = ra_manifest_problems(
+        "manifest-existing-file",
+        "Rules:\n    ra|rules/infantry.yaml\n",
+    );
+
+    // Assert
+    assert_eq!(actual, vec![]);
+}
+
+#[test]
+fn missing_rules_file_is_reported() {
+    // Act
+    let actual = ra_manifest_problems(
+        "manifest-missing-file",
+        "Rules:\n    ra|rules/infantry.yaml\n    ra|rules/vehicles.yaml\n",
+    );
+
+    // Assert
+    assert_eq!(actual, vec![
+        (Some("S:E0011".into()), "`ra|rules/vehicles.yaml` does not exist".into()),
+    ]);
+}
+
+#[test]
+fn missing_file_of_any_section_is_reported() {
+    // Act
+    let actual = ra_manifest_problems(
+        "manifest-missing-chrome-file",
+        "Rules:\n    ra|rules/infantry.yaml\nChromeLayout:\n    ra|chrome/ingame.yaml\n",
+    );
+
+    // Assert
+    assert_eq!(actual, vec![
+        (Some("S:E0011".into()), "`ra|chrome/ingame.yaml` does not exist".into()),
+    ]);
+}
+
+#[test]
+fn rules_file_of_unknown_game_is_reported() {
+    // Act
+    let actual = ra_manifest_problems(
+        "manifest-unknown-game",
+        "Rules:\n    td|rules/infantry.yaml\n",
+    );
+
+    // Assert
+    assert_eq!(actual, vec![
+        (Some("S:E0010".into()), "Unknown game `td`".into()),
+    ]);
+}
+
+#[test]
+fn package_of_unknown_game_is_reported() {
+    // Act
+    let actual = ra_manifest_problems(
+        "manifest-unknown-package-game",
+        "Packages:\n    td|bits/\n",
+    );
+
+    // Assert
+    assert_eq!(actual, vec![
+        (Some("S:E0010".into()), "Unknown game `td`".into()),
+    ]);
+}
+
+#[test]
+fn package_relative_to_unknown_mount_is_reported() {
+    // Act
+    let actual = ra_manifest_problems(
+        "manifest-unknown-mount",
+        "Packages:\n    ^Mount|Content/ra/\n    ^SupportDir|Content/ra/\n",
+    );
+
+    // Assert
+    assert_eq!(actual, vec![
+        (Some("S:E0012".into()), "Unknown mount `^Mount|`".into()),
+    ]);
+}
+
+#[test]
+fn missing_package_is_reported_unless_optional() {
+    // Act
+    let actual = ra_manifest_problems(
+        "manifest-optional-package",
+        "Packages:\n    ~ra|optional/\n    ra|required/\n",
+    );
+
+    // Assert
+    assert_eq!(actual, vec![
+        (Some("S:E0011".into()), "`ra|required/` does not exist".into()),
+    ]);
+}
+
+#[test]
+fn missing_file_is_no_longer_reported_once_created() {
+    // Arrange
+    let workspace = TempWorkspace::new("manifest-created-file")
+        .with_file("mods/ra/mod.yaml", "Rules:\n    ra|rules/infantry.yaml\n");
+
+    let mut db = workspace.load();
+    let game_id = GameId::from("ra".to_owned());
+    let diagnostics_before = db.manifest_diagnostics(game_id.clone()).unwrap_or_default();
+
+    // Act
+    let _workspace = workspace.with_file("mods/ra/rules/infantry.yaml", "E1:\n");
+
+    // Any change to an input starts a new revision
+    db.add_text_file("unrelated.yaml", "");
+
+    let diagnostics_after = db.manifest_diagnostics(game_id).unwrap_or_default();
+
+    // Assert
+    assert_eq!(diagnostics_before.len(), 1, "{:?}", diagnostics_before);
+    assert_eq!(diagnostics_after, vec![]);
+}
\ No newline at end of file
@@ -0,0 +1,124 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+use oraide_actor::{
+    Position,
+};
+
+use oraide_sdk::{
+    GameId,
+    PhysicalPath,
+    SdkCtx,
+};
+
+use oraide_language_server::{
+    LanguageServerCtx,
+};
+
+use super::workspace::TempWorkspace;
+
+fn ra_workspace(name: &str) -> TempWorkspace {
+    TempWorkspace::new(name)
+        .with_file("mods/ra/mod.yaml", concat!(
+            "Rules:\n",
+            "    ra|rules/infantry.yaml\n",
+            "MapFolders:\n",
+            "    ra|maps: System\n",
+        ))
+        .with_file("mods/ra/rules/infantry.yaml", concat!(
+            "E1:\n",
+            "    Health:\n",
+        ))
+        .with_file("mods/ra/maps/forest/map.yaml", concat!(
+            "Title: Forest\n",
+            "RequiresMod: ra\n",
+            "Players:\n",
+            "    PlayerReference@Neutral:\n",
+            "        Name: Neutral\n",
+            "Actors:\n",
+            "    Actor0: e1\n",
+            "        Owner: Neutral\n",
+            "        Location: 1,2\n",
+            "Rules:\n",
+            "    E1:\n",
+            "        Mobile:\n",
+        ))
+        .with_file("mods/ra/maps/readme.txt", "Not a map\n")
+}
+
+#[test]
+fn maps_are_found_in_the_games_map_folders() {
+    // Arrange
+    let workspace = ra_workspace("maps-discovery");
+    let db = workspace.load();
+
+    // Act
+    let actual = db.map_file_paths(GameId::from("ra".to_owned()));
+
+    // Assert
+    assert_eq!(actual, Some(vec![
+        PhysicalPath::Disk(workspace.root().join("mods/ra/maps/forest/map.yaml")),
+    ]));
+}
+
+#[test]
+fn map_file_belongs_to_the_games_whose_map_folders_contain_it() {
+    // Arrange
+    let workspace = ra_workspace("maps-games");
+    let db = workspace.load();
+    let map_file_id = workspace.file_id(&db, "mods/ra/maps/forest/map.yaml");
+    let rules_file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+
+    // Act
+    let map_game_ids = db.game_ids_of_map_file(map_file_id);
+    let rules_game_ids = db.game_ids_of_map_file(rules_file_id);
+
+    // Assert
+    assert_eq!(map_game_ids, Some(vec![GameId::from("ra".to_owned())]));
+    assert_eq!(rules_game_ids, Some(vec![]));
+}
+
+#[test]
+fn hover_on_map_actor_type_includes_the_maps_rules() {
+    // Arrange
+    let workspace = ra_workspace("maps-hover");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, "mods/ra/maps/forest/map.yaml");
+
+    // Act
+    let markdown = db.resolved_actor_documentation_for_position_in_file(file_id, Position::new(6, 12));
+
+    // Assert
+    let markdown = markdown.expect("`e1` should have been resolved");
+    assert!(markdown.starts_with("**`E1`**"), "{}", markdown);
+    assert!(markdown.contains("- `Health`"), "{}", markdown);
+    assert!(markdown.contains("- `Mobile`"), "{}", markdown);
+    assert!(markdown.ends_with("Placed as `Actor0`, owned by `Neutral`, at `1,2`"), "{}", markdown);
+}
+
+#[test]
+fn definition_of_map_actor_type_is_in_the_games_rules() {
+    // Arrange
+    let workspace = ra_workspace("maps-definition");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, "mods/ra/maps/forest/map.yaml");
+    let rules_file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+
+    // Act
+    let actual = db.definition_position_in_file(file_id, Position::new(6, 12));
+
+    // Assert
+    let rules_file_url = db.file_url(rules_file_id).expect("the rules file should have a URL");
+    assert_eq!(actual, Some((rules_file_url, Position::new(0, 0), Position::new(0, 2))));
+}
\ No newline at end of file
@@ -0,0 +1,155 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+use oraide_actor::{
+    Position,
+};
+
+use oraide_language_server::{
+    LanguageServerCtx,
+};
+
+use super::workspace::TempWorkspace;
+
+const RULES: &str = concat!(
+    "E1:\n",
+    "    Health:\n",
+    "APC:\n",
+    "    Cargo:\n",
+    "        InitialUnits: E1\n",
+);
+
+fn two_game_workspace(name: &str) -> TempWorkspace {
+    TempWorkspace::new(name)
+        .with_file("mods/ra/mod.yaml", "Rules:\n    ra|rules/vehicles.yaml\n")
+        .with_file("mods/ra/rules/vehicles.yaml", RULES)
+        .with_file("mods/cnc/mod.yaml", "Rules:\n    cnc|rules/vehicles.yaml\n")
+        .with_file("mods/cnc/rules/vehicles.yaml", RULES)
+}
+
+fn weapons_workspace(name: &str) -> TempWorkspace {
+    TempWorkspace::new(name)
+        .with_file("mods/ra/mod.yaml", concat!(
+            "Rules:\n",
+            "    ra|rules/infantry.yaml\n",
+            "Weapons:\n",
+            "    ra|weapons/smallcaliber.yaml\n",
+        ))
+        .with_file("mods/ra/rules/infantry.yaml", concat!(
+            "E1:\n",
+            "    Armament:\n",
+            "        Weapon: M1Carbine\n",
+        ))
+        .with_file("mods/ra/weapons/smallcaliber.yaml", concat!(
+            "M1Carbine:\n",
+            "    Range: 5c0\n",
+        ))
+}
+
+#[test]
+fn rename_only_changes_the_games_of_the_definition() {
+    // Arrange
+    let workspace = two_game_workspace("rename-games");
+    let db = workspace.load();
+    let ra_file_id = workspace.file_id(&db, "mods/ra/rules/vehicles.yaml");
+
+    // Act
+    let actual = db.rename_spans(ra_file_id, Position::new(0, 0), "E2".into());
+
+    // Assert
+    let spans = actual.expect("`E1` should be renamable");
+    assert_eq!(spans.len(), 2, "{:?}", spans);
+    assert!(spans.iter().all(|span| span.source() == ra_file_id), "{:?}", spans);
+}
+
+#[test]
+fn rename_to_the_current_name_changes_nothing() {
+    // Arrange
+    let workspace = two_game_workspace("rename-same-name");
+    let db = workspace.load();
+    let ra_file_id = workspace.file_id(&db, "mods/ra/rules/vehicles.yaml");
+
+    // Act
+    let actual = db.rename_spans(ra_file_id, Position::new(4, 22), "E1".into());
+
+    // Assert
+    assert_eq!(actual, Ok(vec![]));
+}
+
+#[test]
+fn rename_to_the_name_of_another_definition_in_the_game_is_an_error() {
+    // Arrange
+    let workspace = two_game_workspace("rename-conflict");
+    let db = workspace.load();
+    let ra_file_id = workspace.file_id(&db, "mods/ra/rules/vehicles.yaml");
+
+    // Act
+    let actual = db.rename_spans(ra_file_id, Position::new(0, 0), "APC".into());
+
+    // Assert
+    assert_eq!(actual, Err("A definition named `APC` already exists in game `ra`".to_owned()));
+}
+
+#[test]
+fn rename_of_weapon_definition_changes_its_references_in_rules_files() {
+    // Arrange
+    let workspace = weapons_workspace("rename-weapon-definition");
+    let db = workspace.load();
+    let rules_file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+    let weapons_file_id = workspace.file_id(&db, "mods/ra/weapons/smallcaliber.yaml");
+
+    // Act
+    let actual = db.rename_spans(weapons_file_id, Position::new(0, 0), "M2Carbine".into());
+
+    // Assert
+    let spans = actual.expect("`M1Carbine` should be renamable");
+    let mut sources: Vec<_> = spans.iter().map(|span| span.source()).collect();
+    sources.sort();
+
+    let mut expected_sources = vec![rules_file_id, weapons_file_id];
+    expected_sources.sort();
+
+    assert_eq!(sources, expected_sources);
+}
+
+#[test]
+fn rename_of_weapon_reference_changes_its_definition() {
+    // Arrange
+    let workspace = weapons_workspace("rename-weapon-reference");
+    let db = workspace.load();
+    let rules_file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+    let weapons_file_id = workspace.file_id(&db, "mods/ra/weapons/smallcaliber.yaml");
+
+    // Act
+    let actual = db.rename_spans(rules_file_id, Position::new(2, 16), "M2Carbine".into());
+
+    // Assert
+    let spans = actual.expect("`M1Carbine` should be renamable");
+    assert_eq!(spans.len(), 2, "{:?}", spans);
+    assert!(spans.iter().any(|span| span.source() == weapons_file_id), "{:?}", spans);
+}
+
+#[test]
+fn rename_to_the_name_of_a_weapon_in_the_game_is_an_error() {
+    // Arrange
+    let workspace = weapons_workspace("rename-weapon-conflict");
+    let db = workspace.load();
+    let rules_file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+
+    // Act
+    let actual = db.rename_spans(rules_file_id, Position::new(0, 0), "M1Carbine".into());
+
+    // Assert
+    assert_eq!(actual, Err("A definition named `M1Carbine` already exists in game `ra`".to_owned()));
+}
\ No newline at end of file
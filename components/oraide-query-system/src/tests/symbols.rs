@@ -0,0 +1,111 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+use oraide_actor::{
+    Symbol,
+    SymbolKind,
+};
+
+use oraide_language_server::{
+    LanguageServerCtx,
+};
+
+use super::workspace::TempWorkspace;
+
+fn ra_workspace(name: &str) -> TempWorkspace {
+    TempWorkspace::new(name)
+        .with_type_data()
+        .with_file("mods/ra/mod.yaml", "Rules:\n    ra|rules/infantry.yaml\n")
+        .with_file("mods/ra/rules/infantry.yaml", concat!(
+            "^Infantry:\n",
+            "    Health:\n",
+            "        HP: 50\n",
+            "E1:\n",
+            "    Inherits: ^Infantry\n",
+            "    Armament@PRIMARY:\n",
+            "        Weapon: M1Carbine\n",
+            "    -Health:\n",
+        ))
+}
+
+/// The name, kind and detail of `symbol`
+fn describe(symbol: &Symbol) -> (&str, SymbolKind, Option<&str>) {
+    (&symbol.name, symbol.kind, symbol.detail.as_ref().map(String::as_str))
+}
+
+fn children(symbol: &Symbol) -> Vec<(&str, SymbolKind, Option<&str>)> {
+    symbol.children.as_ref()
+        .expect("the symbol's children should have been computed")
+        .iter()
+        .map(describe)
+        .collect()
+}
+
+#[test]
+fn definitions_are_actors_or_abstracts_described_by_their_parents() {
+    // Arrange
+    let workspace = ra_workspace("symbols-definitions");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+
+    // Act
+    let symbols = db.symbols_in_file(file_id, 0).expect("symbols should have been computed");
+
+    // Assert
+    let actual: Vec<_> = symbols.iter().map(describe).collect();
+    assert_eq!(actual, vec![
+        ("^Infantry", SymbolKind::Abstract, None),
+        ("E1", SymbolKind::Actor, Some("inherits ^Infantry")),
+    ]);
+}
+
+#[test]
+fn traits_instances_properties_and_removals_are_classified() {
+    // Arrange
+    let workspace = ra_workspace("symbols-children");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+
+    // Act
+    let symbols = db.symbols_in_file(file_id, 2).expect("symbols should have been computed");
+
+    // Assert
+    assert_eq!(children(&symbols[0]), vec![
+        ("Health", SymbolKind::Trait, Some("OpenRA.Mods.Common.Traits")),
+    ]);
+    assert_eq!(children(&symbols[0].children.as_ref().unwrap()[0]), vec![
+        ("HP", SymbolKind::Property, Some("Integer")),
+    ]);
+    assert_eq!(children(&symbols[1]), vec![
+        ("Inherits", SymbolKind::Property, Some("^Infantry")),
+        ("Armament@PRIMARY", SymbolKind::TraitInstance, Some("OpenRA.Mods.Common.Traits")),
+        ("-Health", SymbolKind::Removal, Some("removes Health")),
+    ]);
+}
+
+#[test]
+fn children_below_the_maximum_depth_are_not_computed() {
+    // Arrange
+    let workspace = ra_workspace("symbols-depth");
+    let db = workspace.load();
+    let file_id = workspace.file_id(&db, "mods/ra/rules/infantry.yaml");
+
+    // Act
+    let symbols = db.symbols_in_file(file_id, 1).expect("symbols should have been computed");
+
+    // Assert
+    let health = &symbols[0].children.as_ref().expect("depth 1 should have been computed")[0];
+    assert_eq!(health.name, "Health");
+    assert_eq!(health.children, None);
+}
\ No newline at end of file
@@ -0,0 +1,161 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A workspace on disk for tests that need games, manifests and the files
+//! they list
+
+use std::{
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use oraide_span::{
+    FileId,
+};
+
+use oraide_sdk::{
+    SdkCtx,
+    SdkCtxExt,
+};
+
+use crate::OraideDatabase;
+
+/// The contents of a `.oraide/type-data.json` that describes:
+/// - `Health`, with an `HP` integer property
+/// - `Mobile`, with a `Speed` integer property, which requires `Health`
+/// - `Targetable`, with a `TargetTypes` property that has valid values
+/// - `Armament`, with a `Weapon` string property
+pub(super) const TYPE_DATA: &str = r#"[
+    {
+        "DefiningAssemblyName": "OpenRA.Mods.Common",
+        "IsConditional": false,
+        "RequiredTraits": [],
+        "Properties": [
+            { "Kind": "Single", "TypeName": "Int32", "HumanFriendlyTypeName": "Integer", "Name": "HP", "DocLines": null, "DefaultValue": "0", "ValidValues": null }
+        ],
+        "DocLines": ["How much damage this actor can take"],
+        "Namespace": "OpenRA.Mods.Common.Traits",
+        "Name": "Health"
+    },
+    {
+        "DefiningAssemblyName": "OpenRA.Mods.Common",
+        "IsConditional": true,
+        "RequiredTraits": [
+            { "Namespace": "OpenRA.Mods.Common.Traits", "Name": "Health" }
+        ],
+        "Properties": [
+            { "Kind": "Single", "TypeName": "Int32", "HumanFriendlyTypeName": "Integer", "Name": "Speed", "DocLines": null, "DefaultValue": "1", "ValidValues": null }
+        ],
+        "DocLines": null,
+        "Namespace": "OpenRA.Mods.Common.Traits",
+        "Name": "Mobile"
+    },
+    {
+        "DefiningAssemblyName": "OpenRA.Mods.Common",
+        "IsConditional": true,
+        "RequiredTraits": [],
+        "Properties": [
+            { "Kind": "Single", "TypeName": "TargetType", "HumanFriendlyTypeName": "TargetType", "Name": "TargetTypes", "DocLines": null, "DefaultValue": null, "ValidValues": ["Ground", "Water", "Air"] }
+        ],
+        "DocLines": null,
+        "Namespace": "OpenRA.Mods.Common.Traits",
+        "Name": "Targetable"
+    },
+    {
+        "DefiningAssemblyName": "OpenRA.Mods.Common",
+        "IsConditional": true,
+        "RequiredTraits": [],
+        "Properties": [
+            { "Kind": "Single", "TypeName": "String", "HumanFriendlyTypeName": "String", "Name": "Weapon", "DocLines": null, "DefaultValue": null, "ValidValues": null }
+        ],
+        "DocLines": null,
+        "Namespace": "OpenRA.Mods.Common.Traits",
+        "Name": "Armament"
+    }
+]"#;
+
+/// A directory, written to by a test, that is removed when dropped
+pub(super) struct TempWorkspace {
+    root: PathBuf,
+}
+
+impl TempWorkspace {
+    /// Create an empty workspace, where `name` must be unique amongst all
+    /// tests since tests run in parallel
+    pub(super) fn new(name: &str) -> Self {
+        let root = std::env::temp_dir()
+            .join(format!("oraide-{}-{}", name, std::process::id()));
+
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root)
+            .expect(&format!("TEST LOGIC ERROR: failed to create `{}`", root.display()));
+
+        Self {
+            root,
+        }
+    }
+
+    pub(super) fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Write `text` to `rel_path`, creating its parent directories as needed
+    pub(super) fn with_file(self, rel_path: &str, text: &str) -> Self {
+        let path = self.root.join(rel_path);
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .expect(&format!("TEST LOGIC ERROR: failed to create `{}`", dir.display()));
+        }
+
+        fs::write(&path, text)
+            .expect(&format!("TEST LOGIC ERROR: failed to write `{}`", path.display()));
+
+        self
+    }
+
+    /// Write [`TYPE_DATA`] to `.oraide/type-data.json`
+    ///
+    /// [`TYPE_DATA`]: constant.TYPE_DATA.html
+    pub(super) fn with_type_data(self) -> Self {
+        self.with_file(".oraide/type-data.json", TYPE_DATA)
+    }
+
+    /// Create a database rooted at this workspace with every game's files loaded
+    pub(super) fn load(&self) -> OraideDatabase {
+        let mut db = OraideDatabase::default();
+        db.set_workspace_root(Some(self.root.clone()));
+        db.load_all_game_files();
+        db
+    }
+
+    /// The [`FileId`] of the loaded file at `rel_path`
+    ///
+    /// [`FileId`]: ../../oraide_span/struct.FileId.html
+    pub(super) fn file_id(&self, db: &OraideDatabase, rel_path: &str) -> FileId {
+        let path = self.root.join(rel_path);
+
+        db.file_id_of_disk_path(path.clone())
+            .expect(&format!("TEST LOGIC ERROR: `{}` was not loaded", path.display()))
+    }
+}
+
+impl Drop for TempWorkspace {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
\ No newline at end of file
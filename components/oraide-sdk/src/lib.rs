@@ -21,9 +21,51 @@ use {
 mod sdk_ctx;
 pub use sdk_ctx::{
     SdkCtx,
+    SdkCtxExt,
     SdkCtxStorage,
 };
 
+mod manifest;
+pub use manifest::{
+    Manifest,
+    ManifestEntry,
+    ManifestLoadScreen,
+    ManifestMetadata,
+    ManifestPath,
+    ManifestSection,
+    ManifestValue,
+    KNOWN_MOUNTS,
+    parse_manifest_path,
+    split_optional,
+    split_package_path,
+};
+
+mod vfs;
+pub use vfs::{
+    MountedPackage,
+    Package,
+    PhysicalPath,
+    default_support_dir,
+    resolve_in_packages,
+};
+
+mod map;
+pub use map::{
+    Map,
+    MapActor,
+    MapPlayer,
+    MAP_FILE_NAME,
+};
+
+mod rules;
+pub use rules::{
+    RuleNode,
+    ResolvedActor,
+    Resolver,
+    merge_rule_nodes,
+    rule_nodes_from_tree,
+};
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct GameId(String);
 
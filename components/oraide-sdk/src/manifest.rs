@@ -0,0 +1,499 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Types and helpers for working with game manifests (`mod.yaml` files)
+
+use oraide_span::{
+    FileSpan,
+};
+
+use crate::{
+    RuleNode,
+};
+
+/// A top-level section of a game's manifest that lists files
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ManifestSection {
+    Rules,
+    Sequences,
+    Weapons,
+    Voices,
+    Notifications,
+    Music,
+    Chrome,
+    ChromeLayout,
+}
+
+impl ManifestSection {
+    /// All of the sections, in the order they typically appear in a manifest
+    pub const ALL: [ManifestSection; 8] = [
+        ManifestSection::Rules,
+        ManifestSection::Sequences,
+        ManifestSection::Weapons,
+        ManifestSection::Voices,
+        ManifestSection::Notifications,
+        ManifestSection::Music,
+        ManifestSection::Chrome,
+        ManifestSection::ChromeLayout,
+    ];
+
+    /// The key of this section's top-level node in a manifest
+    pub fn key(self) -> &'static str {
+        match self {
+            ManifestSection::Rules => "Rules",
+            ManifestSection::Sequences => "Sequences",
+            ManifestSection::Weapons => "Weapons",
+            ManifestSection::Voices => "Voices",
+            ManifestSection::Notifications => "Notifications",
+            ManifestSection::Music => "Music",
+            ManifestSection::Chrome => "Chrome",
+            ManifestSection::ChromeLayout => "ChromeLayout",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.iter()
+            .find(|section| section.key() == key)
+            .cloned()
+    }
+}
+
+/// Split a manifest entry such as `ra|rules/infantry.yaml` into its
+/// package (game ID) prefix, if any, and its path
+///
+/// # Example
+/// ```rust
+/// # use oraide_sdk::split_package_path;
+/// assert_eq!(split_package_path("ra|rules/infantry.yaml"), (Some("ra"), "rules/infantry.yaml"));
+/// assert_eq!(split_package_path("rules/infantry.yaml"), (None, "rules/infantry.yaml"));
+/// ```
+pub fn split_package_path(entry: &str) -> (Option<&str>, &str) {
+    match entry.find('|') {
+        Some(idx) => (Some(&entry[..idx]), &entry[idx + 1..]),
+        None => (None, entry),
+    }
+}
+
+/// The names of the directories that a `^Name|path` manifest path may be
+/// relative to
+pub const KNOWN_MOUNTS: [&str; 4] = [
+    "SupportDir",
+    "EngineDir",
+    "BinDir",
+    "GameDir",
+];
+
+/// What a path listed in a manifest's `Packages`, `MapFolders` or
+/// `Assemblies` section is relative to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ManifestPath<'a> {
+    /// `$game-id`, the directory of a game
+    GameDir(&'a str),
+
+    /// `game-id|path`, relative to the directory of a game
+    Game {
+        game_id: &'a str,
+        path: &'a str,
+    },
+
+    /// `^Mount|path`, relative to one of the [`KNOWN_MOUNTS`]
+    ///
+    /// The legacy form `^path` is relative to `SupportDir`.
+    ///
+    /// [`KNOWN_MOUNTS`]: constant.KNOWN_MOUNTS.html
+    Mount {
+        mount: &'a str,
+        path: &'a str,
+    },
+
+    /// Relative to the engine's directory, unless absolute
+    Engine(&'a str),
+}
+
+/// Split the `~` prefix, which marks a package or map folder as optional,
+/// from `entry`
+///
+/// # Returns
+/// Whether `entry` is optional and the rest of `entry`
+pub fn split_optional(entry: &str) -> (bool, &str) {
+    if entry.starts_with('~') {
+        (true, &entry[1..])
+    } else {
+        (false, entry)
+    }
+}
+
+/// Determine what `path`, which must not have a `~` prefix (see
+/// [`split_optional`]), is relative to
+///
+/// [`split_optional`]: fn.split_optional.html
+pub fn parse_manifest_path(path: &str) -> ManifestPath<'_> {
+    if path.starts_with('$') {
+        return ManifestPath::GameDir(&path[1..]);
+    }
+
+    if path.starts_with('^') {
+        return match split_package_path(&path[1..]) {
+            (Some(mount), path) => ManifestPath::Mount {
+                mount,
+                path,
+            },
+            (None, path) => ManifestPath::Mount {
+                mount: "SupportDir",
+                path,
+            },
+        };
+    }
+
+    match split_package_path(path) {
+        (Some(game_id), path) => ManifestPath::Game {
+            game_id,
+            path,
+        },
+        (None, path) => ManifestPath::Engine(path),
+    }
+}
+
+/// A piece of text from a manifest along with the span it was written at
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ManifestValue {
+    pub text: String,
+    pub span: FileSpan,
+}
+
+impl ManifestValue {
+    fn key_of(node: &RuleNode) -> Self {
+        Self {
+            text: node.key.clone(),
+            span: node.key_span,
+        }
+    }
+
+    fn value_of(node: &RuleNode) -> Option<Self> {
+        match (&node.value, node.value_span) {
+            (Some(text), Some(span)) => Some(Self {
+                text: text.clone(),
+                span,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Split a comma-separated list, such as `ra, cnc`, into its items
+    ///
+    /// Empty items are skipped.
+    fn split_list(&self) -> Vec<ManifestValue> {
+        let mut items = vec![];
+        let mut offset = 0;
+
+        for item in self.text.split(',') {
+            let trimmed_item = item.trim();
+
+            if !trimmed_item.is_empty() {
+                let start = self.span.start().to_usize() + offset + (item.len() - item.trim_start().len());
+
+                items.push(ManifestValue {
+                    text: trimmed_item.to_owned(),
+                    span: self.span
+                        .with_start(start)
+                        .with_end_exclusive(start + trimmed_item.len()),
+                });
+            }
+
+            offset += item.len() + 1; // account for the `,`
+        }
+
+        items
+    }
+}
+
+/// A `key: value` line in a manifest, such as the `./mods/common: common`
+/// entry of `Packages`, where the value is optional
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ManifestEntry {
+    pub key: ManifestValue,
+    pub value: Option<ManifestValue>,
+}
+
+impl ManifestEntry {
+    fn from_node(node: &RuleNode) -> Self {
+        Self {
+            key: ManifestValue::key_of(node),
+            value: ManifestValue::value_of(node),
+        }
+    }
+}
+
+/// The `Metadata` section of a manifest
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ManifestMetadata {
+    pub title: Option<ManifestValue>,
+    pub version: Option<ManifestValue>,
+    pub website: Option<ManifestValue>,
+}
+
+/// The `LoadScreen` section of a manifest, such as:
+///
+/// ```miniyaml
+/// LoadScreen: LogoStripeLoadScreen
+///     Image: ra|uibits/loadscreen.png
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ManifestLoadScreen {
+    /// The name of the load screen's type, such as `LogoStripeLoadScreen`
+    pub type_name: ManifestValue,
+
+    /// The properties passed to the load screen, such as `Image`
+    pub properties: Vec<ManifestEntry>,
+}
+
+/// A game's manifest (`mod.yaml`)
+///
+/// Sections that are missing from the manifest are empty (or `None`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Manifest {
+    pub metadata: ManifestMetadata,
+
+    /// The packages (directories and archives) to mount, each optionally
+    /// with an explicit name such as `$ra: ra`
+    pub packages: Vec<ManifestEntry>,
+
+    /// The directories that maps are loaded from, each optionally with a
+    /// classification such as `System` or `User`
+    pub map_folders: Vec<ManifestEntry>,
+
+    pub rules: Vec<ManifestValue>,
+    pub sequences: Vec<ManifestValue>,
+    pub weapons: Vec<ManifestValue>,
+    pub assemblies: Vec<ManifestValue>,
+    pub translations: Vec<ManifestValue>,
+    pub load_screen: Option<ManifestLoadScreen>,
+
+    /// The IDs of the games whose maps this game can load
+    pub supports_maps_from: Vec<ManifestValue>,
+}
+
+impl Manifest {
+    /// Build a `Manifest` from the top-level nodes of a `mod.yaml` file
+    ///
+    /// Unknown sections are ignored.
+    pub fn from_rule_nodes(nodes: &[RuleNode]) -> Self {
+        let mut manifest = Self::default();
+
+        for node in nodes {
+            match node.key.as_str() {
+                "Metadata" => {
+                    let metadata_value = |key: &str| node.child(key).and_then(ManifestValue::value_of);
+
+                    manifest.metadata = ManifestMetadata {
+                        title: metadata_value("Title"),
+                        version: metadata_value("Version"),
+                        website: metadata_value("Website"),
+                    };
+                },
+                "Packages" => manifest.packages = Self::entries_of(node),
+                "MapFolders" => manifest.map_folders = Self::entries_of(node),
+                "Rules" => manifest.rules = Self::keys_of(node),
+                "Sequences" => manifest.sequences = Self::keys_of(node),
+                "Weapons" => manifest.weapons = Self::keys_of(node),
+                "Assemblies" => manifest.assemblies = Self::keys_of(node),
+                "Translations" => manifest.translations = Self::keys_of(node),
+                "LoadScreen" => {
+                    manifest.load_screen = ManifestValue::value_of(node)
+                        .map(|type_name| ManifestLoadScreen {
+                            type_name,
+                            properties: Self::entries_of(node),
+                        });
+                },
+                "SupportsMapsFrom" => {
+                    manifest.supports_maps_from = ManifestValue::value_of(node)
+                        .map(|value| value.split_list())
+                        .unwrap_or_default();
+                },
+                _ => {},
+            }
+        }
+
+        manifest
+    }
+
+    fn keys_of(node: &RuleNode) -> Vec<ManifestValue> {
+        node.children.iter()
+            .map(ManifestValue::key_of)
+            .collect()
+    }
+
+    fn entries_of(node: &RuleNode) -> Vec<ManifestEntry> {
+        node.children.iter()
+            .map(ManifestEntry::from_node)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oraide_span::{
+        FileId,
+        FileSpan,
+    };
+
+    use oraide_parser_miniyaml::{
+        Tokenizer,
+        Nodeizer,
+        Treeizer,
+    };
+
+    use crate::{
+        rule_nodes_from_tree,
+    };
+
+    use super::{
+        Manifest,
+        ManifestPath,
+        ManifestSection,
+        ManifestValue,
+        parse_manifest_path,
+        split_optional,
+        split_package_path,
+    };
+
+    fn manifest(file_id: FileId, src: &str) -> Manifest {
+        let mut tokenizer = Tokenizer::new(file_id, src);
+        let tokens = tokenizer.run();
+
+        let mut nodeizer = Nodeizer::new(tokens.into_iter());
+        let nodes = nodeizer.run();
+
+        let mut treeizer = Treeizer::new(nodes.into_iter(), src);
+        let tree = treeizer.run();
+
+        let rule_nodes = rule_nodes_from_tree(&tree, src);
+        Manifest::from_rule_nodes(&rule_nodes)
+    }
+
+    fn texts(values: &[ManifestValue]) -> Vec<&str> {
+        values.iter().map(|value| value.text.as_str()).collect()
+    }
+
+    #[test]
+    fn section_round_trips_through_key() {
+        for section in ManifestSection::ALL.iter() {
+            // Act
+            let actual = ManifestSection::from_key(section.key());
+
+            // Assert
+            assert_eq!(actual, Some(*section));
+        }
+    }
+
+    #[test]
+    fn unknown_section_key_is_none() {
+        // Act
+        let actual = ManifestSection::from_key("Metadata");
+
+        // Assert
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn split_package_path_only_splits_on_first_pipe() {
+        // Act
+        let actual = split_package_path("common|foo|bar.yaml");
+
+        // Assert
+        assert_eq!(actual, (Some("common"), "foo|bar.yaml"));
+    }
+
+    #[test]
+    fn manifest_sections_are_parsed() {
+        // Arrange
+        let src = concat!(
+            "Metadata:\n",
+            "    Title: Red Alert\n",
+            "    Version: {DEV_VERSION}\n",
+            "Packages:\n",
+            "    ~^Content/ra/\n",
+            "    $ra: ra\n",
+            "Rules:\n",
+            "    ra|rules/misc.yaml\n",
+            "    ra|rules/ai.yaml\n",
+            "LoadScreen: LogoStripeLoadScreen\n",
+            "    Image: ra|uibits/loadscreen.png\n",
+        );
+
+        // Act
+        let manifest = manifest(FileId(0), src);
+
+        // Assert
+        assert_eq!(manifest.metadata.title.map(|title| title.text), Some("Red Alert".to_owned()));
+        assert_eq!(manifest.metadata.version.map(|version| version.text), Some("{DEV_VERSION}".to_owned()));
+        assert_eq!(manifest.metadata.website, None);
+
+        assert_eq!(manifest.packages.len(), 2);
+        assert_eq!(manifest.packages[0].key.text, "~^Content/ra/");
+        assert_eq!(manifest.packages[0].value, None);
+        assert_eq!(manifest.packages[1].value.as_ref().map(|value| value.text.as_str()), Some("ra"));
+
+        assert_eq!(texts(&manifest.rules), vec!["ra|rules/misc.yaml", "ra|rules/ai.yaml"]);
+        assert_eq!(manifest.weapons, vec![]);
+
+        let load_screen = manifest.load_screen.unwrap();
+        assert_eq!(load_screen.type_name.text, "LogoStripeLoadScreen");
+        assert_eq!(load_screen.properties[0].key.text, "Image");
+    }
+
+    #[test]
+    fn manifest_values_keep_their_spans() {
+        // Arrange
+        let src = "Rules:\n    rules.yaml\n";
+        let file_id = FileId(0);
+
+        // Act
+        let manifest = manifest(file_id, src);
+
+        // Assert
+        assert_eq!(manifest.rules[0].span, FileSpan::new(file_id, 11, 21));
+    }
+
+    #[test]
+    fn supports_maps_from_is_split_on_commas() {
+        // Arrange
+        let src = "SupportsMapsFrom: ra, cnc\n";
+        let file_id = FileId(0);
+
+        // Act
+        let manifest = manifest(file_id, src);
+
+        // Assert
+        assert_eq!(texts(&manifest.supports_maps_from), vec!["ra", "cnc"]);
+        assert_eq!(manifest.supports_maps_from[1].span, FileSpan::new(file_id, 22, 25));
+    }
+
+    #[test]
+    fn split_optional_strips_tilde() {
+        // Act, Assert
+        assert_eq!(split_optional("~main.mix"), (true, "main.mix"));
+        assert_eq!(split_optional("main.mix"), (false, "main.mix"));
+    }
+
+    #[test]
+    fn parse_manifest_path_recognizes_each_form() {
+        // Act, Assert
+        assert_eq!(parse_manifest_path("$ra"), ManifestPath::GameDir("ra"));
+        assert_eq!(parse_manifest_path("ra|bits"), ManifestPath::Game { game_id: "ra", path: "bits" });
+        assert_eq!(parse_manifest_path("^SupportDir|Content/ra"), ManifestPath::Mount { mount: "SupportDir", path: "Content/ra" });
+        assert_eq!(parse_manifest_path("^Content/ra"), ManifestPath::Mount { mount: "SupportDir", path: "Content/ra" });
+        assert_eq!(parse_manifest_path("./mods/common"), ManifestPath::Engine("./mods/common"));
+    }
+}
\ No newline at end of file
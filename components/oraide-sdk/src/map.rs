@@ -0,0 +1,514 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Types for working with maps (`map.yaml` files), which are found in the
+//! directories listed in a game's `MapFolders`
+
+use oraide_span::{
+    ByteIndex,
+    FileSpan,
+};
+
+use oraide_parser_miniyaml::{
+    Diagnostic,
+    Label,
+};
+
+use crate::{
+    RuleNode,
+};
+
+/// The name of the file, in a map's directory (or `.oramap` zip archive),
+/// that describes the map
+pub const MAP_FILE_NAME: &str = "map.yaml";
+
+/// A player slot, from a map's `Players` section
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MapPlayer {
+    /// The player's name, such as `Neutral` (from `PlayerReference@Neutral`)
+    pub name: String,
+    pub name_span: FileSpan,
+    pub faction: Option<String>,
+}
+
+/// An actor placed in a map, from a map's `Actors` section, such as:
+///
+/// ```miniyaml
+/// Actor123: e1
+///     Owner: Allies
+///     Location: 40,52
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MapActor {
+    /// The actor's unique name within the map, such as `Actor123`
+    pub name: String,
+    pub name_span: FileSpan,
+
+    /// The name of the actor's definition in the game's rules, such as `e1`
+    ///
+    /// OpenRA compares actor types case-insensitively, see
+    /// [`Map::definition_for_actor_type`].
+    ///
+    /// [`Map::definition_for_actor_type`]: struct.Map.html#method.definition_for_actor_type
+    pub actor_type: String,
+    pub actor_type_span: FileSpan,
+
+    /// The name of the player that owns the actor, see [`MapPlayer::name`]
+    ///
+    /// [`MapPlayer::name`]: struct.MapPlayer.html#structfield.name
+    pub owner: Option<String>,
+    pub owner_span: Option<FileSpan>,
+
+    /// The cell the actor is placed at
+    ///
+    /// `None` if the actor has no `Location` or it is malformed, in which
+    /// case `location_text` is `Some`.
+    pub location: Option<(i32, i32)>,
+    pub location_text: Option<String>,
+    pub location_span: Option<FileSpan>,
+}
+
+/// A map's `map.yaml`
+///
+/// Sections that are missing from the map are empty (or `None`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Map {
+    pub title: Option<String>,
+
+    /// The ID of the game that the map was made for
+    pub requires_mod: Option<String>,
+
+    /// The width and height, in cells, of the map
+    pub map_size: Option<(i32, i32)>,
+
+    /// The playable area of the map, as the left and top cell followed by
+    /// the width and height, in cells
+    pub bounds: Option<(i32, i32, i32, i32)>,
+
+    pub players: Vec<MapPlayer>,
+    pub actors: Vec<MapActor>,
+
+    /// Rule definitions that are merged on top of the game's rules while
+    /// the map is played
+    pub rules: Vec<RuleNode>,
+}
+
+impl Map {
+    /// Build a `Map` from the top-level nodes of a `map.yaml` file
+    ///
+    /// Unknown sections are ignored.
+    pub fn from_rule_nodes(nodes: &[RuleNode]) -> Self {
+        let mut map = Self::default();
+
+        for node in nodes {
+            match node.key.as_str() {
+                "Title" => map.title = node.value.clone(),
+                "RequiresMod" => map.requires_mod = node.value.clone(),
+                "MapSize" => {
+                    map.map_size = match node.value.as_ref().and_then(|value| parse_ints(value)) {
+                        Some(ref ints) if ints.len() == 2 => Some((ints[0], ints[1])),
+                        _ => None,
+                    };
+                },
+                "Bounds" => {
+                    map.bounds = match node.value.as_ref().and_then(|value| parse_ints(value)) {
+                        Some(ref ints) if ints.len() == 4 => Some((ints[0], ints[1], ints[2], ints[3])),
+                        _ => None,
+                    };
+                },
+                "Players" => {
+                    map.players = node.children.iter()
+                        .map(Self::player_of)
+                        .collect();
+                },
+                "Actors" => {
+                    map.actors = node.children.iter()
+                        .filter_map(Self::actor_of)
+                        .collect();
+                },
+                "Rules" => map.rules = node.children.clone(),
+                _ => {},
+            }
+        }
+
+        map
+    }
+
+    /// Find the actor whose type is written at `byte_index`
+    pub fn actor_with_type_at(&self, byte_index: ByteIndex) -> Option<&MapActor> {
+        self.actors.iter().find(|actor| actor.actor_type_span.contains(byte_index))
+    }
+
+    /// Find the definition, amongst `definitions`, of `actor_type`
+    ///
+    /// Like OpenRA, actor types are compared case-insensitively so `e1`
+    /// refers to the `E1` definition.
+    pub fn definition_for_actor_type<'defs>(
+        definitions: &'defs [RuleNode],
+        actor_type: &str,
+    ) -> Option<&'defs RuleNode> {
+        definitions.iter().find(|definition| definition.key.eq_ignore_ascii_case(actor_type))
+    }
+
+    /// Check that each actor has a unique name, a known owner, and a
+    /// well-formed location inside of the map
+    ///
+    /// If `definitions`, the game's rule definitions with the map's rules
+    /// merged on top of them, are given then each actor's type must also be
+    /// one of them.
+    pub fn validate(&self, definitions: Option<&[RuleNode]>) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        for (idx, actor) in self.actors.iter().enumerate() {
+            if let Some(first_actor) = self.actors[..idx].iter().find(|other| other.name == actor.name) {
+                diagnostics.push(
+                    Diagnostic::new_error(format!("Duplicate actor `{}`", actor.name))
+                        .with_code("S:E0013")
+                        .with_label(Label::new_primary(actor.name_span))
+                        .with_label(
+                            Label::new_secondary(first_actor.name_span)
+                                .with_message("first placed here")
+                        )
+                        .with_help("Each actor in a map must have a unique name")
+                );
+            }
+
+            if let Some(definitions) = definitions {
+                if Self::definition_for_actor_type(definitions, &actor.actor_type).is_none() {
+                    diagnostics.push(
+                        Diagnostic::new_error(format!("Unknown actor type `{}`", actor.actor_type))
+                            .with_code("S:E0014")
+                            .with_label(Label::new_primary(actor.actor_type_span))
+                    );
+                }
+            }
+
+            if let (Some(owner), Some(owner_span)) = (&actor.owner, actor.owner_span) {
+                if self.players.iter().all(|player| player.name != *owner) {
+                    let player_names: Vec<_> = self.players.iter()
+                        .map(|player| format!("`{}`", player.name))
+                        .collect();
+
+                    diagnostics.push(
+                        Diagnostic::new_error(format!("Unknown owner `{}`", owner))
+                            .with_code("S:E0015")
+                            .with_label(Label::new_primary(owner_span))
+                            .with_help(format!("The map's `Players` are: {}", player_names.join(", ")))
+                    );
+                }
+            }
+
+            let location_span = match actor.location_span {
+                Some(span) => span,
+                _ => continue,
+            };
+
+            let (x, y) = match actor.location {
+                Some(location) => location,
+                _ => {
+                    diagnostics.push(
+                        Diagnostic::new_error(format!(
+                            "Malformed location `{}`",
+                            actor.location_text.as_ref().map(String::as_str).unwrap_or_default(),
+                        ))
+                            .with_code("S:E0016")
+                            .with_label(Label::new_primary(location_span))
+                            .with_help("Expected a cell such as `40,52`")
+                    );
+
+                    continue;
+                },
+            };
+
+            if let Some((width, height)) = self.map_size {
+                if x < 0 || y < 0 || x >= width || y >= height {
+                    diagnostics.push(
+                        Diagnostic::new_error(format!("Location `{},{}` is outside of the map", x, y))
+                            .with_code("S:E0017")
+                            .with_label(Label::new_primary(location_span))
+                            .with_help(format!("The map's `MapSize` is `{},{}`", width, height))
+                    );
+
+                    continue;
+                }
+            }
+
+            // Actors outside of the playable area are allowed, but are
+            // usually a mistake
+            if let Some((left, top, width, height)) = self.bounds {
+                if x < left || y < top || x >= left + width || y >= top + height {
+                    diagnostics.push(
+                        Diagnostic::new_warning(format!("Location `{},{}` is outside of the map's bounds", x, y))
+                            .with_code("S:W0001")
+                            .with_label(Label::new_primary(location_span))
+                            .with_help(format!("The map's `Bounds` are `{},{},{},{}`", left, top, width, height))
+                    );
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    fn player_of(node: &RuleNode) -> MapPlayer {
+        // `PlayerReference@Name` nodes should also have a `Name` child, but
+        // the suffix is enough to identify the player
+        let name_and_span = node.child("Name")
+            .and_then(|name_node| match (&name_node.value, name_node.value_span) {
+                (Some(value), Some(span)) => Some((value.clone(), span)),
+                _ => None,
+            });
+
+        let (name, name_span) = name_and_span.unwrap_or_else(|| {
+            let suffix = node.key.splitn(2, '@').nth(1).unwrap_or(&node.key);
+            (suffix.to_owned(), node.key_span)
+        });
+
+        MapPlayer {
+            name,
+            name_span,
+            faction: node.child("Faction").and_then(|faction_node| faction_node.value.clone()),
+        }
+    }
+
+    fn actor_of(node: &RuleNode) -> Option<MapActor> {
+        let (actor_type, actor_type_span) = match (&node.value, node.value_span) {
+            (Some(value), Some(span)) => (value.clone(), span),
+            _ => return None,
+        };
+
+        let owner_node = node.child("Owner");
+        let location_node = node.child("Location");
+        let location_text = location_node.and_then(|location_node| location_node.value.clone());
+
+        let location = match location_text.as_ref().and_then(|text| parse_ints(text)) {
+            Some(ref ints) if ints.len() == 2 => Some((ints[0], ints[1])),
+            _ => None,
+        };
+
+        Some(MapActor {
+            name: node.key.clone(),
+            name_span: node.key_span,
+            actor_type,
+            actor_type_span,
+            owner: owner_node.and_then(|owner_node| owner_node.value.clone()),
+            owner_span: owner_node.and_then(|owner_node| owner_node.value_span),
+            location,
+            location_text,
+            location_span: location_node.and_then(|location_node| location_node.value_span),
+        })
+    }
+}
+
+/// Parse a comma-separated list of integers, such as the cell `40,52`
+fn parse_ints(value: &str) -> Option<Vec<i32>> {
+    value.split(',')
+        .map(|part| part.trim().parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use oraide_span::{
+        ByteIndex,
+        FileId,
+    };
+
+    use oraide_parser_miniyaml::{
+        Diagnostic,
+        Tokenizer,
+        Nodeizer,
+        Treeizer,
+    };
+
+    use crate::{
+        rule_nodes_from_tree,
+    };
+
+    use super::{
+        Map,
+    };
+
+    fn map(file_id: FileId, src: &str) -> Map {
+        let mut tokenizer = Tokenizer::new(file_id, src);
+        let tokens = tokenizer.run();
+
+        let mut nodeizer = Nodeizer::new(tokens.into_iter());
+        let nodes = nodeizer.run();
+
+        let mut treeizer = Treeizer::new(nodes.into_iter(), src);
+        let tree = treeizer.run();
+
+        let rule_nodes = rule_nodes_from_tree(&tree, src);
+        Map::from_rule_nodes(&rule_nodes)
+    }
+
+    #[test]
+    fn map_sections_are_parsed() {
+        // Arrange
+        let src = concat!(
+            "Title: Forest Path\n",
+            "RequiresMod: ra\n",
+            "Players:\n",
+            "    PlayerReference@Neutral:\n",
+            "        Name: Neutral\n",
+            "        Faction: allies\n",
+            "Actors:\n",
+            "    Actor0: e1\n",
+            "        Owner: Neutral\n",
+            "        Location: 40,52\n",
+            "Rules:\n",
+            "    E1:\n",
+            "        Health:\n",
+            "            HP: 1\n",
+        );
+
+        // Act
+        let map = map(FileId(0), src);
+
+        // Assert
+        assert_eq!(map.title, Some("Forest Path".to_owned()));
+        assert_eq!(map.requires_mod, Some("ra".to_owned()));
+
+        assert_eq!(map.players.len(), 1);
+        assert_eq!(map.players[0].name, "Neutral");
+        assert_eq!(map.players[0].faction, Some("allies".to_owned()));
+
+        assert_eq!(map.actors.len(), 1);
+        assert_eq!(map.actors[0].name, "Actor0");
+        assert_eq!(map.actors[0].actor_type, "e1");
+        assert_eq!(map.actors[0].owner, Some("Neutral".to_owned()));
+        assert_eq!(map.actors[0].location, Some((40, 52)));
+
+        assert_eq!(map.rules.len(), 1);
+        assert_eq!(map.rules[0].key, "E1");
+    }
+
+    #[test]
+    fn actor_type_is_found_by_span() {
+        // Arrange
+        let src = "Actors:\n    Actor0: e1\n";
+        let map = map(FileId(0), src);
+
+        // Act
+        let actual = map.actor_with_type_at(ByteIndex::from(21));
+
+        // Assert
+        assert_eq!(actual.map(|actor| actor.name.as_str()), Some("Actor0"));
+    }
+
+    #[test]
+    fn actor_types_are_case_insensitive() {
+        // Arrange
+        let rules = map(FileId(0), "Rules:\n    E1:\n    E2:\n").rules;
+
+        // Act
+        let actual = Map::definition_for_actor_type(&rules, "e2");
+
+        // Assert
+        assert_eq!(actual.map(|definition| definition.key.as_str()), Some("E2"));
+    }
+
+    fn codes_of(diagnostics: &[Diagnostic]) -> Vec<Option<&str>> {
+        diagnostics.iter()
+            .map(|diag| diag.code.as_ref().map(String::as_str))
+            .collect()
+    }
+
+    #[test]
+    fn validate_reports_unknown_types_and_owners() {
+        // Arrange
+        let src = concat!(
+            "Players:\n",
+            "    PlayerReference@Neutral:\n",
+            "        Name: Neutral\n",
+            "Actors:\n",
+            "    Actor0: e1\n",
+            "        Owner: Soviets\n",
+            "    Actor1: tank\n",
+            "        Owner: Neutral\n",
+        );
+
+        let definitions = map(FileId(1), "Rules:\n    E1:\n").rules;
+        let map = map(FileId(0), src);
+
+        // Act
+        let diagnostics = map.validate(Some(&definitions));
+
+        // Assert
+        assert_eq!(codes_of(&diagnostics), vec![Some("S:E0015"), Some("S:E0014")]);
+        assert_eq!(diagnostics[0].message, "Unknown owner `Soviets`");
+        assert_eq!(diagnostics[1].message, "Unknown actor type `tank`");
+    }
+
+    #[test]
+    fn validate_skips_actor_types_without_definitions() {
+        // Arrange
+        let map = map(FileId(0), "Actors:\n    Actor0: e1\n");
+
+        // Act
+        let diagnostics = map.validate(None);
+
+        // Assert
+        assert_eq!(diagnostics, vec![]);
+    }
+
+    #[test]
+    fn validate_reports_duplicate_actors() {
+        // Arrange
+        let src = concat!(
+            "Actors:\n",
+            "    Actor0: e1\n",
+            "    Actor0: e2\n",
+        );
+
+        let map = map(FileId(0), src);
+
+        // Act
+        let diagnostics = map.validate(None);
+
+        // Assert
+        assert_eq!(codes_of(&diagnostics), vec![Some("S:E0013")]);
+        assert_eq!(diagnostics[0].primary_span(), Some(map.actors[1].name_span));
+    }
+
+    #[test]
+    fn validate_checks_locations_against_map_size_and_bounds() {
+        // Arrange
+        let src = concat!(
+            "MapSize: 10,10\n",
+            "Bounds: 1,1,8,8\n",
+            "Actors:\n",
+            "    Actor0: e1\n",
+            "        Location: 5,5\n",
+            "    Actor1: e1\n",
+            "        Location: 20,5\n",
+            "    Actor2: e1\n",
+            "        Location: 0,0\n",
+            "    Actor3: e1\n",
+            "        Location: five,5\n",
+        );
+
+        let map = map(FileId(0), src);
+
+        // Act
+        let diagnostics = map.validate(None);
+
+        // Assert
+        assert_eq!(map.map_size, Some((10, 10)));
+        assert_eq!(map.bounds, Some((1, 1, 8, 8)));
+        assert_eq!(codes_of(&diagnostics), vec![Some("S:E0017"), Some("S:W0001"), Some("S:E0016")]);
+        assert_eq!(diagnostics[2].message, "Malformed location `five,5`");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,570 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Resolution of rule definitions (actors, weapons, etc.) in the same way
+//! that OpenRA's `MiniYaml.Merge` does
+//!
+//! Resolution happens in two steps:
+//! 1. The top-level nodes of every rule file are merged, in manifest order,
+//!    so that later definitions of a key are merged into earlier ones
+//!    (see [`merge_rule_nodes`])
+//! 2. A single definition has its `Inherits` (and `Inherits@suffix`) nodes
+//!    replaced by the resolved children of the named parent, and its
+//!    `-Key` nodes remove the previously-resolved `Key` (see [`Resolver`])
+//!
+//! [`merge_rule_nodes`]: fn.merge_rule_nodes.html
+//! [`Resolver`]: struct.Resolver.html
+
+use std::{
+    collections::HashMap,
+};
+
+use oraide_span::{
+    FileSpan,
+};
+
+use oraide_parser_miniyaml::{
+    Tree,
+    ArenaNodeId,
+    Diagnostic,
+    Label,
+};
+
+/// A MiniYaml node that has been detached from the text it was parsed from,
+/// so that nodes from multiple files can be merged together
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RuleNode {
+    pub key: String,
+
+    /// The span of the key in the most recent definition of this node
+    pub key_span: FileSpan,
+
+    pub value: Option<String>,
+    pub value_span: Option<FileSpan>,
+    pub children: Vec<RuleNode>,
+
+    /// The name of the definition this node was inherited from, if it was inherited
+    pub inherited_from: Option<String>,
+}
+
+impl RuleNode {
+    /// Find the child with the given `key`, if any
+    pub fn child(&self, key: &str) -> Option<&RuleNode> {
+        self.children.iter().find(|child| child.key == key)
+    }
+
+    /// Whether this node is an `Inherits` or `Inherits@suffix` node
+    pub fn is_inherits(&self) -> bool {
+        self.key == "Inherits" || self.key.starts_with("Inherits@")
+    }
+
+    /// Whether this node is a `-Key` node, which removes `Key` when resolved
+    pub fn is_removal(&self) -> bool {
+        self.key.starts_with('-')
+    }
+}
+
+/// A rule definition with inheritance and removals applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedActor {
+    pub node: RuleNode,
+
+    /// The top-level nodes that this definition itself removed (by a `-Key`
+    /// node) while resolving
+    ///
+    /// Removals made by parents, or inside of a node, are not included.
+    pub removed: Vec<RuleNode>,
+
+    /// Problems found while resolving, such as missing parents or inheritance cycles
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Convert the top-level nodes (and their descendants) of `tree` into [`RuleNode`]s
+///
+/// Nodes without a key, such as comment-only nodes, are skipped.
+///
+/// [`RuleNode`]: struct.RuleNode.html
+pub fn rule_nodes_from_tree(tree: &Tree, text: &str) -> Vec<RuleNode> {
+    tree.node_ids.iter().skip(1) // skip the sentinel
+        .filter(|arena_node_id| match tree.arena.get(**arena_node_id) {
+            Some(shrd_arena_node) => shrd_arena_node.get().is_top_level(),
+            _ => false,
+        })
+        .filter_map(|arena_node_id| rule_node_from_arena_node(tree, text, *arena_node_id))
+        .collect()
+}
+
+fn rule_node_from_arena_node(tree: &Tree, text: &str, arena_node_id: ArenaNodeId) -> Option<RuleNode> {
+    let shrd_node = tree.arena.get(arena_node_id)?.get();
+    let key = shrd_node.key_text(text)?.trim();
+    let key_span = shrd_node.key_span()?;
+
+    let children = arena_node_id.children(&tree.arena)
+        .filter_map(|child_node_id| rule_node_from_arena_node(tree, text, child_node_id))
+        .collect();
+
+    Some(RuleNode {
+        key: key.to_owned(),
+        key_span,
+        value: shrd_node.value_text(text).map(|value| value.to_owned()),
+        value_span: shrd_node.value_span(),
+        children,
+        inherited_from: None,
+    })
+}
+
+/// Merge `overrides` into `existing`, merging nodes that have the same key
+/// and appending those that do not
+///
+/// A `-Key` override removes the existing `Key` before any later overrides
+/// are merged, so `-Key` followed by `Key` replaces `Key` entirely.
+/// A `-Key` override that has no existing `Key` to remove is kept so that it
+/// can remove an inherited `Key` when the definition is resolved.
+pub fn merge_rule_nodes(existing: &mut Vec<RuleNode>, overrides: Vec<RuleNode>) {
+    let mut merged = std::mem::replace(existing, vec![]).into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+
+    let mut idx_by_key = HashMap::<String, usize>::with_capacity(merged.len());
+    for (idx, node) in merged.iter().enumerate() {
+        if let Some(node) = node {
+            idx_by_key.entry(node.key.clone()).or_insert(idx);
+        }
+    }
+
+    for override_node in overrides {
+        if override_node.is_removal() {
+            if let Some(idx) = idx_by_key.remove(&override_node.key[1..]) {
+                merged[idx] = None;
+                continue;
+            }
+        }
+
+        let existing_idx = idx_by_key.get(&override_node.key).cloned();
+        match existing_idx.and_then(|idx| merged[idx].as_mut()) {
+            Some(existing_node) => merge_rule_node(existing_node, override_node),
+            None => {
+                idx_by_key.insert(override_node.key.clone(), merged.len());
+                merged.push(Some(override_node));
+            },
+        }
+    }
+
+    *existing = merged.into_iter().flatten().collect();
+}
+
+fn merge_rule_node(existing: &mut RuleNode, override_node: RuleNode) {
+    existing.key_span = override_node.key_span;
+
+    if override_node.value.is_some() {
+        existing.value = override_node.value;
+        existing.value_span = override_node.value_span;
+    }
+
+    merge_rule_nodes(&mut existing.children, override_node.children);
+}
+
+/// Resolves the inheritance of a single definition against a set of merged
+/// top-level definitions, collecting diagnostics along the way
+pub struct Resolver<'defs> {
+    definitions: &'defs [RuleNode],
+    removed: Vec<RuleNode>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'defs> Resolver<'defs> {
+    pub fn new(definitions: &'defs [RuleNode]) -> Self {
+        Self {
+            definitions,
+            removed: vec![],
+            diagnostics: vec![],
+        }
+    }
+
+    /// Resolve the top-level definition with key `name`
+    ///
+    /// # Returns
+    /// `None` if there is no definition with key `name`
+    pub fn resolve(mut self, name: &str) -> Option<ResolvedActor> {
+        let definitions = self.definitions;
+        let definition = definitions.iter().find(|node| node.key == name)?;
+
+        let mut inheritance_chain = vec![ definition.key.clone() ];
+        let children = self.resolve_children(definition, &mut inheritance_chain, true);
+
+        Some(ResolvedActor {
+            node: RuleNode {
+                children,
+                ..definition.clone()
+            },
+            removed: self.removed,
+            diagnostics: self.diagnostics,
+        })
+    }
+
+    /// Compute the children of `node` with `Inherits` and `-Key` nodes applied
+    ///
+    /// `inheritance_chain` is the names of the definitions currently being
+    /// resolved, outermost first, and is used to detect cycles.
+    ///
+    /// `record_removals` is whether the nodes removed from `node`'s children
+    /// should be recorded in [`ResolvedActor::removed`].
+    ///
+    /// [`ResolvedActor::removed`]: struct.ResolvedActor.html#structfield.removed
+    fn resolve_children(&mut self, node: &RuleNode, inheritance_chain: &mut Vec<String>, record_removals: bool) -> Vec<RuleNode> {
+        let mut resolved = Vec::<RuleNode>::new();
+
+        for child in node.children.iter() {
+            if child.is_inherits() {
+                self.inherit(child, &mut resolved, inheritance_chain);
+            } else if child.is_removal() {
+                let removed_key = &child.key[1..];
+
+                match resolved.iter().position(|node| node.key == removed_key) {
+                    Some(idx) => {
+                        let removed_node = resolved.remove(idx);
+
+                        if record_removals {
+                            self.removed.push(removed_node);
+                        }
+                    },
+                    None => self.diagnostics.push(
+                        Diagnostic::new_error(format!("There is no `{}` to remove", removed_key))
+                            .with_code("S:E0004")
+                            .with_label(Label::new_primary(child.key_span))
+                            .with_help("Removals only apply to nodes defined (or inherited) before the removal")
+                    ),
+                }
+            } else {
+                self.merge_into_resolved(child.clone(), &mut resolved, inheritance_chain);
+            }
+        }
+
+        resolved
+    }
+
+    fn inherit(&mut self, inherits_node: &RuleNode, resolved: &mut Vec<RuleNode>, inheritance_chain: &mut Vec<String>) {
+        let parent_name = match inherits_node.value.as_ref() {
+            Some(name) => name,
+            _ => {
+                self.diagnostics.push(
+                    Diagnostic::new_error("Expected the name of a definition to inherit from")
+                        .with_code("S:E0005")
+                        .with_label(Label::new_primary(inherits_node.key_span))
+                );
+
+                return;
+            },
+        };
+
+        let value_span = inherits_node.value_span.unwrap_or(inherits_node.key_span);
+
+        let definitions = self.definitions;
+        let parent = match definitions.iter().find(|node| &node.key == parent_name) {
+            Some(parent) => parent,
+            _ => {
+                self.diagnostics.push(
+                    Diagnostic::new_error(format!("Unable to find a definition of `{}` to inherit from", parent_name))
+                        .with_code("S:E0003")
+                        .with_label(Label::new_primary(value_span))
+                );
+
+                return;
+            },
+        };
+
+        if inheritance_chain.contains(parent_name) {
+            let cycle = inheritance_chain.iter()
+                .chain(std::iter::once(parent_name))
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+
+            self.diagnostics.push(
+                Diagnostic::new_error(format!("Inheritance cycle detected: {}", cycle))
+                    .with_code("S:E0002")
+                    .with_label(Label::new_primary(value_span))
+                    .with_label(
+                        Label::new_secondary(parent.key_span)
+                            .with_message("this definition is already being inherited")
+                    )
+            );
+
+            return;
+        }
+
+        inheritance_chain.push(parent_name.clone());
+        let parent_children = self.resolve_children(parent, inheritance_chain, false);
+        inheritance_chain.pop();
+
+        for mut parent_child in parent_children {
+            // Nodes the parent itself inherited keep their original source
+            if parent_child.inherited_from.is_none() {
+                parent_child.inherited_from = Some(parent_name.clone());
+            }
+
+            self.merge_into_resolved(parent_child, resolved, inheritance_chain);
+        }
+    }
+
+    fn merge_into_resolved(&mut self, override_node: RuleNode, resolved: &mut Vec<RuleNode>, inheritance_chain: &mut Vec<String>) {
+        let existing = match resolved.iter_mut().find(|node| node.key == override_node.key) {
+            Some(existing) => existing,
+            None => {
+                resolved.push(override_node);
+                return;
+            },
+        };
+
+        // The most recent definition is where the merged node now comes from
+        existing.inherited_from = override_node.inherited_from.clone();
+        merge_rule_node(existing, override_node);
+
+        // The merged children may contain removals (or even inheritance)
+        // which must be applied now that both sets of children are present.
+        let children = self.resolve_children(existing, inheritance_chain, false);
+        existing.children = children;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oraide_span::{
+        FileId,
+        FileSpan,
+    };
+
+    use oraide_parser_miniyaml::{
+        Tokenizer,
+        Nodeizer,
+        Treeizer,
+    };
+
+    use super::{
+        RuleNode,
+        Resolver,
+        merge_rule_nodes,
+        rule_nodes_from_tree,
+    };
+
+    fn rule_nodes(file_id: FileId, src: &str) -> Vec<RuleNode> {
+        let mut tokenizer = Tokenizer::new(file_id, src);
+        let tokens = tokenizer.run();
+
+        let mut nodeizer = Nodeizer::new(tokens.into_iter());
+        let nodes = nodeizer.run();
+
+        let mut treeizer = Treeizer::new(nodes.into_iter(), src);
+        let tree = treeizer.run();
+
+        rule_nodes_from_tree(&tree, src)
+    }
+
+    fn keys_and_values(node: &RuleNode) -> Vec<(&str, Option<&str>)> {
+        node.children.iter()
+            .map(|child| (child.key.as_str(), child.value.as_ref().map(|value| value.as_str())))
+            .collect()
+    }
+
+    #[test]
+    fn inherits_merges_parent_children() {
+        // Arrange
+        let src = concat!(
+            "^Vehicle:\n",
+            "    Health:\n",
+            "        HP: 100\n",
+            "    Mobile:\n",
+            "E1:\n",
+            "    Inherits: ^Vehicle\n",
+            "    Health:\n",
+            "        HP: 50\n",
+        );
+
+        let definitions = rule_nodes(FileId(0), src);
+
+        // Act
+        let resolved = Resolver::new(&definitions).resolve("E1").unwrap();
+
+        // Assert
+        assert_eq!(resolved.diagnostics, vec![]);
+        assert_eq!(keys_and_values(&resolved.node), vec![("Health", None), ("Mobile", None)]);
+        assert_eq!(keys_and_values(resolved.node.child("Health").unwrap()), vec![("HP", Some("50"))]);
+    }
+
+    #[test]
+    fn removal_removes_inherited_trait_and_suffixes_are_distinct() {
+        // Arrange
+        let src = concat!(
+            "^Infantry:\n",
+            "    Armament@PRIMARY:\n",
+            "    Armament@SECONDARY:\n",
+            "    Crushable:\n",
+            "E3:\n",
+            "    Inherits@1: ^Infantry\n",
+            "    -Armament@SECONDARY:\n",
+            "    -Crushable:\n",
+        );
+
+        let definitions = rule_nodes(FileId(0), src);
+
+        // Act
+        let resolved = Resolver::new(&definitions).resolve("E3").unwrap();
+
+        // Assert
+        assert_eq!(resolved.diagnostics, vec![]);
+        assert_eq!(keys_and_values(&resolved.node), vec![("Armament@PRIMARY", None)]);
+        assert_eq!(resolved.node.children[0].inherited_from, Some("^Infantry".to_owned()));
+
+        let removed_keys: Vec<_> = resolved.removed.iter().map(|node| node.key.as_str()).collect();
+        assert_eq!(removed_keys, vec!["Armament@SECONDARY", "Crushable"]);
+    }
+
+    #[test]
+    fn only_the_definitions_own_top_level_removals_are_recorded() {
+        // Arrange
+        let src = concat!(
+            "^Infantry:\n",
+            "    Crushable:\n",
+            "    Buildable:\n",
+            "        Prerequisites: barr\n",
+            "^Soldier:\n",
+            "    Inherits: ^Infantry\n",
+            "    -Crushable:\n",
+            "E1:\n",
+            "    Inherits: ^Soldier\n",
+            "    Buildable:\n",
+            "        -Prerequisites:\n",
+        );
+
+        let definitions = rule_nodes(FileId(0), src);
+
+        // Act
+        let resolved = Resolver::new(&definitions).resolve("E1").unwrap();
+
+        // Assert
+        assert_eq!(resolved.diagnostics, vec![]);
+        assert_eq!(resolved.removed, vec![]);
+        assert_eq!(keys_and_values(&resolved.node), vec![("Buildable", None)]);
+        assert_eq!(keys_and_values(resolved.node.child("Buildable").unwrap()), vec![]);
+    }
+
+    #[test]
+    fn overriding_an_inherited_node_makes_it_the_definitions_own() {
+        // Arrange
+        let src = concat!(
+            "^Vehicle:\n",
+            "    Health:\n",
+            "        HP: 100\n",
+            "    Mobile:\n",
+            "JEEP:\n",
+            "    Inherits: ^Vehicle\n",
+            "    Health:\n",
+            "        HP: 50\n",
+        );
+
+        let definitions = rule_nodes(FileId(0), src);
+
+        // Act
+        let resolved = Resolver::new(&definitions).resolve("JEEP").unwrap();
+
+        // Assert
+        let health = resolved.node.child("Health").unwrap();
+        assert_eq!(health.inherited_from, None);
+        assert_eq!(health.key_span, FileSpan::new(FileId(0), 83, 89));
+        assert_eq!(resolved.node.child("Mobile").unwrap().inherited_from, Some("^Vehicle".to_owned()));
+    }
+
+    #[test]
+    fn later_files_are_merged_into_earlier_ones() {
+        // Arrange
+        let mut definitions = rule_nodes(FileId(0), "E1:\n    Health:\n        HP: 100\n");
+        let overrides = rule_nodes(FileId(1), "E1:\n    Health:\n        HP: 75\n    Tooltip:\n");
+
+        // Act
+        merge_rule_nodes(&mut definitions, overrides);
+        let resolved = Resolver::new(&definitions).resolve("E1").unwrap();
+
+        // Assert
+        assert_eq!(keys_and_values(&resolved.node), vec![("Health", None), ("Tooltip", None)]);
+        assert_eq!(keys_and_values(resolved.node.child("Health").unwrap()), vec![("HP", Some("75"))]);
+        assert_eq!(resolved.node.key_span, FileSpan::new(FileId(1), 0, 2));
+    }
+
+    #[test]
+    fn removal_then_readdition_in_a_later_file_replaces_the_node() {
+        // Arrange
+        let mut definitions = rule_nodes(FileId(0), "E1:\n    Health:\n        HP: 100\n    Tooltip:\n");
+        let overrides = rule_nodes(FileId(1), "E1:\n    -Health:\n    Health:\n        Shape: Circle\n");
+
+        // Act
+        merge_rule_nodes(&mut definitions, overrides);
+        let resolved = Resolver::new(&definitions).resolve("E1").unwrap();
+
+        // Assert
+        assert_eq!(resolved.diagnostics, vec![]);
+        assert_eq!(keys_and_values(&resolved.node), vec![("Tooltip", None), ("Health", None)]);
+        assert_eq!(keys_and_values(resolved.node.child("Health").unwrap()), vec![("Shape", Some("Circle"))]);
+    }
+
+    #[test]
+    fn removal_of_an_inherited_node_in_a_later_file_is_kept_for_resolution() {
+        // Arrange
+        let mut definitions = rule_nodes(FileId(0), "^Infantry:\n    Crushable:\nE1:\n    Inherits: ^Infantry\n");
+        let overrides = rule_nodes(FileId(1), "E1:\n    -Crushable:\n");
+
+        // Act
+        merge_rule_nodes(&mut definitions, overrides);
+        let resolved = Resolver::new(&definitions).resolve("E1").unwrap();
+
+        // Assert
+        assert_eq!(resolved.diagnostics, vec![]);
+        assert_eq!(keys_and_values(&resolved.node), vec![]);
+    }
+
+    #[test]
+    fn missing_parent_is_an_error() {
+        // Arrange
+        let src = "E1:\n    Inherits: ^Missing\n";
+        let definitions = rule_nodes(FileId(0), src);
+
+        // Act
+        let resolved = Resolver::new(&definitions).resolve("E1").unwrap();
+
+        // Assert
+        assert_eq!(resolved.diagnostics.len(), 1);
+        assert_eq!(resolved.diagnostics[0].code, Some("S:E0003".to_owned()));
+        assert_eq!(resolved.diagnostics[0].primary_span(), Some(FileSpan::new(FileId(0), 18, 26)));
+    }
+
+    #[test]
+    fn inheritance_cycle_is_an_error() {
+        // Arrange
+        let src = concat!(
+            "^A:\n",
+            "    Inherits: ^B\n",
+            "^B:\n",
+            "    Inherits: ^A\n",
+        );
+
+        let definitions = rule_nodes(FileId(0), src);
+
+        // Act
+        let resolved = Resolver::new(&definitions).resolve("^A").unwrap();
+
+        // Assert
+        assert_eq!(resolved.diagnostics.len(), 1);
+        assert_eq!(resolved.diagnostics[0].code, Some("S:E0002".to_owned()));
+        assert_eq!(resolved.diagnostics[0].message, "Inheritance cycle detected: `^A` -> `^B` -> `^A`");
+    }
+}
\ No newline at end of file
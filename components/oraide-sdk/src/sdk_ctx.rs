@@ -15,35 +15,361 @@
 //! This module defines `SdkCtx` which is a `salsa` query group for SDK-based
 //! game data such as:
 //! - IDs of all games under a given workspace root directory
+//! - the files listed in each game's manifest
+//! - the packages each game mounts and the files that logical paths, such
+//!   as `common|rules/misc.yaml`, resolve to
+//! - the mapping between files on disk and files in the database
+//! - the maps in each game's `MapFolders`
+//! - rule definitions with inheritance resolved
 
 use {
     std::{
         fs,
         path::{
+            Path,
             PathBuf,
             Component,
         },
     },
-    oraide_language_server::{
-        LanguageServerCtx,
+    url::Url,
+    oraide_span::{
+        FileId,
+    },
+    oraide_parser_miniyaml::{
+        ParserCtx,
+        TextFilesCtxExt,
+        Diagnostic,
+        Label,
     },
     crate::{
         Game,
         GameId,
+        Manifest,
+        ManifestPath,
+        ManifestSection,
+        ManifestValue,
+        KNOWN_MOUNTS,
+        Map,
+        MAP_FILE_NAME,
+        MountedPackage,
+        Package,
+        PhysicalPath,
+        RuleNode,
+        ResolvedActor,
+        Resolver,
+        default_support_dir,
+        merge_rule_nodes,
+        parse_manifest_path,
+        resolve_in_packages,
+        rule_nodes_from_tree,
+        split_optional,
+        split_package_path,
     },
 };
 
-// TODO: This *should not* rely on `oraide-language-server`.
 #[salsa::query_group(SdkCtxStorage)]
-pub trait SdkCtx: LanguageServerCtx {
+pub trait SdkCtx: ParserCtx {
+    /// The root directory of the workspace, typically an SDK-based project
+    #[salsa::input]
+    fn workspace_root(&self) -> Option<PathBuf>;
+
     fn all_games(&self) -> Option<Vec<Game>>;
 
+    /// Compute the directory of `game_id`
+    ///
+    /// `<workspace-root>/mods/<game-id>` is searched first, followed by
+    /// `<workspace-root>/engine/mods/<game-id>` (which is where an SDK-based
+    /// project's engine-provided games, such as `common`, are found).
+    fn game_dir(&self, game_id: GameId) -> Option<PathBuf>;
+
+    /// Compute the path of `game_id`'s manifest (`mod.yaml`)
+    fn manifest_path(&self, game_id: GameId) -> Option<PathBuf>;
+
+    /// Compute the directory of the engine, which paths in a manifest's
+    /// `Packages` section are relative to
+    ///
+    /// This is `<workspace-root>/engine` for an SDK-based project, otherwise
+    /// the workspace root itself.
+    fn engine_dir(&self) -> Option<PathBuf>;
+
+    /// Compute the packages that `game_id` mounts, in mount order, from the
+    /// `Packages` section of its manifest
+    ///
+    /// Packages that cannot be found, or are not directories or zip
+    /// archives, are skipped.
+    fn mounted_packages(&self, game_id: GameId) -> Option<Vec<MountedPackage>>;
+
+    /// Resolve `logical_path`, such as `common|rules/misc.yaml`, to the file
+    /// it refers to using the packages mounted by `game_id`
+    ///
+    /// Paths that none of the mounted packages contain are resolved against
+    /// the directory of the game they name (or `game_id`, if they do not
+    /// name one), so that games which do not mount themselves still work.
+    fn resolve_logical_path(&self, game_id: GameId, logical_path: String) -> Option<PhysicalPath>;
+
+    /// Find the [`FileId`] of a file that was loaded from `path` on disk
+    ///
+    /// Files may be tracked by either their path or their `file://` URL,
+    /// both are checked.
+    ///
+    /// [`FileId`]: ../oraide_span/struct.FileId.html
+    fn file_id_of_disk_path(&self, path: PathBuf) -> Option<FileId>;
+
+    /// Find the [`FileId`] of a file identified by `url`
+    ///
+    /// `file://` URLs are also checked against files that were loaded from disk.
+    ///
+    /// [`FileId`]: ../oraide_span/struct.FileId.html
+    fn file_id_of_url(&self, url: Url) -> Option<FileId>;
+
+    /// Compute a URL for `file_id`, suitable for sending to a language client
+    ///
+    /// Files loaded from a zip archive are given a URL as if the archive
+    /// were a directory, such as `file:///…/ra.oramod/rules/misc.yaml`
+    /// (see [`PhysicalPath::path`]), which no client is able to open.
+    ///
+    /// [`PhysicalPath::path`]: enum.PhysicalPath.html#method.path
+    fn file_url(&self, file_id: FileId) -> Option<Url>;
+
+    /// Compute the typed contents of `game_id`'s manifest
+    ///
+    /// The manifest must have been loaded, see [`SdkCtxExt::load_game_manifests`].
+    ///
+    /// [`SdkCtxExt::load_game_manifests`]: trait.SdkCtxExt.html#method.load_game_manifests
+    fn game_manifest(&self, game_id: GameId) -> Option<Manifest>;
+
+    /// Compute the problems with the paths listed in `game_id`'s manifest,
+    /// such as files that do not exist and references to unknown games
+    ///
+    /// Paths relative to a mount (such as `^SupportDir|`) are only checked
+    /// for a known mount, since the mounted directories are not part of the
+    /// workspace.
+    ///
+    /// The file system is checked again in each new revision, so a missing
+    /// file that is created is no longer reported once any input changes.
+    fn manifest_diagnostics(&self, game_id: GameId) -> Option<Vec<Diagnostic>>;
+
+    /// Compute the entries, as written and along with their spans, of
+    /// `section` in `game_id`'s manifest
+    ///
+    /// The manifest must have been loaded, see [`SdkCtxExt::load_game_manifests`].
+    ///
+    /// [`SdkCtxExt::load_game_manifests`]: trait.SdkCtxExt.html#method.load_game_manifests
+    fn manifest_section_entries(
+        &self,
+        game_id: GameId,
+        section: ManifestSection,
+    ) -> Option<Vec<ManifestValue>>;
+
+    /// Compute where the files listed in `section` of `game_id`'s manifest
+    /// can be read from, see [`resolve_logical_path`]
+    ///
+    /// Entries that cannot be resolved are skipped.
+    ///
+    /// [`resolve_logical_path`]: #tymethod.resolve_logical_path
+    fn resolved_physical_paths_for_game_section(
+        &self,
+        game_id: GameId,
+        section: ManifestSection,
+    ) -> Option<Vec<PhysicalPath>>;
+
+    /// Compute the paths of the files listed in `section` of `game_id`'s
+    /// manifest, resolving `game-id|path` references
+    ///
+    /// Files in zip archives have paths as if the archive were a directory,
+    /// see [`PhysicalPath::path`].
+    ///
+    /// [`PhysicalPath::path`]: enum.PhysicalPath.html#method.path
+    fn resolved_file_paths_for_game_section(
+        &self,
+        game_id: GameId,
+        section: ManifestSection,
+    ) -> Option<Vec<PathBuf>>;
+
     fn resolved_rule_file_paths_for_game(&self, game_id: GameId) -> Option<Vec<PathBuf>>;
+
+    /// Compute every loaded file that is listed in a game's manifest, along
+    /// with the game and the section that lists it
+    ///
+    /// A file that is listed by more than one game (or section) appears once
+    /// per listing.
+    fn manifest_listed_files(&self) -> Option<Vec<(FileId, GameId, ManifestSection)>>;
+
+    /// Compute the IDs of the games whose manifest lists `file_id` as a rules file
+    fn game_ids_of_rule_file(&self, file_id: FileId) -> Option<Vec<GameId>>;
+
+    /// Compute the [`RuleNode`]s of the top-level nodes in `file_id`
+    ///
+    /// [`RuleNode`]: struct.RuleNode.html
+    fn rule_nodes_in_file(&self, file_id: FileId) -> Option<Vec<RuleNode>>;
+
+    /// Compute where the `map.yaml` of each map in the directories listed in
+    /// `game_id`'s `MapFolders` can be read from
+    ///
+    /// A map is either a directory or an `.oramap` zip archive.
+    fn map_file_paths(&self, game_id: GameId) -> Option<Vec<PhysicalPath>>;
+
+    /// Compute the IDs of the games whose `MapFolders` contain `file_id`
+    fn game_ids_of_map_file(&self, file_id: FileId) -> Option<Vec<GameId>>;
+
+    /// Compute the [`Map`] described by `file_id`, a `map.yaml`
+    ///
+    /// [`Map`]: struct.Map.html
+    fn map_in_file(&self, file_id: FileId) -> Option<Map>;
+
+    /// Compute the top-level rule definitions of `game_id` with the inline
+    /// `Rules` of the map in `file_id` merged on top of them
+    fn map_rule_definitions(&self, game_id: GameId, file_id: FileId) -> Option<Vec<RuleNode>>;
+
+    /// Compute the definition of `actor_type`, as placed in the map in
+    /// `file_id`, with the map's rules and all inheritance applied
+    ///
+    /// Actor types are compared case-insensitively, see
+    /// [`Map::definition_for_actor_type`].
+    ///
+    /// [`Map::definition_for_actor_type`]: struct.Map.html#method.definition_for_actor_type
+    fn resolved_map_actor(
+        &self,
+        game_id: GameId,
+        file_id: FileId,
+        actor_type: String,
+    ) -> Option<ResolvedActor>;
+
+    /// Compute the top-level rule definitions of `game_id`, merged across all
+    /// of its rules files in manifest order
+    ///
+    /// Inheritance is *not* resolved, see [`resolved_actor`].
+    ///
+    /// [`resolved_actor`]: #tymethod.resolved_actor
+    fn game_rule_definitions(&self, game_id: GameId) -> Option<Vec<RuleNode>>;
+
+    /// Compute the definition of `actor_name` in `game_id` with all
+    /// inheritance (`Inherits`) and removals (`-Key`) applied
+    fn resolved_actor(&self, game_id: GameId, actor_name: String) -> Option<ResolvedActor>;
+}
+
+pub trait SdkCtxExt: SdkCtx + TextFilesCtxExt {
+    /// Read the manifest of each game in the workspace and add it to the
+    /// database, skipping those that have already been added
+    ///
+    /// # Returns
+    /// The [`FileId`] of each manifest
+    ///
+    /// [`FileId`]: ../oraide_span/struct.FileId.html
+    fn load_game_manifests(&mut self) -> Vec<FileId> {
+        let games = self.all_games().unwrap_or_default();
+
+        games.into_iter()
+            .filter_map(|game| {
+                let manifest_path = self.manifest_path(game.id)?;
+                self.load_disk_file(manifest_path)
+            })
+            .collect()
+    }
+
+    /// Read every game's manifest, every file listed in those manifests and
+    /// every map, and add them to the database, skipping those that have
+    /// already been added
+    ///
+    /// # Returns
+    /// The [`FileId`] of each file
+    ///
+    /// [`FileId`]: ../oraide_span/struct.FileId.html
+    fn load_all_game_files(&mut self) -> Vec<FileId> {
+        let mut file_ids = self.load_game_manifests();
+        let games = self.all_games().unwrap_or_default();
+
+        for game in games {
+            for section in ManifestSection::ALL.iter() {
+                let physical_paths = self.resolved_physical_paths_for_game_section(game.id.clone(), *section)
+                    .unwrap_or_default();
+
+                for physical_path in physical_paths {
+                    if let Some(file_id) = self.load_physical_file(physical_path) {
+                        file_ids.push(file_id);
+                    }
+                }
+            }
+
+            for physical_path in self.map_file_paths(game.id.clone()).unwrap_or_default() {
+                if let Some(file_id) = self.load_physical_file(physical_path) {
+                    file_ids.push(file_id);
+                }
+            }
+        }
+
+        file_ids.sort();
+        file_ids.dedup();
+        file_ids
+    }
+
+    /// Read the file at `path` and add it to the database, skipping
+    /// it if it has already been added
+    ///
+    /// # Returns
+    /// - `Some(file_id)` if the file has been (or was already) added
+    /// - `None` if the file could not be read
+    fn load_disk_file(&mut self, path: PathBuf) -> Option<FileId> {
+        if let Some(file_id) = self.file_id_of_disk_path(path.clone()) {
+            return Some(file_id);
+        }
+
+        let file_text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                log::error!("Failed to read `{}`: {}", path.display(), e);
+                return None;
+            },
+        };
+
+        let file_id = self.add_text_file(path.to_string_lossy(), file_text);
+        Some(file_id)
+    }
+
+    /// Read the file at `physical_path`, which may be in a zip archive, and
+    /// add it to the database, skipping it if it has already been added
+    ///
+    /// The file is tracked by [`PhysicalPath::path`].
+    ///
+    /// # Returns
+    /// - `Some(file_id)` if the file has been (or was already) added
+    /// - `None` if the file could not be read
+    ///
+    /// [`PhysicalPath::path`]: enum.PhysicalPath.html#method.path
+    fn load_physical_file(&mut self, physical_path: PhysicalPath) -> Option<FileId> {
+        let path = physical_path.path();
+
+        if let Some(file_id) = self.file_id_of_disk_path(path.clone()) {
+            return Some(file_id);
+        }
+
+        let file_text = match physical_path.read_to_string() {
+            Ok(text) => text,
+            Err(e) => {
+                log::error!("Failed to read `{}`: {}", path.display(), e);
+                return None;
+            },
+        };
+
+        let file_id = self.add_text_file(path.to_string_lossy(), file_text);
+        Some(file_id)
+    }
+}
+
+/// Let salsa know that the calling query reads from the file system, which
+/// salsa can not track, so that the query is recomputed in every revision
+///
+/// Without this, a file that did not exist when it was first looked for
+/// would be considered missing until restart, even after it is created.
+fn report_file_system_read(db: &impl SdkCtx) {
+    db.salsa_runtime().report_untracked_read();
 }
 
 fn all_games(
     db: &impl SdkCtx,
 ) -> Option<Vec<Game>> {
+    report_file_system_read(db);
+
     let root = db.workspace_root()?;
     let rel_mods_dir = root.join("mods/");
     let read_dir = fs::read_dir(&rel_mods_dir).ok()?;
@@ -74,9 +400,552 @@ fn all_games(
     games.into()
 }
 
+fn game_dir(
+    db: &impl SdkCtx,
+    game_id: GameId,
+) -> Option<PathBuf> {
+    report_file_system_read(db);
+
+    let root = db.workspace_root()?;
+
+    let candidates = vec![
+        root.join("mods").join(&*game_id),
+        root.join("engine").join("mods").join(&*game_id),
+    ];
+
+    candidates.into_iter().find(|path| path.is_dir())
+}
+
+fn manifest_path(
+    db: &impl SdkCtx,
+    game_id: GameId,
+) -> Option<PathBuf> {
+    let dir = db.game_dir(game_id)?;
+    dir.join("mod.yaml").into()
+}
+
+fn engine_dir(
+    db: &impl SdkCtx,
+) -> Option<PathBuf> {
+    let root = db.workspace_root()?;
+    let sdk_engine_dir = root.join("engine");
+
+    if sdk_engine_dir.is_dir() {
+        Some(sdk_engine_dir)
+    } else {
+        Some(root)
+    }
+}
+
+fn mounted_packages(
+    db: &impl SdkCtx,
+    game_id: GameId,
+) -> Option<Vec<MountedPackage>> {
+    report_file_system_read(db);
+
+    let manifest = db.game_manifest(game_id.clone())?;
+    let mut packages: Vec<MountedPackage> = vec![];
+
+    for entry in manifest.packages {
+        let (is_optional, path) = split_optional(&entry.key.text);
+        let explicit_name = entry.value.map(|value| value.text);
+
+        let name = match parse_manifest_path(path) {
+            ManifestPath::GameDir(package_game_id) => explicit_name.or_else(|| Some(package_game_id.to_owned())),
+            _ => explicit_name,
+        };
+
+        let package_path = resolve_package_path(db, &packages, path);
+
+        match package_path.and_then(Package::open) {
+            Some(package) => packages.push(MountedPackage {
+                name,
+                package,
+            }),
+            None if is_optional => {},
+            None => log::warn!("Unable to mount package `{}` for game `{}`", entry.key.text, &*game_id),
+        }
+    }
+
+    packages.into()
+}
+
+/// Resolve `path`, an entry (without its `~` prefix) in a manifest's
+/// `Packages` or `MapFolders` section, to a path on disk
+///
+/// `packages` are those that have already been mounted, in mount order.
+fn resolve_package_path(
+    db: &impl SdkCtx,
+    packages: &[MountedPackage],
+    path: &str,
+) -> Option<PathBuf> {
+    match parse_manifest_path(path) {
+        ManifestPath::GameDir(package_game_id) => db.game_dir(GameId::from(package_game_id.to_owned())),
+        ManifestPath::Game { game_id: package_game_id, path: rel_path } => {
+            // Typically a path inside of an already-mounted game directory
+            match resolve_in_packages(packages, path) {
+                Some(PhysicalPath::Disk(package_path)) => Some(package_path),
+                _ => db.game_dir(GameId::from(package_game_id.to_owned()))
+                    .map(|dir| dir.join(rel_path)),
+            }
+        },
+        ManifestPath::Mount { mount, path: rel_path } => mount_dir(db, mount).map(|dir| dir.join(rel_path)),
+        ManifestPath::Engine(rel_path) => db.engine_dir().map(|dir| dir.join(rel_path)),
+    }
+}
+
+/// The directory that `^mount|` paths are relative to, see [`KNOWN_MOUNTS`]
+///
+/// [`KNOWN_MOUNTS`]: ../constant.KNOWN_MOUNTS.html
+fn mount_dir(
+    db: &impl SdkCtx,
+    mount: &str,
+) -> Option<PathBuf> {
+    match mount {
+        "SupportDir" => default_support_dir(),
+        "EngineDir" | "GameDir" => db.engine_dir(),
+        "BinDir" => db.engine_dir().map(|dir| dir.join("bin")),
+        _ => None,
+    }
+}
+
+fn resolve_logical_path(
+    db: &impl SdkCtx,
+    game_id: GameId,
+    logical_path: String,
+) -> Option<PhysicalPath> {
+    report_file_system_read(db);
+
+    let packages = db.mounted_packages(game_id.clone()).unwrap_or_default();
+
+    if let Some(physical_path) = resolve_in_packages(&packages, &logical_path) {
+        return Some(physical_path);
+    }
+
+    let (opt_package_game_id, rel_path) = split_package_path(&logical_path);
+
+    let package_game_id = match opt_package_game_id {
+        Some(id) => GameId::from(id.to_owned()),
+        None => game_id,
+    };
+
+    let dir = db.game_dir(package_game_id)?;
+    Package::Folder(dir).find(rel_path)
+}
+
+fn file_id_of_disk_path(
+    db: &impl SdkCtx,
+    path: PathBuf,
+) -> Option<FileId> {
+    if let Some(file_id) = db.file_id_of_file_path(path.to_string_lossy().into_owned()) {
+        return Some(file_id);
+    }
+
+    let url = Url::from_file_path(&path).ok()?;
+    db.file_id_of_file_path(url.to_string())
+}
+
+fn file_id_of_url(
+    db: &impl SdkCtx,
+    url: Url,
+) -> Option<FileId> {
+    if let Some(file_id) = db.file_id_of_file_path(url.to_string()) {
+        return Some(file_id);
+    }
+
+    let path = url.to_file_path().ok()?;
+    db.file_id_of_disk_path(path)
+}
+
+fn file_url(
+    db: &impl SdkCtx,
+    file_id: FileId,
+) -> Option<Url> {
+    let file_path = db.file_path(file_id)?;
+
+    // Files opened by a language client are tracked by URL, but those
+    // loaded from disk are tracked by path.
+    match Url::parse(&file_path) {
+        Ok(url) => Some(url),
+        _ => Url::from_file_path(&file_path).ok(),
+    }
+}
+
+fn game_manifest(
+    db: &impl SdkCtx,
+    game_id: GameId,
+) -> Option<Manifest> {
+    let manifest_path = db.manifest_path(game_id)?;
+    let file_id = db.file_id_of_disk_path(manifest_path)?;
+    let rule_nodes = db.rule_nodes_in_file(file_id)?;
+    Manifest::from_rule_nodes(&rule_nodes).into()
+}
+
+fn manifest_diagnostics(
+    db: &impl SdkCtx,
+    game_id: GameId,
+) -> Option<Vec<Diagnostic>> {
+    report_file_system_read(db);
+
+    let manifest = db.game_manifest(game_id.clone())?;
+    let mut diagnostics = vec![];
+
+    // `Translations` are not a `ManifestSection` since they are never loaded,
+    // but their files must exist all the same
+    let file_entries = ManifestSection::ALL.iter()
+        .flat_map(|section| db.manifest_section_entries(game_id.clone(), *section).unwrap_or_default())
+        .chain(manifest.translations.iter().cloned());
+
+    for entry in file_entries {
+        diagnostics.extend(validate_manifest_file_entry(db, &game_id, &entry));
+    }
+
+    let package_entries = manifest.packages.iter()
+        .chain(manifest.map_folders.iter())
+        .map(|entry| &entry.key);
+
+    for entry in package_entries {
+        diagnostics.extend(validate_manifest_package_entry(db, entry, true));
+    }
+
+    // Assemblies are build outputs, so only their package is checked
+    for entry in manifest.assemblies.iter() {
+        diagnostics.extend(validate_manifest_package_entry(db, entry, false));
+    }
+
+    for entry in manifest.supports_maps_from.iter() {
+        if db.game_dir(GameId::from(entry.text.clone())).is_none() {
+            diagnostics.push(unknown_game_diagnostic(entry, &entry.text));
+        }
+    }
+
+    diagnostics.into()
+}
+
+/// Check that `entry`, a file listed in a section such as `Rules`, exists
+/// in one of the packages mounted by `game_id`
+///
+/// Entries without a `game-id|` prefix may also be relative to `game_id`'s
+/// directory, see [`SdkCtx::resolve_logical_path`].
+///
+/// [`SdkCtx::resolve_logical_path`]: trait.SdkCtx.html#tymethod.resolve_logical_path
+fn validate_manifest_file_entry(
+    db: &impl SdkCtx,
+    game_id: &GameId,
+    entry: &ManifestValue,
+) -> Option<Diagnostic> {
+    if db.resolve_logical_path(game_id.clone(), entry.text.clone()).is_some() {
+        return None;
+    }
+
+    let (opt_package_game_id, rel_path) = split_package_path(&entry.text);
+
+    let package_game_id = match opt_package_game_id {
+        Some(id) => GameId::from(id.to_owned()),
+        None => game_id.clone(),
+    };
+
+    let is_mounted_package = opt_package_game_id.map(|name| {
+        db.mounted_packages(game_id.clone())
+            .unwrap_or_default()
+            .iter()
+            .any(|mounted| mounted.name.as_ref().map(String::as_str) == Some(name))
+    }).unwrap_or(false);
+
+    match db.game_dir(package_game_id.clone()) {
+        Some(dir) => Some(missing_path_diagnostic(entry, &dir.join(rel_path))),
+        _ if is_mounted_package => Some(missing_path_diagnostic(entry, Path::new(rel_path))),
+        _ => Some(unknown_game_diagnostic(entry, &package_game_id)),
+    }
+}
+
+/// Check that `entry`, a path listed in a section such as `Packages`,
+/// refers to a known game or mount and, if `check_exists` is `true` and the
+/// entry is not optional (`~`), that it exists
+fn validate_manifest_package_entry(
+    db: &impl SdkCtx,
+    entry: &ManifestValue,
+    check_exists: bool,
+) -> Option<Diagnostic> {
+    let (is_optional, path) = split_optional(&entry.text);
+
+    let candidate_paths = match parse_manifest_path(path) {
+        ManifestPath::GameDir(package_game_id) => match db.game_dir(GameId::from(package_game_id.to_owned())) {
+            Some(dir) => vec![dir],
+            _ => return Some(unknown_game_diagnostic(entry, package_game_id)),
+        },
+        ManifestPath::Game { game_id: package_game_id, path } => match db.game_dir(GameId::from(package_game_id.to_owned())) {
+            Some(dir) => vec![dir.join(path)],
+            _ => return Some(unknown_game_diagnostic(entry, package_game_id)),
+        },
+        ManifestPath::Mount { mount, .. } => {
+            if KNOWN_MOUNTS.contains(&mount) {
+                return None;
+            }
+
+            let expected_mounts: Vec<_> = KNOWN_MOUNTS.iter()
+                .map(|known_mount| format!("`^{}|`", known_mount))
+                .collect();
+
+            return Some(
+                Diagnostic::new_error(format!("Unknown mount `^{}|`", mount))
+                    .with_code("S:E0012")
+                    .with_label(Label::new_primary(entry.span))
+                    .with_help(format!("Expected one of: {}", expected_mounts.join(", ")))
+            );
+        },
+        ManifestPath::Engine(path) => vec![db.engine_dir()?.join(path)],
+    };
+
+    if !check_exists || is_optional || candidate_paths.iter().any(|path| path.exists()) {
+        return None;
+    }
+
+    Some(missing_path_diagnostic(entry, &candidate_paths[0]))
+}
+
+fn unknown_game_diagnostic(entry: &ManifestValue, game_id: &str) -> Diagnostic {
+    Diagnostic::new_error(format!("Unknown game `{}`", game_id))
+        .with_code("S:E0010")
+        .with_label(Label::new_primary(entry.span))
+        .with_help("Games are found in the `mods/` (and `engine/mods/`) directory of the workspace")
+}
+
+fn missing_path_diagnostic(entry: &ManifestValue, path: &Path) -> Diagnostic {
+    Diagnostic::new_error(format!("`{}` does not exist", entry.text))
+        .with_code("S:E0011")
+        .with_label(Label::new_primary(entry.span))
+        .with_help(format!("Expected to find `{}`", path.display()))
+}
+
+fn manifest_section_entries(
+    db: &impl SdkCtx,
+    game_id: GameId,
+    section: ManifestSection,
+) -> Option<Vec<ManifestValue>> {
+    let manifest_path = db.manifest_path(game_id)?;
+    let file_id = db.file_id_of_disk_path(manifest_path)?;
+    let rule_nodes = db.rule_nodes_in_file(file_id)?;
+    let section_node = rule_nodes.iter().find(|node| node.key == section.key())?;
+
+    let entries: Vec<_> = section_node.children.iter()
+        .filter(|entry_node| !entry_node.key.is_empty())
+        .map(|entry_node| ManifestValue {
+            text: entry_node.key.clone(),
+            span: entry_node.key_span,
+        })
+        .collect();
+
+    entries.into()
+}
+
+fn resolved_physical_paths_for_game_section(
+    db: &impl SdkCtx,
+    game_id: GameId,
+    section: ManifestSection,
+) -> Option<Vec<PhysicalPath>> {
+    let entries = db.manifest_section_entries(game_id.clone(), section)?;
+
+    let physical_paths: Vec<_> = entries.into_iter()
+        .filter_map(|entry| match db.resolve_logical_path(game_id.clone(), entry.text.clone()) {
+            Some(physical_path) => Some(physical_path),
+            _ => {
+                log::warn!("Unable to resolve manifest entry `{}` for game `{}`", entry.text, &*game_id);
+                None
+            },
+        })
+        .collect();
+
+    physical_paths.into()
+}
+
+fn resolved_file_paths_for_game_section(
+    db: &impl SdkCtx,
+    game_id: GameId,
+    section: ManifestSection,
+) -> Option<Vec<PathBuf>> {
+    let physical_paths = db.resolved_physical_paths_for_game_section(game_id, section)?;
+
+    let paths: Vec<_> = physical_paths.iter()
+        .map(PhysicalPath::path)
+        .collect();
+
+    paths.into()
+}
+
 fn resolved_rule_file_paths_for_game(
-    _db: &impl SdkCtx,
-    _game_id: GameId,
+    db: &impl SdkCtx,
+    game_id: GameId,
 ) -> Option<Vec<PathBuf>> {
-    unimplemented!()
+    db.resolved_file_paths_for_game_section(game_id, ManifestSection::Rules)
+}
+
+fn manifest_listed_files(
+    db: &impl SdkCtx,
+) -> Option<Vec<(FileId, GameId, ManifestSection)>> {
+    let games = db.all_games()?;
+    let mut listed = vec![];
+
+    for game in games {
+        for section in ManifestSection::ALL.iter().cloned() {
+            let paths = db.resolved_file_paths_for_game_section(game.id.clone(), section)
+                .unwrap_or_default();
+
+            for path in paths {
+                if let Some(file_id) = db.file_id_of_disk_path(path) {
+                    listed.push((file_id, game.id.clone(), section));
+                }
+            }
+        }
+    }
+
+    listed.into()
+}
+
+fn game_ids_of_rule_file(
+    db: &impl SdkCtx,
+    file_id: FileId,
+) -> Option<Vec<GameId>> {
+    let games = db.all_games()?;
+
+    let game_ids: Vec<_> = games.into_iter()
+        .map(|game| game.id)
+        .filter(|game_id| {
+            db.resolved_rule_file_paths_for_game(game_id.clone())
+                .unwrap_or_default()
+                .into_iter()
+                .any(|path| db.file_id_of_disk_path(path) == Some(file_id))
+        })
+        .collect();
+
+    game_ids.into()
+}
+
+fn rule_nodes_in_file(
+    db: &impl SdkCtx,
+    file_id: FileId,
+) -> Option<Vec<RuleNode>> {
+    let file_text = db.file_text(file_id)?;
+    let tree = db.file_tree(file_id)?;
+    rule_nodes_from_tree(&tree, &file_text).into()
+}
+
+fn map_file_paths(
+    db: &impl SdkCtx,
+    game_id: GameId,
+) -> Option<Vec<PhysicalPath>> {
+    report_file_system_read(db);
+
+    let manifest = db.game_manifest(game_id.clone())?;
+    let packages = db.mounted_packages(game_id).unwrap_or_default();
+    let mut map_file_paths = vec![];
+
+    for entry in manifest.map_folders {
+        let (_, path) = split_optional(&entry.key.text);
+
+        let read_dir = match resolve_package_path(db, &packages, path).map(fs::read_dir) {
+            Some(Ok(read_dir)) => read_dir,
+            _ => continue,
+        };
+
+        let mut map_paths: Vec<_> = read_dir
+            .filter_map(|dir_entry| dir_entry.ok())
+            .map(|dir_entry| dir_entry.path())
+            .collect();
+
+        map_paths.sort();
+
+        map_file_paths.extend(
+            map_paths.into_iter()
+                .filter_map(Package::open)
+                .filter_map(|map_package| map_package.find(MAP_FILE_NAME))
+        );
+    }
+
+    map_file_paths.into()
+}
+
+fn game_ids_of_map_file(
+    db: &impl SdkCtx,
+    file_id: FileId,
+) -> Option<Vec<GameId>> {
+    let games = db.all_games()?;
+
+    let game_ids: Vec<_> = games.into_iter()
+        .map(|game| game.id)
+        .filter(|game_id| {
+            db.map_file_paths(game_id.clone())
+                .unwrap_or_default()
+                .into_iter()
+                .any(|physical_path| db.file_id_of_disk_path(physical_path.path()) == Some(file_id))
+        })
+        .collect();
+
+    game_ids.into()
+}
+
+fn map_in_file(
+    db: &impl SdkCtx,
+    file_id: FileId,
+) -> Option<Map> {
+    let rule_nodes = db.rule_nodes_in_file(file_id)?;
+    Map::from_rule_nodes(&rule_nodes).into()
+}
+
+fn map_rule_definitions(
+    db: &impl SdkCtx,
+    game_id: GameId,
+    file_id: FileId,
+) -> Option<Vec<RuleNode>> {
+    let mut definitions = db.game_rule_definitions(game_id)?;
+    let map = db.map_in_file(file_id)?;
+    merge_rule_nodes(&mut definitions, map.rules);
+    definitions.into()
+}
+
+fn resolved_map_actor(
+    db: &impl SdkCtx,
+    game_id: GameId,
+    file_id: FileId,
+    actor_type: String,
+) -> Option<ResolvedActor> {
+    let definitions = db.map_rule_definitions(game_id, file_id)?;
+    let definition_name = Map::definition_for_actor_type(&definitions, &actor_type)?.key.clone();
+    Resolver::new(&definitions).resolve(&definition_name)
+}
+
+fn game_rule_definitions(
+    db: &impl SdkCtx,
+    game_id: GameId,
+) -> Option<Vec<RuleNode>> {
+    let rule_file_paths = db.resolved_rule_file_paths_for_game(game_id.clone())?;
+    let mut definitions = vec![];
+
+    for rule_file_path in rule_file_paths {
+        let file_id = match db.file_id_of_disk_path(rule_file_path.clone()) {
+            Some(id) => id,
+            _ => {
+                log::warn!(
+                    "Rules file `{}` of game `{}` has not been loaded",
+                    rule_file_path.display(),
+                    &*game_id
+                );
+                continue;
+            },
+        };
+
+        let rule_nodes = db.rule_nodes_in_file(file_id).unwrap_or_default();
+        merge_rule_nodes(&mut definitions, rule_nodes);
+    }
+
+    definitions.into()
+}
+
+fn resolved_actor(
+    db: &impl SdkCtx,
+    game_id: GameId,
+    actor_name: String,
+) -> Option<ResolvedActor> {
+    let definitions = db.game_rule_definitions(game_id)?;
+    Resolver::new(&definitions).resolve(&actor_name)
 }
\ No newline at end of file
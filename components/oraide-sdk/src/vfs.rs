@@ -0,0 +1,383 @@
+// This file is part of oraide.  See <https://github.com/Phrohdoh/oraide>.
+// 
+// oraide is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License version 3
+// as published by the Free Software Foundation.
+// 
+// oraide is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+// 
+// You should have received a copy of the GNU Affero General Public License
+// along with oraide.  If not, see <https://www.gnu.org/licenses/>.
+
+//! A virtual file system that resolves the logical paths used by manifests
+//! (such as `common|rules/misc.yaml`) to files on disk, in the same way that
+//! OpenRA's `FileSystem` does
+//!
+//! Each game mounts the packages listed in its manifest's `Packages` section,
+//! in order.  A logical path with a `name|` prefix is looked up in the most
+//! recently mounted package with that name, otherwise every mounted package
+//! is searched, most recently mounted first.
+
+use std::{
+    fs::File,
+    io::{
+        self,
+        Read as _,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use crate::{
+    split_package_path,
+};
+
+/// The extensions of files that are mounted as zip archives
+const ZIP_EXTENSIONS: [&str; 3] = [
+    "oramod",
+    "oramap",
+    "zip",
+];
+
+/// A directory, or an archive, whose contents can be looked up by path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Package {
+    Folder(PathBuf),
+
+    Zip {
+        archive_path: PathBuf,
+
+        /// The names of the archive's entries, sorted
+        ///
+        /// These are read once, when the package is opened, so that looking
+        /// up a path does not reopen the archive.  Mounted packages are
+        /// computed by a query, so the names are cached along with them.
+        entry_names: Vec<String>,
+    },
+}
+
+impl Package {
+    /// Open the package at `path`, which must be either a directory or a
+    /// zip archive (such as an `.oramod` or `.oramap`)
+    ///
+    /// # Returns
+    /// `None` if `path` does not exist, is not a supported kind of package
+    /// or is an archive that cannot be read
+    pub fn open(path: PathBuf) -> Option<Self> {
+        if path.is_dir() {
+            return Some(Package::Folder(path));
+        }
+
+        let is_zip = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ZIP_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            .unwrap_or(false);
+
+        if !is_zip || !path.is_file() {
+            return None;
+        }
+
+        match read_zip_entry_names(&path) {
+            Ok(entry_names) => Some(Package::Zip {
+                archive_path: path,
+                entry_names,
+            }),
+            Err(e) => {
+                log::error!("Failed to read archive `{}`: {}", path.display(), e);
+                None
+            },
+        }
+    }
+
+    /// Find the file at `rel_path` in this package
+    pub fn find(&self, rel_path: &str) -> Option<PhysicalPath> {
+        match self {
+            Package::Folder(dir) => {
+                let path = dir.join(rel_path);
+
+                if path.exists() {
+                    Some(PhysicalPath::Disk(path))
+                } else {
+                    None
+                }
+            },
+            Package::Zip { archive_path, entry_names } => {
+                let entry_name = rel_path.replace('\\', "/");
+
+                if entry_names.binary_search(&entry_name).is_ok() {
+                    Some(PhysicalPath::Zipped {
+                        archive_path: archive_path.clone(),
+                        entry_name,
+                    })
+                } else {
+                    None
+                }
+            },
+        }
+    }
+}
+
+/// A [`Package`] that has been mounted by a game
+///
+/// [`Package`]: enum.Package.html
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MountedPackage {
+    /// The name that a logical path's `name|` prefix refers to this package
+    /// by, such as `common` for `./mods/common: common`
+    pub name: Option<String>,
+
+    pub package: Package,
+}
+
+/// Where the file that a logical path refers to can be read from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PhysicalPath {
+    Disk(PathBuf),
+
+    /// An entry in a zip archive, which is read without being extracted
+    Zipped {
+        archive_path: PathBuf,
+        entry_name: String,
+    },
+}
+
+impl PhysicalPath {
+    /// A path that uniquely identifies this file
+    ///
+    /// Zipped files are identified as if their archive were a directory,
+    /// for example `mods/ra.oramod/rules/misc.yaml`.  Note that this path
+    /// does not exist on disk, so neither does the `file://` URL that a
+    /// language client is given for it, and clients cannot open it.
+    pub fn path(&self) -> PathBuf {
+        match self {
+            PhysicalPath::Disk(path) => path.clone(),
+            PhysicalPath::Zipped { archive_path, entry_name } => archive_path.join(entry_name),
+        }
+    }
+
+    pub fn read_to_string(&self) -> io::Result<String> {
+        match self {
+            PhysicalPath::Disk(path) => std::fs::read_to_string(path),
+            PhysicalPath::Zipped { archive_path, entry_name } => {
+                let mut archive = open_zip(archive_path)?;
+                let mut zipped_file = archive.by_name(entry_name)?;
+
+                let mut text = String::new();
+                zipped_file.read_to_string(&mut text)?;
+                Ok(text)
+            },
+        }
+    }
+}
+
+fn open_zip(archive_path: &Path) -> io::Result<zip::ZipArchive<File>> {
+    let file = File::open(archive_path)?;
+    let archive = zip::ZipArchive::new(file)?;
+    Ok(archive)
+}
+
+fn read_zip_entry_names(archive_path: &Path) -> io::Result<Vec<String>> {
+    let mut archive = open_zip(archive_path)?;
+    let mut entry_names = Vec::with_capacity(archive.len());
+
+    for idx in 0..archive.len() {
+        entry_names.push(archive.by_index(idx)?.name().to_owned());
+    }
+
+    entry_names.sort();
+    Ok(entry_names)
+}
+
+/// Resolve `logical_path` against `packages`, which must be in mount order
+///
+/// # Returns
+/// `None` if no package (with the name of `logical_path`'s prefix, if it has
+/// one) contains the file
+pub fn resolve_in_packages(packages: &[MountedPackage], logical_path: &str) -> Option<PhysicalPath> {
+    match split_package_path(logical_path) {
+        (Some(name), rel_path) => packages.iter().rev()
+            .find(|mounted| mounted.name.as_ref().map(String::as_str) == Some(name))?
+            .package
+            .find(rel_path),
+        (None, rel_path) => packages.iter().rev()
+            .filter_map(|mounted| mounted.package.find(rel_path))
+            .next(),
+    }
+}
+
+/// The directory that OpenRA stores user data (such as downloaded content
+/// and user maps) in, which `^SupportDir|` paths are relative to
+pub fn default_support_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        let app_data = std::env::var_os("APPDATA")?;
+        return Some(PathBuf::from(app_data).join("OpenRA"));
+    }
+
+    let home = PathBuf::from(std::env::var_os("HOME")?);
+
+    if cfg!(target_os = "macos") {
+        Some(home.join("Library").join("Application Support").join("OpenRA"))
+    } else {
+        Some(home.join(".openra"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs::{
+            self,
+            File,
+        },
+        io::Write as _,
+        path::{
+            Path,
+            PathBuf,
+        },
+    };
+
+    use super::{
+        MountedPackage,
+        Package,
+        PhysicalPath,
+        resolve_in_packages,
+    };
+
+    fn crate_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+    }
+
+    /// Write a zip archive to `archive_path` that contains `entries`, each
+    /// of which is a name and text
+    fn write_zip(archive_path: &Path, entries: &[(&str, &str)]) {
+        let file = File::create(archive_path)
+            .expect(&format!("TEST LOGIC ERROR: failed to create `{}`", archive_path.display()));
+
+        let mut writer = zip::ZipWriter::new(file);
+
+        for (name, text) in entries {
+            writer.start_file(*name, zip::write::FileOptions::default())
+                .expect("TEST LOGIC ERROR: failed to start a zip entry");
+
+            writer.write_all(text.as_bytes())
+                .expect("TEST LOGIC ERROR: failed to write a zip entry");
+        }
+
+        writer.finish().expect("TEST LOGIC ERROR: failed to finish the zip archive");
+    }
+
+    fn folder_named(name: &str, dir: PathBuf) -> MountedPackage {
+        MountedPackage {
+            name: Some(name.to_owned()),
+            package: Package::Folder(dir),
+        }
+    }
+
+    #[test]
+    fn prefixed_path_is_resolved_in_named_package() {
+        // Arrange
+        let packages = vec![
+            folder_named("sdk", crate_dir()),
+            folder_named("src", crate_dir().join("src")),
+        ];
+
+        // Act
+        let actual = resolve_in_packages(&packages, "sdk|src/vfs.rs");
+
+        // Assert
+        assert_eq!(actual, Some(PhysicalPath::Disk(crate_dir().join("src/vfs.rs"))));
+    }
+
+    #[test]
+    fn prefixed_path_is_not_resolved_in_other_packages() {
+        // Arrange
+        let packages = vec![
+            folder_named("sdk", crate_dir()),
+        ];
+
+        // Act
+        let actual = resolve_in_packages(&packages, "ra|src/vfs.rs");
+
+        // Assert
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn unprefixed_path_is_resolved_in_most_recently_mounted_package() {
+        // Arrange
+        let packages = vec![
+            folder_named("sdk", crate_dir()),
+            folder_named("src", crate_dir().join("src")),
+        ];
+
+        // Act
+        let actual = resolve_in_packages(&packages, "lib.rs");
+
+        // Assert
+        assert_eq!(actual, Some(PhysicalPath::Disk(crate_dir().join("src").join("lib.rs"))));
+    }
+
+    #[test]
+    fn package_open_rejects_unsupported_files() {
+        // Act
+        let actual = Package::open(crate_dir().join("Cargo.toml"));
+
+        // Assert
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn zipped_path_is_identified_as_if_archive_were_a_directory() {
+        // Arrange
+        let physical_path = PhysicalPath::Zipped {
+            archive_path: PathBuf::from("mods/ra.oramod"),
+            entry_name: "rules/misc.yaml".into(),
+        };
+
+        // Act
+        let actual = physical_path.path();
+
+        // Assert
+        assert_eq!(actual, PathBuf::from("mods/ra.oramod").join("rules/misc.yaml"));
+    }
+
+    #[test]
+    fn path_is_resolved_in_and_read_from_zip_package() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("oraide-sdk-vfs-zip-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("TEST LOGIC ERROR: failed to create the temp dir");
+
+        let archive_path = dir.join("ra.oramod");
+        write_zip(&archive_path, &[
+            ("mod.yaml", "Rules:\n    ra|rules/infantry.yaml\n"),
+            ("rules/infantry.yaml", "E1:\n    Health:\n"),
+        ]);
+
+        let packages = vec![
+            MountedPackage {
+                name: Some("ra".into()),
+                package: Package::open(archive_path.clone()).expect("the archive should have been opened"),
+            },
+        ];
+
+        // Act
+        let physical_path = resolve_in_packages(&packages, "ra|rules/infantry.yaml");
+        let missing_path = resolve_in_packages(&packages, "rules/vehicles.yaml");
+        let text = physical_path.as_ref().map(|physical_path| physical_path.read_to_string().ok());
+
+        let _ = fs::remove_dir_all(&dir);
+
+        // Assert
+        assert_eq!(physical_path, Some(PhysicalPath::Zipped {
+            archive_path,
+            entry_name: "rules/infantry.yaml".into(),
+        }));
+        assert_eq!(missing_path, None);
+        assert_eq!(text, Some(Some("E1:\n    Health:\n".into())));
+    }
+}
\ No newline at end of file
@@ -134,6 +134,9 @@ class ClientWorkspace {
             ],
             diagnosticCollectionName: 'OpenRA IDE',
             synchronize: { configurationSection: 'oraide' },
+            initializationOptions: {
+                documentSymbolDepth: workspace.getConfiguration('oraide', this.dir.uri).get<number>('server.documentSymbolDepth'),
+            },
             workspaceFolder: this.dir,
             outputChannelName: "OpenRA IDE",
             revealOutputChannelOn: RevealOutputChannelOn.Info,